@@ -25,6 +25,95 @@ const registerHandler = () => {
 
         a.addEventListener("mouseover", () => fetchContent(a.href));
     }
+
+    registerSearch();
+};
+
+const element = (tag, props = {}, ...children) => {
+    const el = document.createElement(tag);
+
+    Object.assign(el, props);
+    el.append(...children);
+
+    return el;
+};
+
+const renderSearch = (results, data) => {
+    results.replaceChildren();
+
+    if (data.artifacts.length == 0 && data.files.length == 0 && data.entries.length == 0) {
+        results.append(element("p", { textContent: "No results." }));
+        return;
+    }
+
+    const list = element("ul");
+
+    for (const artifact of data.artifacts) {
+        const item = element(
+            "li",
+            {},
+            element("a", { href: artifact.link, textContent: `${artifact.group}:${artifact.artifact}` }),
+            element("span", { className: "versions", textContent: artifact.versions.slice(0, 5).join(", ") }),
+        );
+
+        if (artifact.description) {
+            item.append(element("div", { className: "description", textContent: artifact.description }));
+        }
+
+        list.append(item);
+    }
+
+    for (const file of data.files) {
+        list.append(element("li", {}, element("a", { href: file.path, textContent: file.path })));
+    }
+
+    for (const entry of data.entries) {
+        list.append(
+            element(
+                "li",
+                {},
+                element("span", { textContent: entry.class_name ?? entry.name }),
+                element("a", { className: "versions", href: entry.link, textContent: entry.jar }),
+            ),
+        );
+    }
+
+    results.append(list);
+
+    if (data.total > data.artifacts.length) {
+        results.append(element("p", { textContent: `Showing ${data.artifacts.length} of ${data.total} artifacts.` }));
+    }
+
+    if (data.total_entries > data.entries.length) {
+        results.append(element("p", { textContent: `Showing ${data.entries.length} of ${data.total_entries} JAR entries.` }));
+    }
+};
+
+const registerSearch = () => {
+    const form = document.querySelector("form.search");
+    const results = document.querySelector(".search-results");
+
+    if (!form || !results) return;
+
+    form.addEventListener("submit", async (ev) => {
+        ev.preventDefault();
+
+        const query = new URLSearchParams(new FormData(form));
+
+        if (!query.get("q")) {
+            results.replaceChildren();
+            return;
+        }
+
+        const resp = await fetch(`${form.action}?${query}`);
+
+        if (!resp.ok) {
+            results.replaceChildren(element("p", { textContent: await resp.text() }));
+            return;
+        }
+
+        renderSearch(results, await resp.json());
+    });
 };
 
 window.addEventListener("popstate", (_ev) => {
@@ -0,0 +1 @@
+DROP INDEX IF EXISTS files_parent_idx;
@@ -0,0 +1 @@
+CREATE INDEX IF NOT EXISTS files_parent_idx ON files (parent);
@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS pom_developers;
+DROP TABLE IF EXISTS pom_licenses;
+DROP TABLE IF EXISTS pom_properties;
+DROP TABLE IF EXISTS pom_dependencies;
+DROP TABLE IF EXISTS poms;
@@ -0,0 +1,69 @@
+CREATE TABLE IF NOT EXISTS poms (
+    id SERIAL NOT NULL PRIMARY KEY,
+    file INTEGER NOT NULL UNIQUE REFERENCES files(id) ON DELETE CASCADE,
+    group_id TEXT NOT NULL,
+    artifact_id TEXT NOT NULL,
+    version TEXT NOT NULL,
+    packaging TEXT NOT NULL,
+    name TEXT,
+    description TEXT,
+    url TEXT,
+    parent_group_id TEXT,
+    parent_artifact_id TEXT,
+    parent_version TEXT,
+    scm_url TEXT,
+    scm_connection TEXT,
+    scm_developer_connection TEXT,
+    scm_tag TEXT,
+    indexed TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS poms_coords_idx ON poms (group_id, artifact_id, version);
+
+CREATE TABLE IF NOT EXISTS pom_dependencies (
+    id SERIAL NOT NULL PRIMARY KEY,
+    pom INTEGER NOT NULL REFERENCES poms(id) ON DELETE CASCADE,
+    group_id TEXT NOT NULL,
+    artifact_id TEXT NOT NULL,
+    version TEXT,
+    scope TEXT,
+    kind TEXT NOT NULL,
+    classifier TEXT,
+    optional BOOL NOT NULL DEFAULT FALSE,
+    managed BOOL NOT NULL DEFAULT FALSE -- true if declared in <dependencyManagement>
+);
+
+CREATE INDEX IF NOT EXISTS pom_dependencies_pom_idx ON pom_dependencies (pom);
+CREATE INDEX IF NOT EXISTS pom_dependencies_target_idx ON pom_dependencies (group_id, artifact_id);
+
+CREATE TABLE IF NOT EXISTS pom_properties (
+    id SERIAL NOT NULL PRIMARY KEY,
+    pom INTEGER NOT NULL REFERENCES poms(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    value TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS pom_properties_pom_idx ON pom_properties (pom);
+
+CREATE TABLE IF NOT EXISTS pom_licenses (
+    id SERIAL NOT NULL PRIMARY KEY,
+    pom INTEGER NOT NULL REFERENCES poms(id) ON DELETE CASCADE,
+    name TEXT,
+    url TEXT,
+    distribution TEXT,
+    comments TEXT
+);
+
+CREATE INDEX IF NOT EXISTS pom_licenses_pom_idx ON pom_licenses (pom);
+
+CREATE TABLE IF NOT EXISTS pom_developers (
+    id SERIAL NOT NULL PRIMARY KEY,
+    pom INTEGER NOT NULL REFERENCES poms(id) ON DELETE CASCADE,
+    developer_id TEXT,
+    name TEXT,
+    email TEXT,
+    url TEXT,
+    organization TEXT
+);
+
+CREATE INDEX IF NOT EXISTS pom_developers_pom_idx ON pom_developers (pom);
@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS files_sha256_idx;
+DROP INDEX IF EXISTS files_sha1_idx;
+DROP INDEX IF EXISTS poms_text_idx;
+DROP INDEX IF EXISTS poms_artifact_id_trgm_idx;
+DROP INDEX IF EXISTS poms_group_id_trgm_idx;
+DROP EXTENSION IF EXISTS pg_trgm;
@@ -0,0 +1,10 @@
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX IF NOT EXISTS poms_group_id_trgm_idx ON poms USING GIN (group_id gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS poms_artifact_id_trgm_idx ON poms USING GIN (artifact_id gin_trgm_ops);
+
+CREATE INDEX IF NOT EXISTS poms_text_idx ON poms
+    USING GIN (to_tsvector('simple', COALESCE(name, '') || ' ' || COALESCE(description, '')));
+
+CREATE INDEX IF NOT EXISTS files_sha1_idx ON files (sha1);
+CREATE INDEX IF NOT EXISTS files_sha256_idx ON files (sha256);
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS jar_entries;
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS jar_entries (
+    id SERIAL NOT NULL PRIMARY KEY,
+    file INTEGER NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    class_name TEXT,
+    size BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS jar_entries_file_idx ON jar_entries (file);
+CREATE INDEX IF NOT EXISTS jar_entries_class_name_trgm_idx ON jar_entries USING GIN (class_name gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS jar_entries_name_trgm_idx ON jar_entries USING GIN (name gin_trgm_ops);
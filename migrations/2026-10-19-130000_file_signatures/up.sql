@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS file_signatures (
+    id SERIAL NOT NULL PRIMARY KEY,
+    file INTEGER NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+    signature INTEGER NOT NULL UNIQUE REFERENCES files(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL, -- "pgp" or "sigstore"
+    key_fingerprint TEXT,
+    verified BOOLEAN NOT NULL DEFAULT FALSE,
+    created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS file_signatures_file_idx ON file_signatures (file);
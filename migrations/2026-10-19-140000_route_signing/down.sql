@@ -0,0 +1 @@
+ALTER TABLE route_data DROP COLUMN IF EXISTS sign;
@@ -0,0 +1 @@
+ALTER TABLE route_data ADD COLUMN IF NOT EXISTS sign BOOLEAN NOT NULL DEFAULT FALSE; -- sign uploads with the server's key
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS staging_files;
+DROP TABLE IF EXISTS staging_sessions;
@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS staging_sessions (
+    id SERIAL NOT NULL PRIMARY KEY,
+    key TEXT NOT NULL UNIQUE,
+    token INTEGER NOT NULL REFERENCES tokens(id) ON DELETE CASCADE,
+    description TEXT,
+    created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS staging_files (
+    id SERIAL NOT NULL PRIMARY KEY,
+    session INTEGER NOT NULL REFERENCES staging_sessions(id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    size BIGINT NOT NULL,
+    uploaded TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    md5 TEXT NOT NULL,
+    sha1 TEXT NOT NULL,
+    sha256 TEXT NOT NULL,
+    sha512 TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    UNIQUE (session, path)
+);
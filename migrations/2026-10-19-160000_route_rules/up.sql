@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS route_rules (
+    id SERIAL NOT NULL PRIMARY KEY,
+    path TEXT NOT NULL UNIQUE,
+    require_pom BOOLEAN NOT NULL DEFAULT FALSE,
+    version_pattern TEXT, -- a regex every version must fully match
+    required_classifiers TEXT, -- comma-separated, like "sources,javadoc"
+    max_size BIGINT, -- in bytes
+    forbidden_extensions TEXT, -- comma-separated, like "exe,dll"
+    created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
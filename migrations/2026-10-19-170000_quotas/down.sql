@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS token_quotas;
+DROP TABLE IF EXISTS route_quotas;
+DROP INDEX IF EXISTS files_uploader_idx;
+ALTER TABLE files DROP COLUMN IF EXISTS uploader;
@@ -0,0 +1,19 @@
+ALTER TABLE files ADD COLUMN IF NOT EXISTS uploader INTEGER REFERENCES tokens(id) ON DELETE SET NULL; -- the token that uploaded it, if known
+CREATE INDEX IF NOT EXISTS files_uploader_idx ON files (uploader);
+
+CREATE TABLE IF NOT EXISTS route_quotas (
+    id SERIAL NOT NULL PRIMARY KEY,
+    path TEXT NOT NULL UNIQUE,
+    max_storage BIGINT, -- total bytes under the route
+    max_file_size BIGINT, -- in bytes
+    created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS token_quotas (
+    id SERIAL NOT NULL PRIMARY KEY,
+    token INTEGER NOT NULL UNIQUE REFERENCES tokens(id) ON DELETE CASCADE,
+    max_storage BIGINT, -- total bytes of files uploaded with the token
+    max_file_size BIGINT, -- in bytes
+    uploads_per_minute INTEGER,
+    created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS token_certificates (
+    id SERIAL NOT NULL PRIMARY KEY,
+    token INTEGER NOT NULL REFERENCES tokens(id) ON DELETE CASCADE,
+    fingerprint TEXT NOT NULL UNIQUE, -- hex SHA-256 of the DER certificate
+    added TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
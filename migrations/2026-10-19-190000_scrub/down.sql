@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS scrub_problems;
+DROP TABLE IF EXISTS scrub_runs;
+ALTER TABLE files DROP COLUMN IF EXISTS quarantined;
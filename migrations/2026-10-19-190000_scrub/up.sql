@@ -0,0 +1,18 @@
+ALTER TABLE files ADD COLUMN IF NOT EXISTS quarantined BOOLEAN NOT NULL DEFAULT FALSE; -- failed an integrity check, so it isn't served
+
+CREATE TABLE IF NOT EXISTS scrub_runs (
+    id SERIAL NOT NULL PRIMARY KEY,
+    started TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    finished TIMESTAMP,
+    checked INTEGER NOT NULL DEFAULT 0,
+    problems INTEGER NOT NULL DEFAULT 0,
+    error TEXT -- set if the run itself failed
+);
+
+CREATE TABLE IF NOT EXISTS scrub_problems (
+    id SERIAL NOT NULL PRIMARY KEY,
+    file INTEGER NOT NULL UNIQUE REFERENCES files(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL, -- missing, size or hash
+    detail TEXT NOT NULL,
+    found TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS import_jobs (
+    id SERIAL NOT NULL PRIMARY KEY,
+    source TEXT NOT NULL, -- a directory or a URL
+    prefix TEXT NOT NULL, -- where imported files go
+    started TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    finished TIMESTAMP,
+    found INTEGER NOT NULL DEFAULT 0,
+    imported INTEGER NOT NULL DEFAULT 0,
+    skipped INTEGER NOT NULL DEFAULT 0, -- already here with the same content
+    failed INTEGER NOT NULL DEFAULT 0,
+    error TEXT -- set if the job itself failed
+);
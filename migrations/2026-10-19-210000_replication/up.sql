@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS replication_outbox (
+    id SERIAL NOT NULL PRIMARY KEY,
+    target TEXT NOT NULL, -- the name of a configured replica
+    event TEXT NOT NULL, -- 'upload' or 'delete'
+    path TEXT NOT NULL,
+    created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_error TEXT
+);
+
+CREATE INDEX IF NOT EXISTS replication_outbox_due ON replication_outbox (target, next_attempt);
@@ -0,0 +1,8 @@
+-- Files that couldn't be indexed, so the startup backfill doesn't keep downloading them.
+CREATE TABLE IF NOT EXISTS index_failures (
+    file INTEGER NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL, -- 'pom' or 'jar'
+    error TEXT NOT NULL,
+    failed TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (file, kind)
+);
@@ -0,0 +1 @@
+ALTER TABLE route_quotas ADD COLUMN IF NOT EXISTS max_file_size BIGINT; -- in bytes
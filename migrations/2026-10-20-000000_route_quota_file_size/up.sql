@@ -0,0 +1,6 @@
+-- Per-file limits live in route_rules.max_size now. Keep whichever limit was lower.
+INSERT INTO route_rules (path, max_size)
+SELECT path, max_file_size FROM route_quotas WHERE max_file_size IS NOT NULL
+ON CONFLICT (path) DO UPDATE SET max_size = LEAST(route_rules.max_size, EXCLUDED.max_size);
+
+ALTER TABLE route_quotas DROP COLUMN IF EXISTS max_file_size;
@@ -0,0 +1,7 @@
+-- When a file was written here, which imports don't backdate like they do `uploaded`.
+ALTER TABLE files ADD COLUMN IF NOT EXISTS stored TIMESTAMP;
+UPDATE files SET stored = uploaded WHERE stored IS NULL;
+ALTER TABLE files ALTER COLUMN stored SET NOT NULL;
+ALTER TABLE files ALTER COLUMN stored SET DEFAULT CURRENT_TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS files_stored_idx ON files (stored);
@@ -1,14 +1,22 @@
-use crate::{cx::RouteContext, tokens::models::MavenToken};
-use anyhow::{Result, anyhow};
+use crate::{cx::RouteContext, err::MavenError, tokens::models::MavenToken};
+use anyhow::Result;
 use axum::http::{HeaderName, HeaderValue, header::AUTHORIZATION};
 use axum_extra::headers::{
     Authorization, Error, Header,
     authorization::{Basic, Credentials},
 };
 
+/// The scheme client certificates are passed to handlers with. This never comes from
+/// clients: it's stripped from incoming requests, and only set once TLS has verified a
+/// certificate.
+pub const CERTIFICATE_SCHEME: &str = "Certificate";
+
 #[derive(Clone, PartialEq, Debug)]
 pub enum AnyAuth {
     Basic(Authorization<Basic>),
+
+    /// A verified TLS client certificate, by fingerprint.
+    Certificate(String),
     None,
 }
 
@@ -16,6 +24,9 @@ impl AnyAuth {
     pub fn encode(&self) -> HeaderValue {
         match self {
             Self::Basic(basic) => basic.0.encode(),
+            Self::Certificate(fingerprint) => {
+                HeaderValue::from_str(&format!("{CERTIFICATE_SCHEME} {fingerprint}")).unwrap()
+            }
             Self::None => unreachable!(),
         }
     }
@@ -23,6 +34,7 @@ impl AnyAuth {
     pub fn scheme(&self) -> &'static str {
         match self {
             Self::Basic(_) => Basic::SCHEME,
+            Self::Certificate(_) => CERTIFICATE_SCHEME,
             Self::None => unreachable!(),
         }
     }
@@ -30,7 +42,8 @@ impl AnyAuth {
     pub async fn get_token(&self, cx: &RouteContext) -> Result<MavenToken> {
         match self {
             Self::Basic(basic) => cx.get_token(basic.username(), basic.password()).await,
-            Self::None => Err(anyhow!("A token is required!")),
+            Self::Certificate(fingerprint) => cx.get_token_by_certificate(fingerprint).await,
+            Self::None => Err(MavenError::Unauthorized("A token is required!".into()).into()),
         }
     }
 }
@@ -53,6 +66,13 @@ impl Header for AnyAuth {
                     && slice[..Basic::SCHEME.len()].eq_ignore_ascii_case(Basic::SCHEME.as_bytes())
                 {
                     Basic::decode(val).map(Authorization).map(AnyAuth::Basic)
+                } else if let Some(fingerprint) = val
+                    .to_str()
+                    .ok()
+                    .and_then(|it| it.strip_prefix(CERTIFICATE_SCHEME))
+                    .and_then(|it| it.strip_prefix(' '))
+                {
+                    Some(Self::Certificate(fingerprint.trim().into()))
                 } else {
                     Some(Self::None)
                 }
@@ -1,6 +1,17 @@
-use crate::{run::run, s3::S3Config, tokens::hash::set_secret};
-use anyhow::Result;
-use clap::Parser;
+use crate::{
+    commands::{Command, open},
+    config::{Config, Seeds},
+    https::TlsOptions,
+    ratelimit::AuthLimits,
+    replication::target::ReplicationTarget,
+    run::{ServeOptions, run},
+    s3::S3Config,
+    scrub::cx::ScrubOptions,
+    tokens::hash::set_secret,
+};
+use anyhow::{Result, bail};
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, parser::ValueSource};
+use std::{path::PathBuf, time::Duration};
 use tracing::level_filters::LevelFilter;
 use tracing_subscriber::{
     EnvFilter, Layer, fmt, layer::SubscriberExt, registry, util::SubscriberInitExt,
@@ -10,6 +21,10 @@ use tracing_subscriber::{
 #[derive(Debug, Parser)]
 #[command(name = "SuperMaven", version, about, author, long_about = None)]
 pub struct Cli {
+    /// A TOML config file. Flags and env vars override anything set in it.
+    #[arg(short, long, env = "MAVEN_CONFIG")]
+    pub config: Option<PathBuf>,
+
     /// The address to host the server on.
     #[arg(short = 'H', long, env = "MAVEN_HOST", default_value = "0.0.0.0")]
     pub host: String,
@@ -54,14 +69,159 @@ pub struct Cli {
     /// The master key.
     #[arg(short = 'M', long, env = "MASTER_KEY")]
     pub master_key: Option<String>,
+
+    /// A public key file (or a directory of them) to verify uploaded `.asc` signatures
+    /// against. If set, uploads with bad signatures are rejected.
+    #[arg(long, env = "TRUSTED_KEYS")]
+    pub trusted_keys: Option<PathBuf>,
+
+    /// A secret key file to sign uploads with, on routes that are set up for it.
+    #[arg(long, env = "SIGNING_KEY")]
+    pub signing_key: Option<PathBuf>,
+
+    /// The password for the signing key, if it has one.
+    #[arg(long, env = "SIGNING_KEY_PASSWORD")]
+    pub signing_key_password: Option<String>,
+
+    /// How many authenticated requests each client IP can make per minute.
+    #[arg(long, env = "AUTH_RATE_LIMIT", default_value_t = 6000)]
+    pub auth_rate_limit: u32,
+
+    /// How many failed logins in a row a client IP (or a token, from one IP) gets before
+    /// it's locked out.
+    #[arg(long, env = "AUTH_LOCKOUT_THRESHOLD", default_value_t = 5)]
+    pub auth_lockout_threshold: u32,
+
+    /// How long the first lockout lasts, in seconds. Every failure after that doubles it.
+    #[arg(long, env = "AUTH_LOCKOUT_SECONDS", default_value_t = 30)]
+    pub auth_lockout_seconds: u64,
+
+    /// The longest a lockout can last, in seconds.
+    #[arg(long, env = "AUTH_LOCKOUT_MAX_SECONDS", default_value_t = 3600)]
+    pub auth_lockout_max_seconds: u64,
+
+    /// Take client IPs from `X-Forwarded-For`. Only turn this on behind a reverse proxy
+    /// that sets it, or clients can pick their own IP.
+    #[arg(long, env = "TRUST_FORWARDED_FOR")]
+    pub trust_forwarded_for: bool,
+
+    /// A PEM certificate chain to serve HTTPS with. Needs `--tls-key` too.
+    #[arg(long, env = "TLS_CERT")]
+    pub tls_cert: Option<PathBuf>,
+
+    /// The PEM private key for `--tls-cert`.
+    #[arg(long, env = "TLS_KEY")]
+    pub tls_key: Option<PathBuf>,
+
+    /// PEM CA certificates to accept client certificates from. Client certificates can
+    /// then be mapped to tokens, instead of sending token values.
+    #[arg(long, env = "TLS_CLIENT_CA")]
+    pub tls_client_ca: Option<PathBuf>,
+
+    /// A port to listen for plain HTTP on, redirecting everything to HTTPS.
+    #[arg(long, env = "TLS_REDIRECT_PORT")]
+    pub tls_redirect_port: Option<u16>,
+
+    /// How long to wait for in-flight uploads to finish when shutting down, in seconds.
+    #[arg(long, env = "DRAIN_TIMEOUT", default_value_t = 30)]
+    pub drain_timeout: u64,
+
+    /// How often to read everything back from storage and check its hashes, in hours.
+    /// 0 turns this off.
+    #[arg(long, env = "SCRUB_INTERVAL_HOURS", default_value_t = 0)]
+    pub scrub_interval_hours: u64,
+
+    /// Stop serving files that fail a scheduled scrub.
+    #[arg(long, env = "SCRUB_QUARANTINE")]
+    pub scrub_quarantine: bool,
+
+    /// Routes and tokens from the config file, to set up when the server starts.
+    #[arg(skip)]
+    pub seeds: Seeds,
+
+    /// Where to replicate to, from the config file.
+    #[arg(skip)]
+    pub replication: Vec<ReplicationTarget>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Fill in fields from the config file, unless they were given as a flag or env var.
+macro_rules! merge {
+    ($cli: ident, $matches: ident, { $($field: ident = $value: expr),* $(,)? }) => {
+        $(
+            if let Some(value) = $value {
+                if !matches!(
+                    $matches.value_source(stringify!($field)),
+                    Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+                ) {
+                    $cli.$field = value.into();
+                }
+            }
+        )*
+    };
 }
 
 impl Cli {
-    pub fn read() -> Self {
+    pub fn read() -> Result<Self> {
         #[cfg(not(miri))]
         let _ = dotenvy::dotenv();
 
-        Self::parse()
+        let matches = Self::command().get_matches();
+        let mut cli = Self::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+
+        if let Some(path) = &cli.config {
+            let config = Config::load(path)?;
+
+            cli.merge(config, &matches);
+        }
+
+        Ok(cli)
+    }
+
+    fn merge(&mut self, config: Config, matches: &ArgMatches) {
+        self.seeds = config.seeds();
+        self.replication = config.replication.clone();
+
+        let Config {
+            server,
+            database,
+            storage,
+            auth,
+            signing,
+            tls,
+            scrub,
+            ..
+        } = config;
+
+        merge!(self, matches, {
+            host = server.host,
+            port = server.port,
+            drain_timeout = server.drain_timeout,
+            database_url = database.url,
+            s3_url = storage.url,
+            s3_region = storage.region,
+            s3_bucket = storage.bucket,
+            s3_access_key_id = storage.access_key_id,
+            s3_access_key_secret = storage.access_key_secret,
+            secret = auth.secret,
+            master_key = auth.master_key,
+            auth_rate_limit = auth.rate_limit,
+            auth_lockout_threshold = auth.lockout_threshold,
+            auth_lockout_seconds = auth.lockout_seconds,
+            auth_lockout_max_seconds = auth.lockout_max_seconds,
+            trust_forwarded_for = auth.trust_forwarded_for,
+            trusted_keys = signing.trusted_keys,
+            signing_key = signing.key,
+            signing_key_password = signing.key_password,
+            tls_cert = tls.cert,
+            tls_key = tls.key,
+            tls_client_ca = tls.client_ca,
+            tls_redirect_port = tls.redirect_port,
+            scrub_interval_hours = scrub.interval_hours,
+            scrub_quarantine = scrub.quarantine,
+        });
     }
 
     pub async fn run(self) -> Result<()> {
@@ -82,19 +242,61 @@ impl Cli {
         registry().with(fmt.with_filter(env)).init();
         set_secret(self.secret);
 
-        run(
-            self.host,
-            self.port,
-            self.database_url,
-            self.master_key,
-            S3Config {
-                region: self.s3_region,
-                bucket: self.s3_bucket,
-                access_key_id: self.s3_access_key_id,
-                access_key_secret: self.s3_access_key_secret,
-                url: self.s3_url,
+        let s3 = S3Config {
+            region: self.s3_region,
+            bucket: self.s3_bucket,
+            access_key_id: self.s3_access_key_id,
+            access_key_secret: self.s3_access_key_secret,
+            url: self.s3_url,
+        };
+
+        match self.command {
+            None | Some(Command::Serve) => {}
+
+            Some(command) => {
+                let cx = open(self.database_url, self.master_key, s3, self.replication).await?;
+
+                return command.run(&cx).await;
+            }
+        }
+
+        let tls = match (self.tls_cert, self.tls_key) {
+            (Some(cert), Some(key)) => Some(TlsOptions {
+                cert,
+                key,
+                client_ca: self.tls_client_ca,
+                redirect_port: self.tls_redirect_port,
+            }),
+
+            (None, None) => None,
+            _ => bail!("--tls-cert and --tls-key have to be used together!"),
+        };
+
+        run(ServeOptions {
+            host: self.host,
+            port: self.port,
+            database_url: self.database_url,
+            master_key: self.master_key,
+            trusted_keys: self.trusted_keys,
+            signing_key: self.signing_key,
+            signing_key_password: self.signing_key_password,
+            s3,
+            auth_limits: AuthLimits {
+                per_minute: self.auth_rate_limit,
+                lockout_threshold: self.auth_lockout_threshold,
+                lockout: Duration::from_secs(self.auth_lockout_seconds),
+                max_lockout: Duration::from_secs(self.auth_lockout_max_seconds),
+                trust_forwarded_for: self.trust_forwarded_for,
+            },
+            tls,
+            drain_timeout: Duration::from_secs(self.drain_timeout),
+            scrub: ScrubOptions {
+                every: Duration::from_secs(self.scrub_interval_hours * 3600),
+                quarantine: self.scrub_quarantine,
             },
-        )
+            seeds: self.seeds,
+            replicas: self.replication,
+        })
         .await
     }
 }
@@ -0,0 +1,439 @@
+//! Management commands that work on the database and storage directly, without going
+//! through a running server.
+
+use crate::{
+    cx::RouteContext,
+    db::{connect, migrate},
+    import::source::ImportSource,
+    ratelimit::AuthLimits,
+    replication::target::ReplicationTarget,
+    router::{
+        access::RouteAccess,
+        dash::{HumanTokenPath, RouteInfo},
+        models::RouteDataIn,
+    },
+    s3::S3Config,
+    seed::seed_db,
+    tokens::{models_in::MavenTokenIn, perms::MavenTokenPermissions},
+};
+use anyhow::{Result, anyhow, bail};
+use chrono::{DateTime, Utc};
+use clap::Subcommand;
+use humansize::{WINDOWS, format_size};
+use rustls::crypto::ring;
+use std::{path::PathBuf, sync::Arc};
+use tokio::sync::Notify;
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Run the server. This is what happens without a command.
+    Serve,
+
+    /// Manage tokens.
+    #[command(subcommand)]
+    Token(TokenCommand),
+
+    /// Manage route access.
+    #[command(subcommand)]
+    Route(RouteCommand),
+
+    /// Manage the master key.
+    #[command(subcommand)]
+    MasterKey(MasterKeyCommand),
+
+    /// Index POMs and JARs that haven't been indexed yet.
+    Reindex {
+        /// Index every POM and JAR again, including ones that failed before. Each file's
+        /// old records stay in place until its new ones are ready.
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Delete stored objects that no file uses anymore.
+    Gc {
+        /// Only list what would be deleted.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Leave objects newer than this many minutes alone, in case an upload is still
+        /// in progress.
+        #[arg(long, default_value_t = 60)]
+        min_age_minutes: i64,
+    },
+
+    /// Check that every file's data is still in storage, at the right size.
+    Verify,
+
+    /// Copy files in from a local repository directory, or another maven server. Running
+    /// this again picks up where it left off.
+    Import {
+        /// A directory (like `~/.m2/repository`), or the URL of another maven server.
+        source: ImportSource,
+
+        /// Where to put the imported files.
+        #[arg(long, default_value = "/")]
+        prefix: String,
+    },
+
+    /// Write the repository out to a directory, `.tar` or `.zip`, with checksum files and
+    /// a manifest.
+    Export {
+        /// A directory, or a path ending in `.tar` or `.zip`.
+        output: PathBuf,
+
+        /// Only export files under this route.
+        #[arg(long, default_value = "/")]
+        prefix: String,
+
+        /// Only export files added here after this (RFC 3339, like
+        /// `2026-01-01T00:00:00Z`). Imported files count from when they were imported.
+        #[arg(long)]
+        since: Option<DateTime<Utc>>,
+    },
+
+    /// Read every file back from storage and check its hashes.
+    Scrub {
+        /// Stop serving files that fail.
+        #[arg(long)]
+        quarantine: bool,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum TokenCommand {
+    /// Create a token and print its value.
+    Create {
+        name: String,
+
+        /// Use this value instead of a random one.
+        #[arg(long)]
+        value: Option<String>,
+    },
+
+    /// List tokens and their paths.
+    List,
+
+    /// Delete a token.
+    Delete { name: String },
+
+    /// Give a token access to a path.
+    Grant {
+        name: String,
+        path: String,
+
+        /// read, write or read_write.
+        #[arg(default_value = "read_write")]
+        permission: MavenTokenPermissions,
+    },
+
+    /// Take a path away from a token.
+    Revoke { name: String, path: String },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum RouteCommand {
+    /// Set a route's access level.
+    Set {
+        path: String,
+
+        /// public, hidden or private.
+        access: RouteAccess,
+
+        /// Sign uploads here with the server's key. Left as is if not given.
+        #[arg(long)]
+        sign: Option<bool>,
+    },
+
+    /// List routes and their access levels.
+    List,
+
+    /// Remove a route's settings, so it inherits its parent's.
+    Delete { path: String },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum MasterKeyCommand {
+    /// Replace the master key and print the new one.
+    Rotate {
+        /// Use this value instead of a random one.
+        #[arg(long)]
+        value: Option<String>,
+    },
+}
+
+/// Connect to everything a command needs, the same way the server does. The routes and
+/// tokens from the config are left for the server to apply.
+pub async fn open(
+    db: String,
+    master_key: Option<String>,
+    s3: S3Config,
+    replicas: Vec<ReplicationTarget>,
+) -> Result<Arc<RouteContext>> {
+    ring::default_provider()
+        .install_default()
+        .map_err(|err| anyhow!("{:?}", err))?;
+
+    let pool = connect(Some(db))?;
+
+    migrate(&pool).await?;
+    seed_db(&pool, master_key).await?;
+
+    Ok(Arc::new(
+        RouteContext::create(
+            s3,
+            pool,
+            Arc::new(Notify::new()),
+            None,
+            None,
+            AuthLimits::default(),
+            replicas,
+        )
+        .await?,
+    ))
+}
+
+impl Command {
+    pub async fn run(self, cx: &RouteContext) -> Result<()> {
+        match self {
+            Self::Serve => unreachable!("The server is started by the CLI"),
+            Self::Token(it) => it.run(cx).await,
+            Self::Route(it) => it.run(cx).await,
+            Self::MasterKey(it) => it.run(cx).await,
+
+            Self::Reindex { all } => {
+                let (poms, jars) = cx.reindex(all).await?;
+
+                println!("Indexed {poms} POM(s) and {jars} JAR(s).");
+
+                Ok(())
+            }
+
+            Self::Gc {
+                dry_run,
+                min_age_minutes,
+            } => {
+                let report = cx
+                    .collect_garbage(chrono::Duration::minutes(min_age_minutes), dry_run)
+                    .await?;
+
+                println!(
+                    "{} {} object(s) ({}), kept {}.",
+                    if dry_run { "Would remove" } else { "Removed" },
+                    report.removed,
+                    format_size(report.freed, WINDOWS.decimal_places(2)),
+                    report.kept
+                );
+
+                Ok(())
+            }
+
+            Self::Verify => {
+                let problems = cx.check_blobs().await?;
+
+                for it in &problems {
+                    println!("{} ({}): {}", it.path, it.md5, it.problem);
+                }
+
+                if !problems.is_empty() {
+                    bail!("Found {} problem(s)!", problems.len());
+                }
+
+                println!("Everything looks good!");
+
+                Ok(())
+            }
+
+            Self::Import { source, prefix } => {
+                let job = cx.create_import(&source, prefix).await?;
+                let job = cx.run_import(job, source).await?;
+
+                if let Some(err) = job.error {
+                    bail!("Import failed: {err}");
+                }
+
+                println!(
+                    "Imported {} of {} file(s), skipped {}, {} failed.",
+                    job.imported, job.found, job.skipped, job.failed
+                );
+
+                Ok(())
+            }
+
+            Self::Export {
+                output,
+                prefix,
+                since,
+            } => {
+                let report = cx
+                    .export(&output, prefix, since.map(|it| it.naive_utc()))
+                    .await?;
+
+                for (path, problem) in &report.failed {
+                    println!("{path}: {problem}");
+                }
+
+                println!(
+                    "Exported {} file(s) to {}.",
+                    report.exported,
+                    output.display()
+                );
+
+                if !report.failed.is_empty() {
+                    bail!("{} file(s) couldn't be exported!", report.failed.len());
+                }
+
+                Ok(())
+            }
+
+            Self::Scrub { quarantine } => {
+                let run = cx.scrub(quarantine).await?;
+
+                if let Some(err) = run.error {
+                    bail!("Scrub failed: {err}");
+                }
+
+                for it in cx.get_scrub_report().await?.problems {
+                    println!(
+                        "{}: {}{}",
+                        it.path,
+                        it.detail,
+                        if it.quarantined { " (quarantined)" } else { "" }
+                    );
+                }
+
+                println!(
+                    "Checked {} file(s), found {} problem(s).",
+                    run.checked, run.problems
+                );
+
+                if run.problems > 0 {
+                    bail!("Found {} problem(s)!", run.problems);
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl TokenCommand {
+    pub async fn run(self, cx: &RouteContext) -> Result<()> {
+        match self {
+            Self::Create { name, value } => {
+                let generated = value.is_none();
+
+                let token = match value {
+                    Some(value) => MavenTokenIn { name, value },
+                    None => MavenTokenIn::new_random(name),
+                };
+
+                let token = cx.create_token(token, generated).await?;
+
+                match token.value {
+                    Some(value) => println!("Created token {}: {value}", token.name),
+                    None => println!("Created token {}.", token.name),
+                }
+            }
+
+            Self::List => {
+                for (token, paths) in cx.get_tokens_with_paths().await? {
+                    println!("{} (created {})", token.name, token.created);
+
+                    for path in paths {
+                        let path: HumanTokenPath = path.into();
+
+                        println!("    {} [{}]", path.path, path.permission);
+                    }
+                }
+            }
+
+            Self::Delete { name } => {
+                cx.get_token_by_name(&name).await?;
+                cx.delete_token(&name).await?;
+
+                println!("Deleted token {name}.");
+            }
+
+            Self::Grant {
+                name,
+                path,
+                permission,
+            } => {
+                cx.add_token_path(&name, &path, permission).await?;
+
+                println!("Gave {name} access to {path}.");
+            }
+
+            Self::Revoke { name, path } => {
+                cx.remove_token_path(&name, &path).await?;
+
+                println!("Took {path} away from {name}.");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl RouteCommand {
+    pub async fn run(self, cx: &RouteContext) -> Result<()> {
+        match self {
+            Self::Set { path, access, sign } => {
+                if !path.starts_with('/') {
+                    bail!("Route paths have to start with '/'!");
+                }
+
+                cx.set_route_access(RouteDataIn {
+                    path,
+                    visibility: access.value(),
+                    sign,
+                })
+                .await?;
+
+                println!("Updated route settings.");
+            }
+
+            Self::List => {
+                for route in cx.get_routes().await? {
+                    let route: RouteInfo = route.into();
+
+                    println!(
+                        "{} [{}]{}",
+                        route.path,
+                        route.access,
+                        if route.signed { " (signed)" } else { "" }
+                    );
+                }
+            }
+
+            Self::Delete { path } => {
+                if path == "/" {
+                    bail!("The root route can't be deleted!");
+                }
+
+                if !cx.delete_route_access(&path).await? {
+                    bail!("No settings for {path}!");
+                }
+
+                println!("Deleted settings for {path}.");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl MasterKeyCommand {
+    pub async fn run(self, cx: &RouteContext) -> Result<()> {
+        match self {
+            Self::Rotate { value } => {
+                let key = cx.rotate_master_key(value).await?;
+
+                println!("The new master key is: {}", key.value);
+                println!("Write it down, it won't be displayed again!");
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,249 @@
+//! The optional TOML config file. Anything set here can also be set with flags or env
+//! vars, which win over the file. Routes and tokens can only be set up here.
+
+use crate::{
+    replication::target::ReplicationTarget, router::access::RouteAccess,
+    tokens::perms::MavenTokenPermissions,
+};
+use anyhow::{Context, Result, bail};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub storage: StorageConfig,
+    pub auth: AuthConfig,
+    pub signing: SigningConfig,
+    pub tls: TlsConfig,
+    pub scrub: ScrubConfig,
+
+    /// Route settings to apply on startup.
+    pub routes: Vec<RouteSeed>,
+
+    /// Tokens to create (if needed) and give paths to on startup.
+    pub tokens: Vec<TokenSeed>,
+
+    /// Other servers or buckets to copy uploads and deletes to.
+    pub replication: Vec<ReplicationTarget>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub drain_timeout: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    pub url: Option<String>,
+    pub region: Option<String>,
+    pub bucket: Option<String>,
+    pub access_key_id: Option<String>,
+    pub access_key_secret: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    pub secret: Option<String>,
+    pub master_key: Option<String>,
+    pub rate_limit: Option<u32>,
+    pub lockout_threshold: Option<u32>,
+    pub lockout_seconds: Option<u64>,
+    pub lockout_max_seconds: Option<u64>,
+    pub trust_forwarded_for: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SigningConfig {
+    pub trusted_keys: Option<PathBuf>,
+    pub key: Option<PathBuf>,
+    pub key_password: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsConfig {
+    pub cert: Option<PathBuf>,
+    pub key: Option<PathBuf>,
+    pub client_ca: Option<PathBuf>,
+    pub redirect_port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScrubConfig {
+    pub interval_hours: Option<u64>,
+    pub quarantine: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RouteSeed {
+    pub path: String,
+    pub access: RouteAccess,
+
+    /// Leaving this out keeps the current setting (or the default, for new routes).
+    #[serde(default)]
+    pub sign: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenSeed {
+    pub name: String,
+
+    /// The token's value. If this is left out, new tokens get a random one, which is
+    /// logged once.
+    #[serde(default)]
+    pub value: Option<String>,
+
+    #[serde(default)]
+    pub paths: Vec<TokenPathSeed>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenPathSeed {
+    pub path: String,
+    pub permission: MavenTokenPermissions,
+}
+
+/// The parts of the config that get written to the database when the server starts.
+#[derive(Debug, Clone, Default)]
+pub struct Seeds {
+    pub routes: Vec<RouteSeed>,
+    pub tokens: Vec<TokenSeed>,
+}
+
+impl Config {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Could not read config file {}", path.display()))?;
+
+        let mut config: Self = toml::from_str(&text)
+            .with_context(|| format!("Could not parse config file {}", path.display()))?;
+
+        config
+            .validate()
+            .with_context(|| format!("Invalid config file {}", path.display()))?;
+
+        // Relative paths are relative to the config file, not wherever we were started.
+        if let Some(dir) = path.parent() {
+            for it in [
+                &mut config.signing.trusted_keys,
+                &mut config.signing.key,
+                &mut config.tls.cert,
+                &mut config.tls.key,
+                &mut config.tls.client_ca,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                if it.is_relative() {
+                    *it = dir.join(&it);
+                }
+            }
+        }
+
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let mut routes = HashSet::new();
+
+        for route in &self.routes {
+            if !route.path.starts_with('/') {
+                bail!("Route paths have to start with '/': {}", route.path);
+            }
+
+            if !routes.insert(&route.path) {
+                bail!("Route {} is set up more than once", route.path);
+            }
+        }
+
+        let mut tokens = HashSet::new();
+
+        for token in &self.tokens {
+            if token.name.trim().is_empty() {
+                bail!("Tokens need a name");
+            }
+
+            if !tokens.insert(&token.name) {
+                bail!("Token {} is set up more than once", token.name);
+            }
+
+            if token.value.as_ref().is_some_and(|it| it.is_empty()) {
+                bail!("Token {} has an empty value", token.name);
+            }
+
+            let mut paths = HashSet::new();
+
+            for path in &token.paths {
+                if !path.path.starts_with('/') {
+                    bail!(
+                        "Token {} has a path that doesn't start with '/': {}",
+                        token.name,
+                        path.path
+                    );
+                }
+
+                if !paths.insert(&path.path) {
+                    bail!("Token {} lists {} more than once", token.name, path.path);
+                }
+            }
+        }
+
+        let mut replicas = HashSet::new();
+
+        for target in &self.replication {
+            if target.name().trim().is_empty() {
+                bail!("Replication targets need a name");
+            }
+
+            if !replicas.insert(target.name()) {
+                bail!(
+                    "Replication target {} is set up more than once",
+                    target.name()
+                );
+            }
+
+            if let ReplicationTarget::Server { url, .. } = target {
+                url.parse::<reqwest::Url>().with_context(|| {
+                    format!("Replication target {} has a bad URL", target.name())
+                })?;
+            }
+        }
+
+        if self.tls.cert.is_some() != self.tls.key.is_some() {
+            bail!("tls.cert and tls.key have to be used together");
+        }
+
+        Ok(())
+    }
+
+    pub fn seeds(&self) -> Seeds {
+        Seeds {
+            routes: self.routes.clone(),
+            tokens: self.tokens.clone(),
+        }
+    }
+}
@@ -1,15 +1,52 @@
-use crate::{db::DbPool, s3::S3Config};
+use crate::{
+    db::DbPool,
+    files::{signatures::Keyring, signing::SigningKey},
+    ratelimit::{AuthLimiter, AuthLimits, RateLimiter},
+    replication::target::{Replica, ReplicationTarget},
+    s3::S3Config,
+    shutdown::Drain,
+};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use object_store::aws::{AmazonS3, AmazonS3Builder};
+use object_store::aws::AmazonS3;
 use tokio::sync::Notify;
-use std::sync::Arc;
+use std::sync::{Arc, atomic::AtomicBool};
 
 pub struct RouteContext {
     pub storage: Arc<AmazonS3>,
     pub pool: DbPool,
     pub notify: Arc<Notify>,
     pub start_time: DateTime<Utc>,
+
+    /// Keys that uploaded signatures are checked against, if configured.
+    pub keyring: Option<Keyring>,
+
+    /// The key that uploads are signed with on routes that ask for it, if configured.
+    pub signing_key: Option<SigningKey>,
+
+    /// Per-token upload rate limits.
+    pub upload_limiter: RateLimiter<i32>,
+
+    /// Rate limits and lockouts for authentication.
+    pub auth_limiter: AuthLimiter,
+
+    /// Tracks writes, so shutdown can wait for them.
+    pub drain: Drain,
+
+    /// Whether the first directory index since startup has finished.
+    pub indexed: AtomicBool,
+
+    /// Whether a scrub is running right now.
+    pub scrubbing: AtomicBool,
+
+    /// Where uploads and deletes get copied to.
+    pub replicas: Vec<Replica>,
+
+    /// Wakes up replication when there's something new in the outbox.
+    pub replication_notify: Notify,
+
+    /// Wakes up webhook delivery when something gets queued.
+    pub webhook_notify: Notify,
 }
 
 impl RouteContext {
@@ -17,26 +54,29 @@ impl RouteContext {
         s3: S3Config,
         conn: DbPool,
         notify: Arc<Notify>,
+        keyring: Option<Keyring>,
+        signing_key: Option<SigningKey>,
+        auth_limits: AuthLimits,
+        replicas: Vec<ReplicationTarget>,
     ) -> Result<Self> {
-        let mut builder = AmazonS3Builder::new()
-            .with_region(s3.region)
-            .with_bucket_name(s3.bucket)
-            .with_access_key_id(s3.access_key_id)
-            .with_secret_access_key(s3.access_key_secret);
-
-        if let Some(url) = s3.url {
-            if url.starts_with("http:") {
-                builder = builder.with_allow_http(true);
-            }
-
-            builder = builder.with_endpoint(url);
-        }
-
         Ok(Self {
-            storage: Arc::new(builder.build()?),
+            storage: Arc::new(s3.build()?),
             pool: conn,
             notify,
             start_time: Utc::now(),
+            keyring,
+            signing_key,
+            upload_limiter: RateLimiter::new(),
+            auth_limiter: AuthLimiter::new(auth_limits),
+            drain: Drain::new(),
+            indexed: AtomicBool::new(false),
+            scrubbing: AtomicBool::new(false),
+            replicas: replicas
+                .into_iter()
+                .map(Replica::new)
+                .collect::<Result<_>>()?,
+            replication_notify: Notify::new(),
+            webhook_notify: Notify::new(),
         })
     }
 }
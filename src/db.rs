@@ -68,6 +68,19 @@ pub async fn migrate(pool: &DbPool) -> Result<()> {
     Ok(())
 }
 
+/// Whether the database is missing any migrations this build knows about.
+pub async fn has_pending_migrations(pool: &DbPool) -> Result<bool> {
+    let mut async_wrapper: AsyncConnectionWrapper<PoolConn> =
+        AsyncConnectionWrapper::from(pool.get().await?);
+
+    tokio::task::spawn_blocking(move || {
+        async_wrapper
+            .has_pending_migration(MIGRATIONS)
+            .map_err(|e| anyhow!("{e}"))
+    })
+    .await?
+}
+
 async fn run_migrations<A: AsyncConnection<Backend = B> + 'static, B: Backend>(
     conn: A,
     migrations: EmbeddedMigrations,
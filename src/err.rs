@@ -1,21 +1,160 @@
+use crate::{
+    files::{hashes::ChecksumMismatch, rules::ValidationError, signatures::SignatureError},
+    staging::models::InvalidStaging,
+};
 use anyhow::Result;
-use axum::{body::Body, response::Response};
+use axum::{
+    body::Body,
+    http::header::{CONTENT_TYPE, RETRY_AFTER},
+    response::Response,
+};
 use std::fmt::Display;
 use tokio::task::JoinError;
 
+/// The `WWW-Authenticate` challenges sent with 401s, for Basic (tokens) and Bearer
+/// (master key) auth.
+pub const BASIC_CHALLENGE: &str = "Basic realm=\"Maven\", charset=\"UTF-8\"";
+pub const BEARER_CHALLENGE: &str = "Bearer realm=\"Maven\"";
+
+/// Errors that map to a specific HTTP status. Anything else is a 500.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MavenError {
+    BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
+    NotFound(String),
+    Conflict(String),
+    PayloadTooLarge(String),
+
+    /// Slow down, and try again after this many seconds.
+    TooManyRequests(String, u64),
+
+    /// Something we depend on (like the object store) failed.
+    Upstream(String),
+
+    /// We're shutting down, or otherwise can't take this right now.
+    Unavailable(String),
+}
+
+impl MavenError {
+    pub fn not_found() -> Self {
+        Self::NotFound("404 Not Found".into())
+    }
+
+    pub fn forbidden() -> Self {
+        Self::Forbidden("403 Access Denied".into())
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::BadRequest(it)
+            | Self::Unauthorized(it)
+            | Self::Forbidden(it)
+            | Self::NotFound(it)
+            | Self::Conflict(it)
+            | Self::PayloadTooLarge(it)
+            | Self::TooManyRequests(it, _)
+            | Self::Upstream(it)
+            | Self::Unavailable(it) => it,
+        }
+    }
+}
+
+impl Display for MavenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for MavenError {}
+
+/// A short, stable name for a status code, used as `error` in JSON error bodies.
+pub fn error_kind(code: u16) -> &'static str {
+    match code {
+        400 => "bad_request",
+        401 => "unauthorized",
+        403 => "forbidden",
+        404 => "not_found",
+        405 => "method_not_allowed",
+        409 => "conflict",
+        413 => "payload_too_large",
+        422 => "unprocessable_entity",
+        429 => "too_many_requests",
+        502 => "upstream",
+        503 => "unavailable",
+        _ if code >= 500 => "internal",
+        _ => "error",
+    }
+}
+
+/// The JSON form of an error response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorBody {
+    pub status: u16,
+    pub error: String,
+    pub message: String,
+}
+
 pub trait HasCode {
     /// Get the error code.
     fn code(&self) -> u16;
+
+    /// How long the client should wait before retrying, in seconds, if it should.
+    fn retry_after(&self) -> Option<u64> {
+        None
+    }
+}
+
+impl HasCode for MavenError {
+    fn code(&self) -> u16 {
+        match self {
+            Self::BadRequest(_) => 400,
+            Self::Unauthorized(_) => 401,
+            Self::Forbidden(_) => 403,
+            Self::NotFound(_) => 404,
+            Self::Conflict(_) => 409,
+            Self::PayloadTooLarge(_) => 413,
+            Self::TooManyRequests(_, _) => 429,
+            Self::Upstream(_) => 502,
+            Self::Unavailable(_) => 503,
+        }
+    }
+
+    fn retry_after(&self) -> Option<u64> {
+        match self {
+            Self::TooManyRequests(_, secs) => Some(*secs),
+            _ => None,
+        }
+    }
 }
 
 impl HasCode for anyhow::Error {
     fn code(&self) -> u16 {
-        if self.to_string() == "404 Not Found" {
-            404
+        if let Some(err) = self.downcast_ref::<MavenError>() {
+            err.code()
+        } else if self.downcast_ref::<ChecksumMismatch>().is_some()
+            || self.downcast_ref::<SignatureError>().is_some()
+        {
+            400
+        } else if self.downcast_ref::<ValidationError>().is_some() {
+            422
+        } else if self.downcast_ref::<InvalidStaging>().is_some() {
+            409
+        } else if let Some(err) = self.downcast_ref::<diesel::result::Error>() {
+            err.code()
+        } else if let Some(err) = self.downcast_ref::<object_store::Error>() {
+            err.code()
+        } else if let Some(err) = self.downcast_ref::<zip::result::ZipError>() {
+            err.code()
         } else {
             500
         }
     }
+
+    fn retry_after(&self) -> Option<u64> {
+        self.downcast_ref::<MavenError>()
+            .and_then(MavenError::retry_after)
+    }
 }
 
 impl HasCode for std::io::Error {
@@ -44,7 +183,10 @@ impl HasCode for serde_json::Error {
 
 impl HasCode for object_store::Error {
     fn code(&self) -> u16 {
-        502
+        match self {
+            Self::NotFound { .. } => 404,
+            _ => 502,
+        }
     }
 }
 
@@ -56,7 +198,10 @@ impl HasCode for askama::Error {
 
 impl HasCode for zip::result::ZipError {
     fn code(&self) -> u16 {
-        500
+        match self {
+            Self::FileNotFound => 404,
+            _ => 500,
+        }
     }
 }
 
@@ -68,7 +213,10 @@ impl HasCode for JoinError {
 
 impl HasCode for diesel::result::Error {
     fn code(&self) -> u16 {
-        500
+        match self {
+            Self::NotFound => 404,
+            _ => 500,
+        }
     }
 }
 
@@ -83,10 +231,17 @@ where
     Self: Sized + HasCode,
 {
     fn into_axum(self) -> Response {
-        match Response::builder()
-            .status(self.code())
-            .body(Body::new(format!("{}", self)))
-        {
+        let code = self.code();
+
+        let mut builder = Response::builder()
+            .status(code)
+            .header(CONTENT_TYPE, "text/plain; charset=utf-8");
+
+        if let Some(secs) = self.retry_after() {
+            builder = builder.header(RETRY_AFTER, secs);
+        }
+
+        match builder.body(Body::new(format!("{}", self))) {
             Ok(it) => it,
             Err(err) => Response::new(Body::new(format!("Could not create a response: {}", err))),
         }
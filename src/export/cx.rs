@@ -0,0 +1,185 @@
+use super::writer::ExportWriter;
+use crate::{
+    cx::RouteContext,
+    files::{hashes::HASH_TYPES, models::MavenFile},
+    schema::files,
+    util::escape_like,
+};
+use anyhow::Result;
+use chrono::{NaiveDateTime, Utc};
+use diesel::{ExpressionMethods, QueryDsl, SelectableHelper, TextExpressionMethods};
+use diesel_async::RunQueryDsl;
+use futures_util::TryStreamExt;
+use object_store::{ObjectStore, path::Path as StorePath};
+use std::path::Path;
+
+/// Where the manifest goes, at the top of the export.
+pub const MANIFEST_NAME: &str = "export-manifest.json";
+
+/// Everything that made it into an export, so a copy can be checked against it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub created: NaiveDateTime,
+    pub prefix: String,
+
+    /// Only files added here after this are included, if it's set.
+    pub since: Option<NaiveDateTime>,
+    pub files: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub size: i64,
+    pub uploaded: NaiveDateTime,
+    pub md5: String,
+    pub sha1: String,
+    pub sha256: String,
+    pub sha512: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportReport {
+    pub exported: usize,
+
+    /// Files that failed, and why. These are left out of the manifest.
+    pub failed: Vec<(String, String)>,
+}
+
+impl From<&MavenFile> for ManifestEntry {
+    fn from(file: &MavenFile) -> Self {
+        Self {
+            path: file.path.clone(),
+            size: file.size,
+            uploaded: file.uploaded,
+            md5: file.md5.clone(),
+            sha1: file.sha1.clone(),
+            sha256: file.sha256.clone(),
+            sha512: file.sha512.clone(),
+        }
+    }
+}
+
+impl RouteContext {
+    /// Write every file under `prefix` out to a directory, `.tar` or `.zip` (going by
+    /// `output`'s extension), with checksum files and a manifest. With `since`, only
+    /// files added here after it are included.
+    pub async fn export(
+        &self,
+        output: impl AsRef<Path>,
+        prefix: impl AsRef<str>,
+        since: Option<NaiveDateTime>,
+    ) -> Result<ExportReport> {
+        let prefix = prefix.as_ref();
+
+        let mut query = files::table
+            .filter(files::path.like(format!("{}%", escape_like(prefix))))
+            .select(MavenFile::as_select())
+            .order_by(files::path)
+            .into_boxed();
+
+        if let Some(since) = since {
+            query = query.filter(files::stored.gt(since));
+        }
+
+        let files = query.load(&mut self.pool.get().await?).await?;
+
+        info!("Exporting {} file(s)...", files.len());
+
+        let mut out = ExportWriter::create(output).await?;
+        let mut report = ExportReport::default();
+        let mut entries = Vec::new();
+
+        for (i, file) in files.iter().enumerate() {
+            match self.export_file(&mut out, file).await? {
+                Some(problem) => {
+                    warn!("Could not export {}: {problem}", file.path);
+                    report.failed.push((file.path.clone(), problem));
+                }
+
+                None => {
+                    entries.push(ManifestEntry::from(file));
+                    report.exported += 1;
+                }
+            }
+
+            if (i + 1) % 100 == 0 {
+                info!("Exported {}/{} file(s)...", i + 1, files.len());
+            }
+        }
+
+        let manifest = ExportManifest {
+            created: Utc::now().naive_utc(),
+            prefix: prefix.into(),
+            since,
+            files: entries,
+        };
+
+        out.add(
+            MANIFEST_NAME,
+            serde_json::to_vec_pretty(&manifest)?.into(),
+            manifest.created,
+        )
+        .await?;
+
+        out.finish().await?;
+
+        Ok(report)
+    }
+
+    /// Write a file and its checksums out. Problems with the file itself (rather than
+    /// the output) are returned, so the rest of the export can carry on.
+    async fn export_file(
+        &self,
+        out: &mut ExportWriter,
+        file: &MavenFile,
+    ) -> Result<Option<String>> {
+        // Archives can't take anything back once it's written, so the object is checked
+        // in full first, and only copied out if it's what it should be.
+        let Some(hashes) = self.hash_object(&file.md5).await? else {
+            return Ok(Some("Missing from storage".into()));
+        };
+
+        if hashes.size != file.size as u64 {
+            return Ok(Some(format!(
+                "Expected {} bytes, but storage has {}",
+                file.size, hashes.size
+            )));
+        }
+
+        if hashes.sha256 != file.sha256 {
+            return Ok(Some(format!(
+                "Its data doesn't match its hash (expected {}, found {})",
+                file.sha256, hashes.sha256
+            )));
+        }
+
+        let mut stream = self
+            .storage
+            .get(&StorePath::from(file.md5.as_str()))
+            .await?
+            .into_stream();
+
+        out.start(&file.path, file.size as u64, file.uploaded)
+            .await?;
+
+        while let Some(chunk) = stream.try_next().await? {
+            out.write(&chunk).await?;
+        }
+
+        for (alg, hash) in
+            HASH_TYPES
+                .iter()
+                .zip([&file.md5, &file.sha1, &file.sha256, &file.sha512])
+        {
+            out.add(
+                &format!("{}.{alg}", file.path),
+                hash.clone().into(),
+                file.uploaded,
+            )
+            .await?;
+        }
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,2 @@
+pub mod cx;
+pub mod writer;
@@ -0,0 +1,257 @@
+use anyhow::{Result, bail};
+use axum::body::Bytes;
+use chrono::{Datelike, NaiveDateTime, Timelike};
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
+use tokio::{fs, io::AsyncWriteExt};
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+/// Tar files are made of blocks this big.
+const TAR_BLOCK: usize = 512;
+
+/// The longest path that fits in a tar header. Longer ones get a GNU long name entry.
+const TAR_NAME: usize = 100;
+
+/// Files that are already compressed, so zipping them again is a waste of time.
+const COMPRESSED: &[&str] = &[".jar", ".war", ".aar", ".zip", ".gz", ".xz"];
+
+/// Where an export goes, and what shape it takes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A plain maven repository layout.
+    Dir,
+    Tar,
+    Zip,
+}
+
+impl ExportFormat {
+    pub fn for_path(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension().and_then(|it| it.to_str()) {
+            Some("tar") => Self::Tar,
+            Some("zip") => Self::Zip,
+            _ => Self::Dir,
+        }
+    }
+}
+
+/// Writes files out one chunk at a time, so nothing big has to be in memory.
+pub enum ExportWriter {
+    Dir {
+        root: PathBuf,
+        file: Option<fs::File>,
+    },
+
+    Tar {
+        out: fs::File,
+
+        /// How big the current entry says it is, and how much of it has been written.
+        size: u64,
+        written: u64,
+    },
+
+    Zip(ZipWriter<File>),
+}
+
+impl ExportWriter {
+    pub async fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+
+        Ok(match ExportFormat::for_path(path) {
+            ExportFormat::Dir => {
+                fs::create_dir_all(path).await?;
+
+                Self::Dir {
+                    root: path.to_path_buf(),
+                    file: None,
+                }
+            }
+
+            ExportFormat::Tar => Self::Tar {
+                out: fs::File::create(path).await?,
+                size: 0,
+                written: 0,
+            },
+
+            ExportFormat::Zip => Self::Zip(ZipWriter::new(File::create(path)?)),
+        })
+    }
+
+    /// Start a new file. Its data has to be written with [`ExportWriter::write`] before
+    /// the next one is started, and has to add up to `size` for archives.
+    pub async fn start(&mut self, path: &str, size: u64, modified: NaiveDateTime) -> Result<()> {
+        let path = path.trim_start_matches('/');
+
+        match self {
+            Self::Dir { root, file } => {
+                let dest = root.join(path);
+
+                if let Some(parent) = dest.parent() {
+                    fs::create_dir_all(parent).await?;
+                }
+
+                if let Some(mut it) = file.take() {
+                    it.flush().await?;
+                }
+
+                *file = Some(fs::File::create(dest).await?);
+            }
+
+            Self::Tar {
+                out,
+                size: current,
+                written,
+            } => {
+                end_tar_entry(out, *current, written).await?;
+
+                *current = size;
+
+                let mut header = tar::Header::new_gnu();
+
+                if path.len() > TAR_NAME {
+                    write_long_name(out, path).await?;
+
+                    // Readers go by the long name, so this is just for ones that don't.
+                    header.as_old_mut().name[..TAR_NAME]
+                        .copy_from_slice(&path.as_bytes()[..TAR_NAME]);
+                } else {
+                    header.set_path(path)?;
+                }
+
+                header.set_size(size);
+                header.set_mode(0o644);
+                header.set_mtime(modified.and_utc().timestamp().max(0) as u64);
+                header.set_entry_type(tar::EntryType::Regular);
+                header.set_cksum();
+
+                out.write_all(header.as_bytes()).await?;
+            }
+
+            Self::Zip(zip) => {
+                let method = if COMPRESSED.iter().any(|it| path.ends_with(it)) {
+                    CompressionMethod::Stored
+                } else {
+                    CompressionMethod::Deflated
+                };
+
+                let mut options = SimpleFileOptions::default()
+                    .compression_method(method)
+                    .large_file(size >= u32::MAX as u64);
+
+                if let Ok(time) = zip::DateTime::from_date_and_time(
+                    modified.year() as u16,
+                    modified.month() as u8,
+                    modified.day() as u8,
+                    modified.hour() as u8,
+                    modified.minute() as u8,
+                    modified.second() as u8,
+                ) {
+                    options = options.last_modified_time(time);
+                }
+
+                tokio::task::block_in_place(|| zip.start_file(path, options))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn write(&mut self, data: &[u8]) -> Result<()> {
+        match self {
+            Self::Dir { file, .. } => match file {
+                Some(it) => it.write_all(data).await?,
+                None => bail!("No file has been started!"),
+            },
+
+            Self::Tar { out, size, written } => {
+                *written += data.len() as u64;
+
+                // The header's already out, so there's no fixing this.
+                if *written > *size {
+                    bail!("Entry is bigger than the {size} bytes it should be!");
+                }
+
+                out.write_all(data).await?;
+            }
+
+            Self::Zip(zip) => tokio::task::block_in_place(|| zip.write_all(data))?,
+        }
+
+        Ok(())
+    }
+
+    /// Write a whole (small) file at once.
+    pub async fn add(&mut self, path: &str, data: Bytes, modified: NaiveDateTime) -> Result<()> {
+        self.start(path, data.len() as u64, modified).await?;
+        self.write(&data).await
+    }
+
+    pub async fn finish(self) -> Result<()> {
+        match self {
+            Self::Dir { file, .. } => {
+                if let Some(mut it) = file {
+                    it.flush().await?;
+                }
+            }
+
+            Self::Tar {
+                mut out,
+                size,
+                mut written,
+            } => {
+                end_tar_entry(&mut out, size, &mut written).await?;
+
+                // Two empty blocks mark the end of the archive.
+                out.write_all(&[0; TAR_BLOCK * 2]).await?;
+                out.flush().await?;
+            }
+
+            Self::Zip(zip) => {
+                tokio::task::block_in_place(|| zip.finish())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Check the last entry was as big as it said, and pad it out to a whole block.
+async fn end_tar_entry(out: &mut fs::File, size: u64, written: &mut u64) -> Result<()> {
+    if *written != size {
+        bail!("Entry is {written} bytes, but should be {size}!");
+    }
+
+    let extra = (*written % TAR_BLOCK as u64) as usize;
+
+    if extra != 0 {
+        out.write_all(&vec![0; TAR_BLOCK - extra]).await?;
+    }
+
+    *written = 0;
+
+    Ok(())
+}
+
+/// Write a GNU long name entry, which holds the path of the entry after it.
+async fn write_long_name(out: &mut fs::File, path: &str) -> Result<()> {
+    let mut name = path.as_bytes().to_vec();
+
+    name.push(0);
+
+    let mut header = tar::Header::new_gnu();
+
+    header.set_path("././@LongLink")?;
+    header.set_size(name.len() as u64);
+    header.set_mode(0o644);
+    header.set_entry_type(tar::EntryType::GNULongName);
+    header.set_cksum();
+
+    out.write_all(header.as_bytes()).await?;
+    out.write_all(&name).await?;
+
+    let mut written = name.len() as u64;
+
+    end_tar_entry(out, written, &mut written).await
+}
@@ -1,13 +1,29 @@
-use super::{hashes::HASH_TYPES, models::MavenFile, models_in::MavenFileIn};
+use super::{
+    hashes::{ChecksumMismatch, ExpectedChecksums, HASH_TYPES},
+    models::{MavenFile, MavenFileSignature},
+    models_in::{MavenFileIn, MavenFileSignatureIn},
+    signatures::{SIGNATURE_TYPES, SignatureError, signature_target},
+    signing::is_signable,
+    types::FILE_TYPES,
+};
 use crate::{
     cx::RouteContext,
-    router::stats::InstanceStats,
-    schema::{files, tokens},
+    err::MavenError,
+    maven::{jar::scan_jar, pom::Pom},
+    replication::cx::{DELETE_EVENT, UPLOAD_EVENT},
+    router::{models::RouteData, stats::InstanceStats},
+    schema::{file_signatures, files, route_data, tokens},
+    util::escape_like,
+    webhooks::models::{WebhookEvent, WebhookPayload, WebhookVersion},
 };
-use anyhow::{Result, anyhow};
+use anyhow::Result;
+use axum::body::Bytes;
 use chrono::Utc;
-use diesel::{ExpressionMethods, QueryDsl, SelectableHelper, delete, insert_into, pg::Pg};
-use diesel_async::{AsyncConnection, RunQueryDsl};
+use diesel::{
+    ExpressionMethods, QueryDsl, SelectableHelper, TextExpressionMethods, delete, insert_into,
+    pg::Pg,
+};
+use diesel_async::{AsyncConnection, RunQueryDsl, scoped_futures::ScopedFutureExt};
 use object_store::{ObjectStore, PutPayload};
 use std::collections::HashMap;
 
@@ -36,6 +52,70 @@ impl RouteContext {
             .await?)
     }
 
+    pub async fn get_files_in_folder(&self, folder: impl AsRef<str>) -> Result<Vec<MavenFile>> {
+        Ok(self
+            .get_files_in_folder_inner(folder, &mut self.pool.get().await?)
+            .await?)
+    }
+
+    pub async fn get_files_in_folder_inner(
+        &self,
+        folder: impl AsRef<str>,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<Vec<MavenFile>> {
+        let folder = format!("{}/", folder.as_ref()).replace("//", "/");
+
+        Ok(files::table
+            .filter(files::parent.eq(folder))
+            .select(MavenFile::as_select())
+            .load(conn)
+            .await?)
+    }
+
+    pub async fn get_files_under(&self, prefix: impl AsRef<str>) -> Result<Vec<MavenFile>> {
+        Ok(self
+            .get_files_under_inner(prefix, &mut self.pool.get().await?)
+            .await?)
+    }
+
+    pub async fn get_files_under_inner(
+        &self,
+        prefix: impl AsRef<str>,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<Vec<MavenFile>> {
+        Ok(files::table
+            .filter(files::path.like(format!("{}%", escape_like(prefix))))
+            .select(MavenFile::as_select())
+            .load(conn)
+            .await?)
+    }
+
+    /// Find files by their SHA-1 and/or SHA-256 hashes.
+    pub async fn get_files_by_hash(
+        &self,
+        sha1: Option<&str>,
+        sha256: Option<&str>,
+    ) -> Result<Vec<MavenFile>> {
+        if sha1.is_none() && sha256.is_none() {
+            return Ok(Vec::new());
+        }
+
+        let mut query = files::table.select(MavenFile::as_select()).into_boxed();
+
+        if let Some(sha1) = sha1 {
+            query = query.filter(files::sha1.eq(sha1.to_lowercase()));
+        }
+
+        if let Some(sha256) = sha256 {
+            query = query.filter(files::sha256.eq(sha256.to_lowercase()));
+        }
+
+        Ok(query
+            .order(files::path.asc())
+            .load(&mut self.pool.get().await?)
+            .await?)
+    }
+
     pub async fn has_file(&self, path: impl AsRef<str>) -> bool {
         self.get_file(path).await.is_ok()
     }
@@ -49,9 +129,33 @@ impl RouteContext {
     }
 
     pub async fn delete_file(&self, path: impl AsRef<str>) -> Result<MavenFile> {
-        Ok(self
-            .delete_file_inner(path, &mut self.pool.get().await?)
-            .await?)
+        let mut conn = self.pool.get().await?;
+        let path = path.as_ref();
+
+        let file = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                async move {
+                    let file = self.delete_file_inner(path, conn).await?;
+
+                    self.record_replication_inner(DELETE_EVENT, path, conn)
+                        .await?;
+
+                    self.record_webhook_inner(
+                        WebhookPayload::for_file(WebhookEvent::FileDeleted, &file),
+                        conn,
+                    )
+                    .await?;
+
+                    Ok(file)
+                }
+                .scope_boxed()
+            })
+            .await?;
+
+        self.replication_notify.notify_one();
+        self.webhook_notify.notify_one();
+
+        Ok(file)
     }
 
     pub async fn delete_file_inner(
@@ -92,6 +196,8 @@ impl RouteContext {
         &self,
         path: impl AsRef<str>,
         bytes: impl AsRef<[u8]>,
+        expected: &ExpectedChecksums,
+        uploader: Option<i32>,
     ) -> Result<MavenFile> {
         let mut conn = self.pool.get().await?;
         let path = format!("/{}", path.as_ref()).replace("//", "/");
@@ -103,38 +209,157 @@ impl RouteContext {
             let alg = path.split(".").last().unwrap();
             let real = path.trim_end_matches(&format!(".{}", alg));
             let given = String::from_utf8(bytes.as_ref().to_vec())?;
+            let given = given.trim();
 
             return match self.get_file_inner(&real.to_owned(), &mut conn).await {
                 Ok(file) => {
                     let existing = file.get_hash(alg)?;
 
-                    if existing == given {
+                    if existing.eq_ignore_ascii_case(given) {
                         Ok(file)
                     } else {
-                        Err(anyhow!("Hash mismatch: expected {existing}, got {given}"))
+                        Err(ChecksumMismatch {
+                            algorithm: alg.into(),
+                            expected: given.into(),
+                            actual: existing,
+                        }
+                        .into())
                     }
                 }
 
-                _ => Err(anyhow!("Could not get a parent file for path: {}", path)),
+                _ => Err(MavenError::Conflict(format!(
+                    "Could not get a parent file for path: {}",
+                    path
+                ))
+                .into()),
             };
         }
 
-        let bytes = bytes.as_ref().to_vec();
-        let file = MavenFileIn::new(&path, &bytes).await?;
+        let bytes = Bytes::from(bytes.as_ref().to_vec());
+        let mut file = MavenFileIn::new(&path, &bytes).await?;
+
+        file.uploader = uploader;
+
+        // These have to happen before anything is stored, so a bad upload changes nothing.
+        expected.verify(&file)?;
+        self.check_rules_inner(&path, &bytes, &mut conn).await?;
+        self.check_pom_present_inner(&path, &mut conn).await?;
+
+        let fingerprint = self
+            .verify_signature_inner(&path, &bytes, None, &mut conn)
+            .await?;
 
         self.storage
             .put(
                 &file.md5.clone().into(),
-                PutPayload::from_bytes(bytes.into()),
+                PutPayload::from_bytes(bytes.clone()),
             )
             .await?;
 
+        // All or nothing, so a shutdown (or anything else) part way through can't leave
+        // the file half-indexed.
+        let result = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                async move { self.publish_inner(file, &bytes, fingerprint, conn).await }
+                    .scope_boxed()
+            })
+            .await?;
+
+        self.notify.notify_waiters();
+        self.replication_notify.notify_one();
+        self.webhook_notify.notify_one();
+
+        Ok(result)
+    }
+
+    /// If a path is a PGP signature and there's a keyring, check it against the file it
+    /// signs, and get the fingerprint of the key that made it. `target` is the signed
+    /// file's data, if it isn't live yet.
+    pub async fn verify_signature_inner(
+        &self,
+        path: impl AsRef<str>,
+        signature: &[u8],
+        target: Option<&[u8]>,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<Option<String>> {
+        let path = path.as_ref();
+
+        let (Some(keyring), Some(("pgp", target_path))) = (&self.keyring, signature_target(path))
+        else {
+            return Ok(None);
+        };
+
+        let data = match target {
+            Some(it) => it.to_vec(),
+
+            None => match self.get_file_inner(&target_path.to_owned(), conn).await {
+                Ok(it) => it.get_bytes(&self.storage).await?,
+                Err(_) => {
+                    return Err(SignatureError::new(path, "The signed file doesn't exist").into());
+                }
+            },
+        };
+
+        Ok(Some(keyring.verify(path, data, signature)?))
+    }
+
+    /// Put an already-stored file into the live tree, replacing whatever was at its path,
+    /// and index it. `fingerprint` is the key that made it, if it's a verified signature.
+    pub async fn publish_inner(
+        &self,
+        file: MavenFileIn,
+        bytes: &Bytes,
+        fingerprint: Option<String>,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<MavenFile> {
+        let path = file.path.clone();
+
+        let signed = match signature_target(&path) {
+            Some((kind, target)) => self
+                .get_file_inner(&target.to_owned(), conn)
+                .await
+                .ok()
+                .map(|it| (kind, it)),
+
+            None => None,
+        };
+
+        let pom = if path.ends_with(".pom") {
+            match Pom::parse(bytes) {
+                Ok(it) => Some(it),
+
+                Err(err) => {
+                    warn!("Could not parse POM {path}: {err}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // Javadoc JARs are just HTML, which isn't worth indexing.
+        let jar = if file.kind == *FILE_TYPES.get("jar").unwrap() && !path.ends_with("-javadoc.jar")
+        {
+            let bytes = bytes.clone();
+
+            match tokio::task::spawn_blocking(move || scan_jar(bytes)).await? {
+                Ok(it) => Some(it),
+
+                Err(err) => {
+                    warn!("Could not read JAR {path}: {err}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
         debug!("Checking for existing record...");
 
-        if self.has_file_inner(&file.path, &mut conn).await {
+        if self.has_file_inner(&file.path, conn).await {
             debug!("Deleting existing record...");
 
-            self.delete_file_inner(&file.path, &mut conn).await?;
+            self.delete_file_inner(&file.path, conn).await?;
         }
 
         debug!("Inserting into database...");
@@ -142,14 +367,205 @@ impl RouteContext {
         let result = insert_into(files::table)
             .values(file)
             .returning(MavenFile::as_returning())
-            .get_result(&mut conn)
+            .get_result(conn)
             .await?;
 
-        self.notify.notify_waiters();
+        self.record_replication_inner(UPLOAD_EVENT, &result.path, conn)
+            .await?;
+
+        self.record_webhook_inner(
+            WebhookPayload::for_file(WebhookEvent::ArtifactUploaded, &result),
+            conn,
+        )
+        .await?;
+
+        if let Some(version) = pom.as_ref().and_then(WebhookVersion::from_pom) {
+            self.record_webhook_inner(
+                WebhookPayload {
+                    version: Some(version),
+                    ..WebhookPayload::for_file(WebhookEvent::VersionPublished, &result)
+                },
+                conn,
+            )
+            .await?;
+        }
+
+        if let Some(pom) = pom {
+            debug!("Indexing POM...");
+
+            if let Err(err) = self.index_pom_inner(&result, &pom, conn).await {
+                warn!("Could not index POM {path}: {err}");
+            }
+        }
+
+        if let Some(entries) = jar {
+            debug!("Indexing JAR entries...");
+
+            if let Err(err) = self.index_jar_inner(&result, &entries, conn).await {
+                warn!("Could not index JAR {path}: {err}");
+            }
+        }
+
+        match &signed {
+            Some((kind, target)) => {
+                debug!("Linking signature...");
+
+                self.link_signature_inner(target, &result, kind, fingerprint, conn)
+                    .await?;
+            }
+
+            // Replacing a file drops its signature links, so pick them back up.
+            None => self.relink_signatures_inner(&result, bytes, conn).await?,
+        }
+
+        if signed.is_none() && is_signable(&path) && self.signs_route_inner(&path, conn).await? {
+            debug!("Signing...");
+
+            self.sign_file_inner(&result, bytes, conn).await?;
+        }
 
         Ok(result)
     }
 
+    /// Record that `signature` is a signature for `file`.
+    pub async fn link_signature_inner(
+        &self,
+        file: &MavenFile,
+        signature: &MavenFile,
+        kind: impl AsRef<str>,
+        key_fingerprint: Option<String>,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<MavenFileSignature> {
+        delete(file_signatures::table)
+            .filter(file_signatures::signature.eq(signature.id))
+            .execute(conn)
+            .await?;
+
+        Ok(insert_into(file_signatures::table)
+            .values(MavenFileSignatureIn {
+                file: file.id,
+                signature: signature.id,
+                kind: kind.as_ref().into(),
+                verified: key_fingerprint.is_some(),
+                key_fingerprint,
+            })
+            .returning(MavenFileSignature::as_returning())
+            .get_result(conn)
+            .await?)
+    }
+
+    /// Does the route a path is under want uploads signed with the server's key?
+    pub async fn signs_route_inner(
+        &self,
+        path: impl AsRef<str>,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<bool> {
+        if self.signing_key.is_none() {
+            return Ok(false);
+        }
+
+        let path = path.as_ref();
+
+        Ok(route_data::table
+            .select(RouteData::as_select())
+            .load(conn)
+            .await?
+            .into_iter()
+            .filter(|it| path.starts_with(&it.path))
+            .max_by_key(|it| it.path.len())
+            .is_some_and(|it| it.sign))
+    }
+
+    /// Sign a file with the server's key, storing the signature next to it as `.asc`.
+    /// This replaces any signature that was already there.
+    pub async fn sign_file_inner(
+        &self,
+        file: &MavenFile,
+        data: &[u8],
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<Option<MavenFile>> {
+        let Some(key) = &self.signing_key else {
+            return Ok(None);
+        };
+
+        let armored = key.sign(data)?;
+        let mut signature = MavenFileIn::new(format!("{}.asc", file.path), &armored).await?;
+
+        signature.uploader = file.uploader;
+
+        self.storage
+            .put(
+                &signature.md5.clone().into(),
+                PutPayload::from(armored.into_bytes()),
+            )
+            .await?;
+
+        if self.has_file_inner(&signature.path, conn).await {
+            self.delete_file_inner(&signature.path, conn).await?;
+        }
+
+        let signature = insert_into(files::table)
+            .values(signature)
+            .returning(MavenFile::as_returning())
+            .get_result(conn)
+            .await?;
+
+        self.record_replication_inner(UPLOAD_EVENT, &signature.path, conn)
+            .await?;
+
+        self.record_webhook_inner(
+            WebhookPayload::for_file(WebhookEvent::ArtifactUploaded, &signature),
+            conn,
+        )
+        .await?;
+
+        self.link_signature_inner(file, &signature, "pgp", Some(key.fingerprint()), conn)
+            .await?;
+
+        Ok(Some(signature))
+    }
+
+    /// Link any existing signature files to a freshly uploaded file. Signatures
+    /// that no longer match are still linked, just not marked as verified.
+    pub async fn relink_signatures_inner(
+        &self,
+        file: &MavenFile,
+        data: &[u8],
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<()> {
+        for (ext, kind) in SIGNATURE_TYPES {
+            let path = format!("{}.{ext}", file.path);
+
+            let Ok(signature) = self.get_file_inner(&path, conn).await else {
+                continue;
+            };
+
+            let fingerprint = match &self.keyring {
+                Some(keyring) if *kind == "pgp" => {
+                    let bytes = signature.get_bytes(&self.storage).await?;
+
+                    keyring.verify(&path, data, bytes).ok()
+                }
+
+                _ => None,
+            };
+
+            self.link_signature_inner(file, &signature, kind, fingerprint, conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the signatures of some files.
+    pub async fn get_signatures(&self, files: &[i32]) -> Result<Vec<MavenFileSignature>> {
+        Ok(file_signatures::table
+            .filter(file_signatures::file.eq_any(files))
+            .select(MavenFileSignature::as_select())
+            .load(&mut self.pool.get().await?)
+            .await?)
+    }
+
     pub fn get_path(&self, route: impl AsRef<str>) -> String {
         format!(
             "/{}",
@@ -1,5 +1,8 @@
+use super::models_in::MavenFileIn;
+use axum::http::HeaderMap;
 use sha1::{Digest, Sha1};
 use sha2::{Sha256, Sha512};
+use std::fmt::Display;
 
 pub const HASH_TYPES: &[&str] = &["md5", "sha1", "sha256", "sha512"];
 
@@ -30,3 +33,133 @@ pub fn get_sha512(file: impl AsRef<[u8]>) -> String {
 
     format!("{:x}", hasher.finalize())
 }
+
+/// Hashes data a chunk at a time, so big files don't have to be in memory all at once.
+pub struct FileHasher {
+    size: u64,
+    md5: md5::Context,
+    sha1: Sha1,
+    sha256: Sha256,
+    sha512: Sha512,
+}
+
+/// Everything [`FileHasher`] works out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileHashes {
+    pub size: u64,
+    pub md5: String,
+    pub sha1: String,
+    pub sha256: String,
+    pub sha512: String,
+}
+
+impl Default for FileHasher {
+    fn default() -> Self {
+        Self {
+            size: 0,
+            md5: md5::Context::new(),
+            sha1: Sha1::new(),
+            sha256: Sha256::new(),
+            sha512: Sha512::new(),
+        }
+    }
+}
+
+impl FileHasher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, data: impl AsRef<[u8]>) {
+        let data = data.as_ref();
+
+        self.size += data.len() as u64;
+        self.md5.consume(data);
+        self.sha1.update(data);
+        self.sha256.update(data);
+        self.sha512.update(data);
+    }
+
+    pub fn finish(self) -> FileHashes {
+        FileHashes {
+            size: self.size,
+            md5: format!("{:x}", self.md5.compute()),
+            sha1: format!("{:x}", self.sha1.finalize()),
+            sha256: format!("{:x}", self.sha256.finalize()),
+            sha512: format!("{:x}", self.sha512.finalize()),
+        }
+    }
+}
+
+pub const MD5_HEADER: &str = "x-checksum-md5";
+pub const SHA1_HEADER: &str = "x-checksum-sha1";
+pub const SHA256_HEADER: &str = "x-checksum-sha256";
+
+/// Returned when an upload doesn't match a checksum the client gave us.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksumMismatch {
+    pub algorithm: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl Display for ChecksumMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Checksum mismatch ({}): expected {}, got {}",
+            self.algorithm, self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for ChecksumMismatch {}
+
+/// Checksums a client expects an upload to have, from the `X-Checksum-*` headers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExpectedChecksums {
+    pub md5: Option<String>,
+    pub sha1: Option<String>,
+    pub sha256: Option<String>,
+}
+
+impl ExpectedChecksums {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let get = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|it| it.to_str().ok())
+                .map(|it| it.trim().to_lowercase())
+                .filter(|it| !it.is_empty())
+        };
+
+        Self {
+            md5: get(MD5_HEADER),
+            sha1: get(SHA1_HEADER),
+            sha256: get(SHA256_HEADER),
+        }
+    }
+
+    /// Check every given checksum against the file's actual hashes.
+    pub fn verify(&self, file: &MavenFileIn) -> Result<(), ChecksumMismatch> {
+        let checks = [
+            ("md5", &self.md5, &file.md5),
+            ("sha1", &self.sha1, &file.sha1),
+            ("sha256", &self.sha256, &file.sha256),
+        ];
+
+        for (algorithm, expected, actual) in checks {
+            if let Some(expected) = expected {
+                if !expected.eq_ignore_ascii_case(actual) {
+                    return Err(ChecksumMismatch {
+                        algorithm: algorithm.into(),
+                        expected: expected.clone(),
+                        actual: actual.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use super::models::MavenFile;
+use crate::{
+    cx::RouteContext,
+    schema::{deleted_files, files, staging_files},
+};
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use diesel::{QueryDsl, SelectableHelper};
+use diesel_async::RunQueryDsl;
+use futures_util::TryStreamExt;
+use object_store::{ObjectStore, path::Path};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GcReport {
+    /// Objects that are still used by something.
+    pub kept: usize,
+
+    /// Objects nothing uses anymore, which were deleted (or would be, on a dry run).
+    pub removed: usize,
+
+    /// How many bytes the removed objects took up.
+    pub freed: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlobProblem {
+    pub path: String,
+    pub md5: String,
+    pub problem: String,
+}
+
+impl RouteContext {
+    /// Delete stored objects that no file, staged file or deleted file points to. Objects
+    /// newer than `min_age` are left alone, since an upload in progress stores its data
+    /// before the row that points to it.
+    pub async fn collect_garbage(&self, min_age: Duration, dry_run: bool) -> Result<GcReport> {
+        let mut conn = self.pool.get().await?;
+        let mut used = HashSet::<String>::new();
+
+        used.extend(files::table.select(files::md5).load(&mut conn).await?);
+
+        used.extend(
+            staging_files::table
+                .select(staging_files::md5)
+                .load(&mut conn)
+                .await?,
+        );
+
+        used.extend(
+            deleted_files::table
+                .select(deleted_files::md5)
+                .load(&mut conn)
+                .await?,
+        );
+
+        let cutoff = Utc::now() - min_age;
+        let mut report = GcReport::default();
+        let mut objects = self.storage.list(None);
+
+        while let Some(object) = objects.try_next().await? {
+            if used.contains(object.location.as_ref()) || object.last_modified > cutoff {
+                report.kept += 1;
+                continue;
+            }
+
+            if dry_run {
+                info!("Would remove {} ({} bytes)", object.location, object.size);
+            } else {
+                debug!("Removing {}...", object.location);
+
+                self.storage.delete(&object.location).await?;
+            }
+
+            report.removed += 1;
+            report.freed += object.size;
+        }
+
+        Ok(report)
+    }
+
+    /// Check that every file's object exists and is the right size. This doesn't read
+    /// the data, so it's cheap enough to run any time.
+    pub async fn check_blobs(&self) -> Result<Vec<BlobProblem>> {
+        let files = files::table
+            .select(MavenFile::as_select())
+            .load(&mut self.pool.get().await?)
+            .await?;
+
+        let mut problems = Vec::new();
+
+        for file in files {
+            let problem = match self.storage.head(&Path::from(file.md5.as_str())).await {
+                Ok(meta) if meta.size != file.size as u64 => Some(format!(
+                    "Size mismatch: expected {} bytes, found {}",
+                    file.size, meta.size
+                )),
+
+                Ok(_) => None,
+                Err(object_store::Error::NotFound { .. }) => Some("Missing from storage".into()),
+                Err(err) => Some(format!("Could not check storage: {err}")),
+            };
+
+            if let Some(problem) = problem {
+                problems.push(BlobProblem {
+                    path: file.path,
+                    md5: file.md5,
+                    problem,
+                });
+            }
+        }
+
+        Ok(problems)
+    }
+
+    /// Index POMs and JARs that haven't been yet. With `all`, everything gets parsed
+    /// again (including files that failed before), replacing the index file by file.
+    pub async fn reindex(&self, all: bool) -> Result<(usize, usize)> {
+        Ok((self.index_poms(all).await?, self.index_jars(all).await?))
+    }
+}
@@ -1,6 +1,10 @@
 pub mod cx;
 pub mod hashes;
 pub mod index;
+pub mod maintenance;
 pub mod models;
 pub mod models_in;
+pub mod rules;
+pub mod signatures;
+pub mod signing;
 pub mod types;
@@ -1,3 +1,4 @@
+use crate::err::MavenError;
 use anyhow::{Result, anyhow};
 use chrono::NaiveDateTime;
 use object_store::ObjectStore;
@@ -16,6 +17,17 @@ pub struct MavenFile {
     pub sha256: String,
     pub sha512: String,
     pub kind: String,
+
+    /// The token that uploaded this, if it's known. Counts towards that token's quota.
+    #[serde(skip)]
+    pub uploader: Option<i32>,
+
+    /// Whether this failed an integrity check, and shouldn't be served.
+    pub quarantined: bool,
+
+    /// When this was written here. Imports keep the source's `uploaded` time, but not
+    /// this.
+    pub stored: NaiveDateTime,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Insertable, Identifiable, Queryable, Selectable)]
@@ -34,6 +46,33 @@ pub struct DeletedMavenFile {
     pub kind: String,
 }
 
+#[derive(
+    Debug,
+    Clone,
+    Serialize,
+    Deserialize,
+    Insertable,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Associations,
+)]
+#[diesel(table_name = crate::schema::file_signatures)]
+#[diesel(belongs_to(MavenFile, foreign_key = file))]
+pub struct MavenFileSignature {
+    pub id: i32,
+
+    /// The signed file.
+    pub file: i32,
+
+    /// The signature file itself.
+    pub signature: i32,
+    pub kind: String,
+    pub key_fingerprint: Option<String>,
+    pub verified: bool,
+    pub created: NaiveDateTime,
+}
+
 impl MavenFile {
     pub async fn get_bytes<S: ObjectStore>(&self, store: &Arc<S>) -> Result<Vec<u8>> {
         Ok(store
@@ -74,17 +113,22 @@ impl MavenFile {
         } else if path == format!("{}.sha512", self.path) {
             Ok(self.sha512.as_bytes().to_vec())
         } else if path == self.path {
+            // Checksums come from the database, so they're still fine to hand out.
+            if self.quarantined {
+                return Err(MavenError::Unavailable(
+                    "This file failed an integrity check and has been quarantined.".into(),
+                )
+                .into());
+            }
+
             Ok(self.get_bytes(store).await?)
         } else {
-            Err(anyhow!("404 Not Found"))
+            Err(MavenError::not_found().into())
         }
     }
 
-    pub async fn get_size<S: ObjectStore>(
-        &self,
-        path: impl AsRef<str>,
-        store: &Arc<S>,
-    ) -> Result<u64> {
+    /// Get the size of a route from the stored metadata, without touching the object store.
+    pub fn get_stored_size(&self, path: impl AsRef<str>) -> Result<u64> {
         let path = path.as_ref();
 
         if path == format!("{}.md5", self.path) {
@@ -96,9 +140,9 @@ impl MavenFile {
         } else if path == format!("{}.sha512", self.path) {
             Ok(self.sha512.len() as u64)
         } else if path == self.path {
-            Ok(self.get_content_size(store).await?)
+            Ok(self.size as u64)
         } else {
-            Err(anyhow!("404 Not Found"))
+            Err(MavenError::not_found().into())
         }
     }
 
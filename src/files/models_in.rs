@@ -15,6 +15,7 @@ pub struct MavenFileIn {
     pub sha256: String,
     pub sha512: String,
     pub kind: String,
+    pub uploader: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
@@ -30,6 +31,16 @@ pub struct DeletedMavenFileIn {
     pub kind: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::file_signatures)]
+pub struct MavenFileSignatureIn {
+    pub file: i32,
+    pub signature: i32,
+    pub kind: String,
+    pub key_fingerprint: Option<String>,
+    pub verified: bool,
+}
+
 impl MavenFileIn {
     pub async fn new(path: impl AsRef<str>, bytes: impl AsRef<[u8]>) -> Result<Self> {
         let bytes = bytes.as_ref();
@@ -61,6 +72,7 @@ impl MavenFileIn {
             sha256: sha256_task.await?,
             sha512: sha512_task.await?,
             kind,
+            uploader: None,
         })
     }
 }
@@ -0,0 +1,283 @@
+//! Per-route rules that uploads have to pass before they're accepted.
+
+use super::signatures::signature_target;
+use crate::{
+    cx::RouteContext,
+    maven::{coords::Coordinates, pom::Pom},
+    router::models::RouteRules,
+    schema::route_rules,
+};
+use anyhow::Result;
+use diesel::{QueryDsl, SelectableHelper, pg::Pg};
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use itertools::Itertools;
+use regex::Regex;
+use std::fmt::Display;
+
+/// Returned when an upload breaks one of its route's rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    pub path: String,
+    pub message: String,
+}
+
+impl ValidationError {
+    pub fn new(path: impl AsRef<str>, message: impl AsRef<str>) -> Self {
+        Self {
+            path: path.as_ref().into(),
+            message: message.as_ref().into(),
+        }
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Rejected {}: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// If a path is a file belonging to some artifact version (rather than metadata or
+/// anything else), get that version's coordinates.
+pub fn artifact_coordinates(path: impl AsRef<str>) -> Option<Coordinates> {
+    let path = path.as_ref();
+    let (folder, name) = path.rsplit_once('/')?;
+    let coords = Coordinates::from_version_folder(folder)?;
+
+    name.starts_with(&format!("{}-", coords.artifact))
+        .then_some(coords)
+}
+
+fn split_list(list: &Option<String>) -> Vec<String> {
+    list.as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(|it| it.trim().trim_start_matches('.').to_lowercase())
+        .filter(|it| !it.is_empty())
+        .collect()
+}
+
+impl RouteRules {
+    pub fn classifiers(&self) -> Vec<String> {
+        split_list(&self.required_classifiers)
+    }
+
+    pub fn extensions(&self) -> Vec<String> {
+        split_list(&self.forbidden_extensions)
+    }
+
+    /// Compile the version pattern, anchored so it has to match the whole version.
+    pub fn version_regex(&self) -> Option<Result<Regex, regex::Error>> {
+        self.version_pattern
+            .as_ref()
+            .map(|it| Regex::new(&format!("^(?:{it})$")))
+    }
+
+    /// Check a single upload. `bytes` is only looked at for POMs.
+    pub fn check_upload(&self, path: &str, bytes: &[u8]) -> Result<(), ValidationError> {
+        let name = path.rsplit('/').next().unwrap_or(path).to_lowercase();
+
+        if let Some(ext) = self
+            .extensions()
+            .into_iter()
+            .find(|it| name.ends_with(&format!(".{it}")))
+        {
+            return Err(ValidationError::new(
+                path,
+                format!("`.{ext}` files aren't allowed here"),
+            ));
+        }
+
+        // Uploads over HTTP are turned away (with a 413) before they get this far, so
+        // this only catches ones that don't come through a request, like imports.
+        if let Some(max) = self.max_size {
+            if bytes.len() as i64 > max {
+                return Err(ValidationError::new(
+                    path,
+                    format!("{} bytes is over the limit of {max} bytes", bytes.len()),
+                ));
+            }
+        }
+
+        // Signatures are named after what they sign, so the checks below already
+        // happened (or will) on the real file.
+        if signature_target(path).is_some() {
+            return Ok(());
+        }
+
+        let coords = artifact_coordinates(path);
+
+        if let (Some(regex), Some(coords)) = (self.version_regex(), &coords) {
+            let regex = regex.map_err(|err| {
+                ValidationError::new(path, format!("Invalid version pattern: {err}"))
+            })?;
+
+            if !regex.is_match(&coords.version) {
+                return Err(ValidationError::new(
+                    path,
+                    format!(
+                        "version {} doesn't match `{}`",
+                        coords.version,
+                        self.version_pattern.as_deref().unwrap_or_default()
+                    ),
+                ));
+            }
+        }
+
+        if self.require_pom && path.ends_with(".pom") {
+            let Some(coords) = coords else {
+                return Err(ValidationError::new(
+                    path,
+                    "POMs must be in a version folder",
+                ));
+            };
+
+            let pom = Pom::parse(bytes)
+                .map_err(|err| ValidationError::new(path, format!("Invalid POM: {err}")))?;
+
+            let declared = Coordinates::new(
+                pom.interpolate(pom.group_id().unwrap_or_default()),
+                pom.interpolate(&pom.artifact_id),
+                pom.interpolate(pom.version().unwrap_or_default()),
+            );
+
+            if declared != coords {
+                return Err(ValidationError::new(
+                    path,
+                    format!("POM is for {declared}, but its path is for {coords}"),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check a version folder that's about to be promoted, given the names of the
+    /// files it'll have.
+    pub fn check_version(&self, folder: &str, names: &[&str]) -> Vec<ValidationError> {
+        let Some(coords) = Coordinates::from_version_folder(folder) else {
+            return Vec::new();
+        };
+
+        let mut problems = Vec::new();
+
+        // POM-only versions (parents, BOMs) don't have anything to attach sources to.
+        if !names.iter().any(|it| it.ends_with(".jar")) {
+            return problems;
+        }
+
+        let prefix = format!("{}-", coords.artifact);
+
+        for classifier in self.classifiers() {
+            let suffix = format!("-{classifier}.jar");
+
+            if !names
+                .iter()
+                .any(|it| it.starts_with(&prefix) && it.ends_with(&suffix))
+            {
+                problems.push(ValidationError::new(
+                    folder,
+                    format!("missing the `{classifier}` JAR"),
+                ));
+            }
+        }
+
+        problems
+    }
+}
+
+impl RouteContext {
+    /// Get the rules for the most specific route a path is under.
+    pub async fn get_route_rules_inner(
+        &self,
+        path: impl AsRef<str>,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<Option<RouteRules>> {
+        let path = path.as_ref();
+
+        Ok(route_rules::table
+            .select(RouteRules::as_select())
+            .load(conn)
+            .await?
+            .into_iter()
+            .filter(|it| path.starts_with(&it.path))
+            .max_by_key(|it| it.path.len()))
+    }
+
+    /// Check an upload against its route's rules, if it has any.
+    pub async fn check_rules_inner(
+        &self,
+        path: impl AsRef<str>,
+        bytes: &[u8],
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<()> {
+        let path = path.as_ref();
+
+        if let Some(rules) = self.get_route_rules_inner(path, conn).await? {
+            rules.check_upload(path, bytes)?;
+        }
+
+        Ok(())
+    }
+
+    /// With `require_pom`, `maven-metadata.xml` can't be written while a version it
+    /// covers is missing its POM. Clients write that last, so the rest of a version can
+    /// go up in any order. Staged uploads skip this, since promotion checks each
+    /// version as a whole.
+    pub async fn check_pom_present_inner(
+        &self,
+        path: impl AsRef<str>,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<()> {
+        let path = path.as_ref();
+
+        let Some(folder) = path
+            .strip_suffix("maven-metadata.xml")
+            .filter(|it| it.ends_with('/'))
+        else {
+            return Ok(());
+        };
+
+        let Some(rules) = self.get_route_rules_inner(path, conn).await? else {
+            return Ok(());
+        };
+
+        if !rules.require_pom {
+            return Ok(());
+        }
+
+        // Artifact metadata sits just above its versions, and snapshot metadata sits in
+        // the version itself.
+        let missing = self
+            .get_files_under_inner(folder, conn)
+            .await?
+            .into_iter()
+            .filter(|it| {
+                it.parent
+                    .strip_prefix(folder)
+                    .is_some_and(|it| !it.trim_end_matches('/').contains('/'))
+            })
+            .into_group_map_by(|it| it.parent.clone())
+            .into_iter()
+            .filter(|(_, files)| {
+                files
+                    .iter()
+                    .any(|it| artifact_coordinates(&it.path).is_some())
+                    && !files.iter().any(|it| it.path.ends_with(".pom"))
+            })
+            .map(|(folder, _)| folder)
+            .sorted()
+            .collect_vec();
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        Err(ValidationError::new(
+            path,
+            format!("no POM in {}, so upload that first", missing.join(", ")),
+        )
+        .into())
+    }
+}
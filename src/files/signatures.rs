@@ -0,0 +1,127 @@
+//! Detached signatures (`.asc` from `maven-gpg-plugin`, and `.sigstore` bundles) and
+//! verifying them against a set of trusted public keys.
+
+use anyhow::Result;
+use pgp::{Deserializable, SignedPublicKey, StandaloneSignature, types::PublicKeyTrait};
+use std::{fmt::Display, fs, io::Cursor, path::Path};
+
+/// Signature file extensions, and the kind of signature they hold.
+pub const SIGNATURE_TYPES: &[(&str, &str)] = &[("asc", "pgp"), ("sigstore", "sigstore")];
+
+/// If a path is a signature, get its kind and the path of the file it signs.
+pub fn signature_target(path: &str) -> Option<(&'static str, &str)> {
+    SIGNATURE_TYPES.iter().find_map(|(ext, kind)| {
+        path.strip_suffix(&format!(".{ext}"))
+            .map(|target| (*kind, target))
+    })
+}
+
+/// Returned when a signature can't be verified against the trusted keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureError {
+    pub path: String,
+    pub reason: String,
+}
+
+impl SignatureError {
+    pub fn new(path: impl AsRef<str>, reason: impl AsRef<str>) -> Self {
+        Self {
+            path: path.as_ref().into(),
+            reason: reason.as_ref().into(),
+        }
+    }
+}
+
+impl Display for SignatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Bad signature {}: {}", self.path, self.reason)
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+/// A set of trusted OpenPGP public keys.
+#[derive(Debug, Clone, Default)]
+pub struct Keyring {
+    keys: Vec<SignedPublicKey>,
+}
+
+impl Keyring {
+    /// Load keys from a file, or from every file in a directory. Both armored and
+    /// binary keyrings work.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut keys = Vec::new();
+
+        let files = if path.is_dir() {
+            fs::read_dir(path)?
+                .map(|it| Ok(it?.path()))
+                .collect::<Result<Vec<_>>>()?
+                .into_iter()
+                .filter(|it| it.is_file())
+                .collect()
+        } else {
+            vec![path.to_path_buf()]
+        };
+
+        for file in files {
+            let bytes = fs::read(&file)?;
+
+            let parsed: Vec<_> = if bytes.starts_with(b"-----BEGIN") {
+                SignedPublicKey::from_armor_many(Cursor::new(bytes))?
+                    .0
+                    .collect()
+            } else {
+                SignedPublicKey::from_bytes_many(Cursor::new(bytes)).collect()
+            };
+
+            for key in parsed {
+                let key = key?;
+
+                key.verify()?;
+                keys.push(key);
+            }
+        }
+
+        Ok(Self { keys })
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Verify an armored detached signature over some data. On success, this returns
+    /// the fingerprint of the (primary) key that made it.
+    pub fn verify(
+        &self,
+        path: impl AsRef<str>,
+        data: impl AsRef<[u8]>,
+        signature: impl AsRef<[u8]>,
+    ) -> Result<String, SignatureError> {
+        let path = path.as_ref();
+        let data = data.as_ref();
+
+        let (signature, _) = StandaloneSignature::from_armor_single(Cursor::new(signature))
+            .map_err(|err| {
+                SignatureError::new(path, format!("Could not parse signature: {err}"))
+            })?;
+
+        for key in &self.keys {
+            let signed = signature.verify(key, data).is_ok()
+                || key
+                    .public_subkeys
+                    .iter()
+                    .any(|sub| signature.verify(sub, data).is_ok());
+
+            if signed {
+                return Ok(hex::encode_upper(key.fingerprint().as_bytes()));
+            }
+        }
+
+        Err(SignatureError::new(path, "Not signed by a trusted key"))
+    }
+}
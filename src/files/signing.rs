@@ -0,0 +1,86 @@
+//! Signing uploads with a key held by the server, for routes that are set up for it.
+
+use super::signatures::signature_target;
+use anyhow::Result;
+use chrono::{SubsecRound, Utc};
+use pgp::{
+    ArmorOptions, Deserializable, SignedPublicKey, SignedSecretKey, StandaloneSignature,
+    crypto::hash::HashAlgorithm,
+    packet::{SignatureConfig, SignatureType, SignatureVersion, Subpacket, SubpacketData},
+    types::{PublicKeyTrait, SecretKeyTrait},
+};
+use std::{fs, io::Cursor, path::Path};
+
+/// Files that never get a server signature. Maven doesn't sign its metadata.
+pub const UNSIGNED_FILES: &[&str] = &["maven-metadata.xml"];
+
+#[derive(Clone)]
+pub struct SigningKey {
+    key: SignedSecretKey,
+    password: String,
+
+    /// The armored public half of the key, as published at `/api/signing-key`.
+    public: String,
+}
+
+impl SigningKey {
+    /// Load a secret key from a file. Both armored and binary keys work.
+    pub fn load(path: impl AsRef<Path>, password: Option<String>) -> Result<Self> {
+        let bytes = fs::read(path)?;
+
+        let key = if bytes.starts_with(b"-----BEGIN") {
+            SignedSecretKey::from_armor_single(Cursor::new(bytes))?.0
+        } else {
+            SignedSecretKey::from_bytes(Cursor::new(bytes))?
+        };
+
+        key.verify()?;
+
+        let public =
+            SignedPublicKey::from(key.clone()).to_armored_string(ArmorOptions::default())?;
+
+        Ok(Self {
+            key,
+            password: password.unwrap_or_default(),
+            public,
+        })
+    }
+
+    pub fn fingerprint(&self) -> String {
+        hex::encode_upper(self.key.fingerprint().as_bytes())
+    }
+
+    pub fn public_key(&self) -> &str {
+        &self.public
+    }
+
+    /// Make an armored detached signature over some data, like `gpg --armor --detach-sign`.
+    pub fn sign(&self, data: impl AsRef<[u8]>) -> Result<String> {
+        let config = SignatureConfig::new_v4(
+            SignatureVersion::V4,
+            SignatureType::Binary,
+            self.key.algorithm(),
+            HashAlgorithm::SHA2_256,
+            vec![
+                Subpacket::regular(SubpacketData::SignatureCreationTime(
+                    Utc::now().trunc_subsecs(0),
+                )),
+                Subpacket::regular(SubpacketData::Issuer(self.key.key_id())),
+            ],
+            Vec::new(),
+        );
+
+        let signature = config.sign(&self.key, || self.password.clone(), data.as_ref())?;
+
+        Ok(StandaloneSignature::new(signature).to_armored_string(ArmorOptions::default())?)
+    }
+}
+
+/// Should this path get a server signature (if its route asks for one)? Signatures
+/// never do, even ones that got here before the file they sign.
+pub fn is_signable(path: impl AsRef<str>) -> bool {
+    let path = path.as_ref();
+    let name = path.rsplit('/').next().unwrap_or(path);
+
+    !UNSIGNED_FILES.contains(&name) && signature_target(path).is_none()
+}
@@ -0,0 +1,254 @@
+//! HTTPS for the server itself (as opposed to `tls`, which is for connecting to the
+//! database): certificates, reloading them, client certificates, and redirecting plain
+//! HTTP.
+
+use anyhow::{Result, anyhow};
+use axum::{
+    Extension, Router,
+    http::{
+        HeaderMap, StatusCode, Uri,
+        header::{HOST, LOCATION},
+    },
+    middleware::AddExtension,
+    response::{IntoResponse, Response},
+};
+use axum_server::{
+    Handle,
+    accept::Accept,
+    tls_rustls::{RustlsAcceptor, RustlsConfig},
+};
+use futures_util::future::BoxFuture;
+use rustls::{
+    RootCertStore, ServerConfig,
+    pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject},
+    server::WebPkiClientVerifier,
+};
+use sha2::{Digest, Sha256};
+use std::{
+    fs, io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tower::Layer;
+
+/// How often certificate files are checked for changes.
+const RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsOptions {
+    /// The certificate chain, as PEM.
+    pub cert: PathBuf,
+
+    /// The private key, as PEM.
+    pub key: PathBuf,
+
+    /// CA certificates that client certificates are checked against. Setting this turns
+    /// on client certificate auth, but clients without one can still use tokens.
+    pub client_ca: Option<PathBuf>,
+
+    /// A port to listen for plain HTTP on, just to redirect it to HTTPS.
+    pub redirect_port: Option<u16>,
+}
+
+impl TlsOptions {
+    fn paths(&self) -> Vec<&Path> {
+        [Some(&self.cert), Some(&self.key), self.client_ca.as_ref()]
+            .into_iter()
+            .flatten()
+            .map(PathBuf::as_path)
+            .collect()
+    }
+
+    fn modified(&self) -> Vec<Option<SystemTime>> {
+        self.paths()
+            .into_iter()
+            .map(|it| fs::metadata(it).and_then(|it| it.modified()).ok())
+            .collect()
+    }
+
+    pub fn server_config(&self) -> Result<Arc<ServerConfig>> {
+        let certs = CertificateDer::pem_file_iter(&self.cert)?.collect::<Result<Vec<_>, _>>()?;
+        let key = PrivateKeyDer::from_pem_file(&self.key)?;
+
+        let builder = match &self.client_ca {
+            Some(path) => {
+                let mut roots = RootCertStore::empty();
+
+                for cert in CertificateDer::pem_file_iter(path)? {
+                    roots.add(cert?)?;
+                }
+
+                let verifier = WebPkiClientVerifier::builder(Arc::new(roots))
+                    .allow_unauthenticated()
+                    .build()?;
+
+                ServerConfig::builder().with_client_cert_verifier(verifier)
+            }
+
+            None => ServerConfig::builder().with_no_client_auth(),
+        };
+
+        let mut config = builder.with_single_cert(certs, key)?;
+
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+        Ok(Arc::new(config))
+    }
+}
+
+/// Get the fingerprint client certificates are matched to tokens by.
+pub fn certificate_fingerprint(der: impl AsRef<[u8]>) -> String {
+    hex::encode(Sha256::digest(der.as_ref()))
+}
+
+/// Get the fingerprint of a PEM certificate.
+pub fn pem_fingerprint(pem: impl AsRef<str>) -> Result<String> {
+    let cert = CertificateDer::from_pem_slice(pem.as_ref().as_bytes())
+        .map_err(|err| anyhow!("Invalid certificate: {err}"))?;
+
+    Ok(certificate_fingerprint(cert))
+}
+
+/// The verified certificate a connection was made with. Every request gets one of these
+/// (as `Option<ClientCertificate>`) when serving HTTPS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientCertificate {
+    pub fingerprint: String,
+}
+
+/// Terminates TLS, and tags each connection's requests with its client certificate.
+#[derive(Clone)]
+pub struct ClientCertAcceptor {
+    inner: RustlsAcceptor,
+}
+
+impl ClientCertAcceptor {
+    pub fn new(config: RustlsConfig) -> Self {
+        Self {
+            inner: RustlsAcceptor::new(config),
+        }
+    }
+}
+
+impl<I, S> Accept<I, S> for ClientCertAcceptor
+where
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    S: Send + 'static,
+{
+    type Stream = <RustlsAcceptor as Accept<I, S>>::Stream;
+    type Service = AddExtension<S, Option<ClientCertificate>>;
+    type Future = BoxFuture<'static, io::Result<(Self::Stream, Self::Service)>>;
+
+    fn accept(&self, stream: I, service: S) -> Self::Future {
+        let acceptor = self.inner.clone();
+
+        Box::pin(async move {
+            let (stream, service) = acceptor.accept(stream, service).await?;
+
+            let cert = stream
+                .get_ref()
+                .1
+                .peer_certificates()
+                .and_then(|it| it.first())
+                .map(|it| ClientCertificate {
+                    fingerprint: certificate_fingerprint(it),
+                });
+
+            Ok((stream, Extension(cert).layer(service)))
+        })
+    }
+}
+
+/// Reload certificates when the process gets a SIGHUP, or when their files change.
+/// If the new ones don't load, the old ones stay in use.
+pub fn watch_certificates(options: TlsOptions, config: RustlsConfig) {
+    tokio::task::spawn(async move {
+        #[cfg(unix)]
+        let mut hangup =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()).ok();
+
+        let mut interval = tokio::time::interval(RELOAD_CHECK_INTERVAL);
+        let mut modified = options.modified();
+
+        loop {
+            #[cfg(unix)]
+            let signal = async {
+                match &mut hangup {
+                    Some(it) => it.recv().await,
+                    None => std::future::pending().await,
+                }
+            };
+
+            #[cfg(not(unix))]
+            let signal = std::future::pending::<Option<()>>();
+
+            let reason = tokio::select! {
+                _ = signal => "SIGHUP",
+
+                _ = interval.tick() => {
+                    if options.modified() == modified {
+                        continue;
+                    }
+
+                    "a file change"
+                }
+            };
+
+            modified = options.modified();
+
+            match options.server_config() {
+                Ok(it) => {
+                    config.reload_from_config(it);
+                    info!("Reloaded TLS certificates after {reason}!");
+                }
+
+                Err(err) => warn!("Could not reload TLS certificates: {err}"),
+            }
+        }
+    });
+}
+
+async fn redirect(https_port: u16, uri: Uri, headers: HeaderMap) -> Response {
+    let Some(host) = headers
+        .get(HOST)
+        .and_then(|it| it.to_str().ok())
+        .or(uri.host())
+    else {
+        return (StatusCode::BAD_REQUEST, "A Host header is required!").into_response();
+    };
+
+    // Drop any port, taking care not to chop up IPv6 addresses.
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if !port.contains(']') => name,
+        _ => host,
+    };
+
+    let port = match https_port {
+        443 => String::new(),
+        it => format!(":{it}"),
+    };
+
+    let path = uri.path_and_query().map(|it| it.as_str()).unwrap_or("/");
+
+    Response::builder()
+        .status(StatusCode::PERMANENT_REDIRECT)
+        .header(LOCATION, format!("https://{host}{port}{path}"))
+        .body("Redirecting to HTTPS...".into())
+        .unwrap()
+}
+
+/// Listen for plain HTTP, and send everything to HTTPS.
+pub async fn serve_redirect(addr: SocketAddr, https_port: u16, handle: Handle) -> Result<()> {
+    let app = Router::new()
+        .fallback(move |uri: Uri, headers: HeaderMap| redirect(https_port, uri, headers));
+
+    axum_server::bind(addr)
+        .handle(handle)
+        .serve(app.into_make_service())
+        .await?;
+
+    Ok(())
+}
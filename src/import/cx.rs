@@ -0,0 +1,203 @@
+use super::{
+    models::{ImportJob, ImportJobIn},
+    source::{ImportSource, SourceFile},
+};
+use crate::{
+    cx::RouteContext,
+    files::hashes::{ExpectedChecksums, get_md5},
+    schema::{files, import_jobs},
+};
+use anyhow::Result;
+use chrono::Utc;
+use diesel::{ExpressionMethods, QueryDsl, SelectableHelper, insert_into, update};
+use diesel_async::RunQueryDsl;
+use itertools::Itertools;
+use reqwest::Client;
+
+/// How many files to import between progress updates.
+const PROGRESS_EVERY: usize = 50;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ImportCounts {
+    found: usize,
+    imported: usize,
+    skipped: usize,
+    failed: usize,
+}
+
+enum Outcome {
+    Imported,
+    Skipped,
+}
+
+impl RouteContext {
+    /// Record a new import, so it shows up before it starts doing anything.
+    pub async fn create_import(
+        &self,
+        source: &ImportSource,
+        prefix: impl AsRef<str>,
+    ) -> Result<ImportJob> {
+        let prefix = format!("/{}/", prefix.as_ref().trim_matches('/')).replace("//", "/");
+
+        Ok(insert_into(import_jobs::table)
+            .values(ImportJobIn {
+                source: source.to_string(),
+                prefix,
+            })
+            .returning(ImportJob::as_returning())
+            .get_result(&mut self.pool.get().await?)
+            .await?)
+    }
+
+    pub async fn get_imports(&self) -> Result<Vec<ImportJob>> {
+        Ok(import_jobs::table
+            .select(ImportJob::as_select())
+            .order_by(import_jobs::started.desc())
+            .limit(50)
+            .load(&mut self.pool.get().await?)
+            .await?)
+    }
+
+    /// Copy everything from a source into the repository, under the job's prefix. Files
+    /// that are already here with the same content are skipped, so running an import
+    /// again picks up where it stopped.
+    pub async fn run_import(&self, job: ImportJob, source: ImportSource) -> Result<ImportJob> {
+        info!("Importing from {source} into {}...", job.prefix);
+
+        let client = Client::builder()
+            .user_agent(concat!("mvn/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+
+        let mut counts = ImportCounts::default();
+        let res = self.import_files(&job, &source, &client, &mut counts).await;
+
+        let error = match res {
+            Ok(()) => {
+                info!(
+                    "Imported {} file(s) from {source}, skipped {}, {} failed!",
+                    counts.imported, counts.skipped, counts.failed
+                );
+
+                None
+            }
+
+            Err(err) => {
+                warn!("Import from {source} failed: {err}");
+
+                Some(err.to_string())
+            }
+        };
+
+        self.update_import(job.id, counts, true, error).await
+    }
+
+    async fn import_files(
+        &self,
+        job: &ImportJob,
+        source: &ImportSource,
+        client: &Client,
+        counts: &mut ImportCounts,
+    ) -> Result<()> {
+        let files = source.list(client).await?;
+
+        counts.found = files.len();
+
+        info!("Found {} file(s) to import!", counts.found);
+
+        self.update_import(job.id, *counts, false, None).await?;
+
+        // POMs go first, since routes that require them won't take metadata for a
+        // version without one.
+        for (i, file) in files
+            .iter()
+            .sorted_by_key(|it| !it.path.ends_with(".pom"))
+            .enumerate()
+        {
+            match self.import_file(&job.prefix, source, client, file).await {
+                Ok(Outcome::Imported) => counts.imported += 1,
+                Ok(Outcome::Skipped) => counts.skipped += 1,
+
+                Err(err) => {
+                    warn!("Could not import {}: {err}", file.path);
+                    counts.failed += 1;
+                }
+            }
+
+            if (i + 1) % PROGRESS_EVERY == 0 {
+                info!("Imported {}/{} file(s)...", i + 1, counts.found);
+
+                self.update_import(job.id, *counts, false, None).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn import_file(
+        &self,
+        prefix: &str,
+        source: &ImportSource,
+        client: &Client,
+        file: &SourceFile,
+    ) -> Result<Outcome> {
+        let path = format!("{prefix}{}", file.path);
+        let existing = self.get_file(&path).await.ok();
+        let sha1 = source.sha1(client, file).await.unwrap_or_default();
+
+        // The source's checksum is enough to tell it's the same, without downloading it.
+        if let (Some(existing), Some(sha1)) = (&existing, &sha1) {
+            if existing.sha1.eq_ignore_ascii_case(sha1) {
+                return Ok(Outcome::Skipped);
+            }
+        }
+
+        let (bytes, modified) = source.read(client, file).await?;
+
+        if let Some(existing) = &existing {
+            if existing.md5 == get_md5(&bytes) {
+                return Ok(Outcome::Skipped);
+            }
+        }
+
+        let expected = ExpectedChecksums {
+            sha1,
+            ..Default::default()
+        };
+
+        debug!("Importing {path}...");
+
+        let uploaded = self.upload(&path, &bytes, &expected, None).await?;
+
+        if let Some(modified) = modified {
+            update(files::table)
+                .filter(files::id.eq(uploaded.id))
+                .set(files::uploaded.eq(modified))
+                .execute(&mut self.pool.get().await?)
+                .await?;
+        }
+
+        Ok(Outcome::Imported)
+    }
+
+    async fn update_import(
+        &self,
+        id: i32,
+        counts: ImportCounts,
+        finished: bool,
+        error: Option<String>,
+    ) -> Result<ImportJob> {
+        Ok(update(import_jobs::table)
+            .filter(import_jobs::id.eq(id))
+            .set((
+                import_jobs::found.eq(counts.found as i32),
+                import_jobs::imported.eq(counts.imported as i32),
+                import_jobs::skipped.eq(counts.skipped as i32),
+                import_jobs::failed.eq(counts.failed as i32),
+                import_jobs::finished.eq(finished.then(|| Utc::now().naive_utc())),
+                import_jobs::error.eq(error),
+            ))
+            .returning(ImportJob::as_returning())
+            .get_result(&mut self.pool.get().await?)
+            .await?)
+    }
+}
@@ -0,0 +1,31 @@
+use chrono::NaiveDateTime;
+
+/// One run of an import. Running the same import again picks up where it left off,
+/// since files that are already here get skipped.
+#[derive(Debug, Clone, Serialize, Deserialize, Identifiable, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::import_jobs)]
+pub struct ImportJob {
+    pub id: i32,
+    pub source: String,
+    pub prefix: String,
+    pub started: NaiveDateTime,
+
+    /// Still running (or killed part way through) if this isn't set.
+    pub finished: Option<NaiveDateTime>,
+    pub found: i32,
+    pub imported: i32,
+
+    /// Files that were already here, with the same content.
+    pub skipped: i32,
+    pub failed: i32,
+
+    /// Why the job stopped early, if it did.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::import_jobs)]
+pub struct ImportJobIn {
+    pub source: String,
+    pub prefix: String,
+}
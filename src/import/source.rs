@@ -0,0 +1,282 @@
+use crate::files::hashes::HASH_TYPES;
+use anyhow::{Result, anyhow, bail};
+use axum::body::Bytes;
+use chrono::{DateTime, NaiveDateTime};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::{Client, header::LAST_MODIFIED};
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use url::Url;
+
+static HREF: Lazy<Regex> = Lazy::new(|| Regex::new(r#"href\s*=\s*["']([^"'#?]+)["']"#).unwrap());
+
+/// Files that tools leave in a repository, which aren't part of it.
+const IGNORED_NAMES: &[&str] = &[
+    "_remote.repositories",
+    "resolver-status.properties",
+    "maven-metadata-local.xml",
+];
+
+/// Where an import reads files from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportSource {
+    /// A local repository layout, like `~/.m2/repository` or a Nexus/Reposilite storage
+    /// directory.
+    Dir(PathBuf),
+
+    /// Another maven server, crawled through its directory listings.
+    Remote(Url),
+}
+
+/// A file found in a source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFile {
+    /// Relative to the source's root, without a leading `/`.
+    pub path: String,
+
+    /// When the file was last changed, if the source says.
+    pub modified: Option<NaiveDateTime>,
+
+    /// Whether there's a `.sha1` next to it, so it can be compared without downloading.
+    pub has_sha1: bool,
+}
+
+impl FromStr for ImportSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s.starts_with("http://") || s.starts_with("https://") {
+            let mut url = Url::parse(s)?;
+
+            if !url.path().ends_with('/') {
+                url.set_path(&format!("{}/", url.path()));
+            }
+
+            Ok(Self::Remote(url))
+        } else {
+            let path = PathBuf::from(s);
+
+            if !path.is_dir() {
+                bail!("{s} isn't a directory!");
+            }
+
+            Ok(Self::Dir(path))
+        }
+    }
+}
+
+impl Display for ImportSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Dir(it) => write!(f, "{}", it.display()),
+            Self::Remote(it) => write!(f, "{it}"),
+        }
+    }
+}
+
+fn is_ignored(name: &str) -> bool {
+    name.starts_with('.')
+        || name.ends_with(".lastUpdated")
+        || IGNORED_NAMES.contains(&name)
+        || HASH_TYPES
+            .iter()
+            .any(|it| name.ends_with(&format!(".{it}")))
+}
+
+impl ImportSource {
+    /// Find every file in the source. Checksum files and tool leftovers are left out.
+    pub async fn list(&self, client: &Client) -> Result<Vec<SourceFile>> {
+        let mut files = match self {
+            Self::Dir(root) => {
+                let root = root.clone();
+
+                tokio::task::spawn_blocking(move || list_dir(&root)).await??
+            }
+
+            Self::Remote(root) => crawl(client, root).await?,
+        };
+
+        // Signatures are checked against the file they sign, so that has to be here first.
+        files.sort_by_cached_key(|it| (it.path.ends_with(".asc"), it.path.clone()));
+
+        Ok(files)
+    }
+
+    /// Read a file, and when it was last changed if we didn't know already.
+    pub async fn read(
+        &self,
+        client: &Client,
+        file: &SourceFile,
+    ) -> Result<(Bytes, Option<NaiveDateTime>)> {
+        match self {
+            Self::Dir(root) => Ok((
+                tokio::fs::read(root.join(&file.path)).await?.into(),
+                file.modified,
+            )),
+
+            Self::Remote(root) => {
+                let res = client
+                    .get(root.join(&file.path)?)
+                    .send()
+                    .await?
+                    .error_for_status()?;
+
+                let modified = file.modified.or_else(|| {
+                    res.headers()
+                        .get(LAST_MODIFIED)
+                        .and_then(|it| it.to_str().ok())
+                        .and_then(|it| DateTime::parse_from_rfc2822(it).ok())
+                        .map(|it| it.naive_utc())
+                });
+
+                Ok((res.bytes().await?, modified))
+            }
+        }
+    }
+
+    /// The SHA-1 the source has next to a file, if it has one.
+    pub async fn sha1(&self, client: &Client, file: &SourceFile) -> Result<Option<String>> {
+        if !file.has_sha1 {
+            return Ok(None);
+        }
+
+        let text = match self {
+            Self::Dir(root) => {
+                tokio::fs::read_to_string(root.join(format!("{}.sha1", file.path))).await?
+            }
+
+            Self::Remote(root) => {
+                client
+                    .get(root.join(&format!("{}.sha1", file.path))?)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .text()
+                    .await?
+            }
+        };
+
+        // Some tools write `<hash>  <file name>`.
+        Ok(text
+            .split_whitespace()
+            .next()
+            .map(|it| it.to_lowercase())
+            .filter(|it| it.len() == 40))
+    }
+}
+
+fn list_dir(root: &Path) -> Result<Vec<SourceFile>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let meta = entry.metadata()?;
+
+            if meta.is_dir() {
+                if !name.starts_with('.') {
+                    dirs.push(entry.path());
+                }
+
+                continue;
+            }
+
+            if is_ignored(&name) {
+                continue;
+            }
+
+            let path = entry.path();
+
+            let rel = path
+                .strip_prefix(root)?
+                .components()
+                .map(|it| it.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+
+            files.push(SourceFile {
+                has_sha1: path.with_file_name(format!("{name}.sha1")).is_file(),
+                modified: meta
+                    .modified()
+                    .ok()
+                    .map(|it| DateTime::<chrono::Utc>::from(it).naive_utc()),
+                path: rel,
+            });
+        }
+    }
+
+    Ok(files)
+}
+
+/// Walk a server's directory listings. Links that leave the starting directory (like
+/// `../`, or absolute links elsewhere) are ignored.
+async fn crawl(client: &Client, root: &Url) -> Result<Vec<SourceFile>> {
+    let mut files = Vec::new();
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::from([root.clone()]);
+
+    while let Some(dir) = queue.pop_front() {
+        debug!("Crawling {dir}...");
+
+        let html = client
+            .get(dir.clone())
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        let mut names = Vec::new();
+
+        for cap in HREF.captures_iter(&html) {
+            let Ok(link) = dir.join(&cap[1]) else {
+                continue;
+            };
+
+            if !link.as_str().starts_with(dir.as_str()) || link == dir || !seen.insert(link.clone())
+            {
+                continue;
+            }
+
+            if link.path().ends_with('/') {
+                queue.push_back(link);
+            } else {
+                names.push(link);
+            }
+        }
+
+        let listed = names
+            .iter()
+            .map(|it| it.as_str().to_string())
+            .collect::<HashSet<_>>();
+
+        for link in names {
+            let name = link.path().rsplit('/').next().unwrap_or_default();
+
+            if is_ignored(name) {
+                continue;
+            }
+
+            let path = link
+                .as_str()
+                .strip_prefix(root.as_str())
+                .ok_or(anyhow!("{link} isn't under {root}"))?;
+
+            files.push(SourceFile {
+                path: urlencoding::decode(path)?.into_owned(),
+                modified: None,
+                has_sha1: listed.contains(&format!("{link}.sha1")),
+            });
+        }
+    }
+
+    Ok(files)
+}
@@ -11,16 +11,29 @@ extern crate tracing;
 
 pub mod auth;
 pub mod cli;
+pub mod commands;
+pub mod config;
 pub mod cx;
 pub mod db;
 pub mod err;
+pub mod export;
 pub mod files;
+pub mod https;
+pub mod import;
+pub mod maven;
 pub mod queue;
+pub mod quotas;
+pub mod ratelimit;
+pub mod replication;
 pub mod router;
 pub mod run;
 pub mod schema;
+pub mod scrub;
 pub mod seed;
+pub mod shutdown;
+pub mod staging;
 pub mod tokens;
 pub mod util;
+pub mod webhooks;
 pub mod s3;
 pub mod tls;
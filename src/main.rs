@@ -3,5 +3,5 @@ use mvn::cli::Cli;
 
 #[tokio::main]
 pub async fn main() -> Result<()> {
-    Cli::read().run().await
+    Cli::read()?.run().await
 }
@@ -0,0 +1,98 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Coordinates {
+    pub group: String,
+    pub artifact: String,
+    pub version: String,
+}
+
+impl Coordinates {
+    pub fn new(
+        group: impl AsRef<str>,
+        artifact: impl AsRef<str>,
+        version: impl AsRef<str>,
+    ) -> Self {
+        Self {
+            group: group.as_ref().into(),
+            artifact: artifact.as_ref().into(),
+            version: version.as_ref().into(),
+        }
+    }
+
+    /// Parse a version folder path like `/com/example/thing/1.0.0/`.
+    pub fn from_version_folder(path: impl AsRef<str>) -> Option<Self> {
+        let mut parts = path
+            .as_ref()
+            .split("/")
+            .filter(|it| !it.is_empty())
+            .collect::<Vec<_>>();
+
+        let version = parts.pop()?;
+        let artifact = parts.pop()?;
+
+        if parts.is_empty() {
+            return None;
+        }
+
+        Some(Self::new(parts.join("."), artifact, version))
+    }
+
+    /// The folder holding all versions of this artifact, like `/com/example/thing/`.
+    pub fn artifact_folder(&self) -> String {
+        format!("/{}/{}/", self.group.replace('.', "/"), self.artifact)
+    }
+
+    /// The folder holding this version, like `/com/example/thing/1.0.0/`.
+    pub fn version_folder(&self) -> String {
+        format!("{}{}/", self.artifact_folder(), self.version)
+    }
+
+    /// The path of the main POM for this version (for releases).
+    pub fn pom_path(&self) -> String {
+        format!(
+            "{}{}-{}.pom",
+            self.version_folder(),
+            self.artifact,
+            self.version
+        )
+    }
+
+    pub fn maven_snippet(&self) -> String {
+        format!(
+            "<dependency>\n    <groupId>{}</groupId>\n    <artifactId>{}</artifactId>\n    <version>{}</version>\n</dependency>",
+            self.group, self.artifact, self.version
+        )
+    }
+
+    pub fn gradle_groovy_snippet(&self) -> String {
+        format!("implementation '{self}'")
+    }
+
+    pub fn gradle_kotlin_snippet(&self) -> String {
+        format!("implementation(\"{self}\")")
+    }
+
+    pub fn sbt_snippet(&self) -> String {
+        format!(
+            "libraryDependencies += \"{}\" % \"{}\" % \"{}\"",
+            self.group, self.artifact, self.version
+        )
+    }
+
+    /// All dependency snippets, as `(label, code)` pairs.
+    pub fn snippets(&self) -> Vec<(String, String)> {
+        vec![
+            ("Maven".into(), self.maven_snippet()),
+            ("Gradle (Groovy)".into(), self.gradle_groovy_snippet()),
+            ("Gradle (Kotlin)".into(), self.gradle_kotlin_snippet()),
+            ("sbt".into(), self.sbt_snippet()),
+        ]
+    }
+}
+
+impl Display for Coordinates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.group, self.artifact, self.version)
+    }
+}
@@ -0,0 +1,636 @@
+use super::{
+    jar::{JarEntry, scan_jar},
+    models::{
+        MavenJarEntry, MavenPom, MavenPomDependency, MavenPomDeveloper, MavenPomLicense,
+        MavenPomProperty,
+    },
+    models_in::{
+        IndexFailureIn, MavenJarEntryIn, MavenPomDependencyIn, MavenPomDeveloperIn, MavenPomIn,
+        MavenPomLicenseIn, MavenPomPropertyIn,
+    },
+    pom::{
+        Pom, PomDependencies, PomDependency, PomDependencyManagement, PomDeveloper, PomDevelopers,
+        PomLicense, PomLicenses, PomParent, PomScm,
+    },
+};
+use crate::{
+    cx::RouteContext,
+    files::{models::MavenFile, types::FILE_TYPES},
+    schema::{
+        files, index_failures, jar_entries, pom_dependencies, pom_developers, pom_licenses,
+        pom_properties, poms,
+    },
+    util::escape_like,
+};
+use anyhow::{Result, anyhow};
+use diesel::{
+    BelongingToDsl, BoolExpressionMethods, ExpressionMethods, OptionalExtension,
+    PgTextExpressionMethods, QueryDsl, SelectableHelper, TextExpressionMethods, delete,
+    dsl::{exists, not, sql},
+    insert_into,
+    pg::Pg,
+    sql_types::{Bool, Text},
+};
+use diesel_async::{AsyncConnection, RunQueryDsl, scoped_futures::ScopedFutureExt};
+use std::collections::HashSet;
+
+/// The most rows a single search will look at.
+pub const SEARCH_LIMIT: i64 = 5000;
+
+/// How many JAR entries to insert per statement.
+const JAR_INSERT_CHUNK: usize = 10000;
+
+/// Full-text match on a POM's name and description. This has to line up with the
+/// expression in the `poms_text_idx` index for it to be used.
+const POM_TEXT_MATCH: &str = "to_tsvector('simple', COALESCE(poms.name, '') || ' ' || COALESCE(poms.description, '')) @@ plainto_tsquery('simple', ";
+
+impl RouteContext {
+    /// Store the structured contents of a POM file, replacing any existing records for it.
+    pub async fn index_pom_inner(
+        &self,
+        file: &MavenFile,
+        pom: &Pom,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<MavenPom> {
+        let record = MavenPomIn::new(file.id, pom)?;
+
+        conn.transaction::<_, anyhow::Error, _>(|conn| {
+            async move {
+                delete(poms::table)
+                    .filter(poms::file.eq(record.file))
+                    .execute(conn)
+                    .await?;
+
+                let created = insert_into(poms::table)
+                    .values(record)
+                    .returning(MavenPom::as_returning())
+                    .get_result(conn)
+                    .await?;
+
+                let deps = pom
+                    .dependencies
+                    .items
+                    .iter()
+                    .map(|it| MavenPomDependencyIn::new(created.id, pom, it, false))
+                    .chain(
+                        pom.dependency_management
+                            .dependencies
+                            .items
+                            .iter()
+                            .map(|it| MavenPomDependencyIn::new(created.id, pom, it, true)),
+                    )
+                    .collect::<Vec<_>>();
+
+                let props = pom
+                    .properties
+                    .iter()
+                    .map(|(name, value)| MavenPomPropertyIn {
+                        pom: created.id,
+                        name: name.clone(),
+                        value: value.clone(),
+                    })
+                    .collect::<Vec<_>>();
+
+                let licenses = pom
+                    .licenses
+                    .items
+                    .iter()
+                    .map(|it| MavenPomLicenseIn::new(created.id, it))
+                    .collect::<Vec<_>>();
+
+                let developers = pom
+                    .developers
+                    .items
+                    .iter()
+                    .map(|it| MavenPomDeveloperIn::new(created.id, it))
+                    .collect::<Vec<_>>();
+
+                if !deps.is_empty() {
+                    insert_into(pom_dependencies::table)
+                        .values(deps)
+                        .execute(conn)
+                        .await?;
+                }
+
+                if !props.is_empty() {
+                    insert_into(pom_properties::table)
+                        .values(props)
+                        .execute(conn)
+                        .await?;
+                }
+
+                if !licenses.is_empty() {
+                    insert_into(pom_licenses::table)
+                        .values(licenses)
+                        .execute(conn)
+                        .await?;
+                }
+
+                if !developers.is_empty() {
+                    insert_into(pom_developers::table)
+                        .values(developers)
+                        .execute(conn)
+                        .await?;
+                }
+
+                Ok(created)
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
+    /// Remember that a file couldn't be indexed, so the backfill skips it from now on.
+    /// Replacing the file clears this.
+    pub async fn mark_index_failure_inner(
+        &self,
+        file: &MavenFile,
+        kind: &str,
+        error: impl ToString,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<()> {
+        let data = IndexFailureIn {
+            file: file.id,
+            kind: kind.into(),
+            error: error.to_string(),
+        };
+
+        insert_into(index_failures::table)
+            .values(&data)
+            .on_conflict((index_failures::file, index_failures::kind))
+            .do_update()
+            .set(&data)
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// The files that have already failed to index as `kind`.
+    async fn index_failures_inner(
+        &self,
+        kind: &str,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<HashSet<i32>> {
+        Ok(index_failures::table
+            .filter(index_failures::kind.eq(kind))
+            .select(index_failures::file)
+            .load::<i32>(conn)
+            .await?
+            .into_iter()
+            .collect())
+    }
+
+    /// Index every POM file that doesn't have records yet (e.g. ones uploaded before
+    /// POM indexing existed), or every one at all with `all`. Files that can't be read
+    /// are skipped, and ones that can't be parsed are marked so they aren't tried again
+    /// until everything is. Each file's records are only replaced once it's parsed, so
+    /// the existing index keeps working while this runs.
+    pub async fn index_poms(&self, all: bool) -> Result<usize> {
+        let mut conn = self.pool.get().await?;
+
+        if all {
+            delete(index_failures::table)
+                .filter(index_failures::kind.eq("pom"))
+                .execute(&mut conn)
+                .await?;
+        }
+
+        let failed = self.index_failures_inner("pom", &mut conn).await?;
+
+        let mut query = files::table
+            .filter(files::path.like("%.pom"))
+            .select(MavenFile::as_select())
+            .into_boxed();
+
+        if !all {
+            query = query.filter(not(exists(poms::table.filter(poms::file.eq(files::id)))));
+        }
+
+        let missing = query
+            .load(&mut conn)
+            .await?
+            .into_iter()
+            .filter(|it| !failed.contains(&it.id));
+
+        let mut count = 0;
+
+        for file in missing {
+            let bytes = match file.get_bytes(&self.storage).await {
+                Ok(it) => it,
+
+                Err(err) => {
+                    warn!("Could not read POM {}: {err}", file.path);
+                    continue;
+                }
+            };
+
+            let res = match Pom::parse(bytes) {
+                Ok(pom) => self
+                    .index_pom_inner(&file, &pom, &mut conn)
+                    .await
+                    .map(|_| ()),
+                Err(err) => Err(err),
+            };
+
+            match res {
+                Ok(()) => count += 1,
+
+                Err(err) => {
+                    warn!("Could not index POM {}: {err}", file.path);
+
+                    self.mark_index_failure_inner(&file, "pom", err, &mut conn)
+                        .await?;
+                }
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Store the entries of a JAR, replacing any existing ones for it.
+    pub async fn index_jar_inner(
+        &self,
+        file: &MavenFile,
+        entries: &[JarEntry],
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<usize> {
+        let records = entries
+            .iter()
+            .map(|it| MavenJarEntryIn::new(file.id, it))
+            .collect::<Vec<_>>();
+
+        conn.transaction::<_, anyhow::Error, _>(|conn| {
+            async move {
+                delete(jar_entries::table)
+                    .filter(jar_entries::file.eq(file.id))
+                    .execute(conn)
+                    .await?;
+
+                let mut count = 0;
+
+                // Postgres caps the number of bind parameters in a single statement.
+                for chunk in records.chunks(JAR_INSERT_CHUNK) {
+                    count += insert_into(jar_entries::table)
+                        .values(chunk)
+                        .execute(conn)
+                        .await?;
+                }
+
+                Ok(count)
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
+    /// Index every JAR that doesn't have any entries yet, or every one at all with `all`.
+    /// Like with POMs, unreadable files are skipped and broken (or empty) ones are marked
+    /// so they aren't tried again.
+    pub async fn index_jars(&self, all: bool) -> Result<usize> {
+        let mut conn = self.pool.get().await?;
+
+        if all {
+            delete(index_failures::table)
+                .filter(index_failures::kind.eq("jar"))
+                .execute(&mut conn)
+                .await?;
+        }
+
+        let failed = self.index_failures_inner("jar", &mut conn).await?;
+
+        let mut query = files::table
+            .filter(files::kind.eq(*FILE_TYPES.get("jar").unwrap()))
+            .filter(files::path.not_like("%-javadoc.jar"))
+            .select(MavenFile::as_select())
+            .into_boxed();
+
+        if !all {
+            query = query.filter(not(exists(
+                jar_entries::table.filter(jar_entries::file.eq(files::id)),
+            )));
+        }
+
+        let missing = query
+            .load(&mut conn)
+            .await?
+            .into_iter()
+            .filter(|it| !failed.contains(&it.id));
+
+        let mut count = 0;
+
+        for file in missing {
+            let bytes = match file.get_bytes(&self.storage).await {
+                Ok(it) => it,
+
+                Err(err) => {
+                    warn!("Could not read JAR {}: {err}", file.path);
+                    continue;
+                }
+            };
+
+            let res = match tokio::task::spawn_blocking(move || scan_jar(bytes)).await? {
+                // Nothing would be stored for these, so they'd look unindexed forever.
+                Ok(entries) if entries.is_empty() => Err(anyhow!("The JAR is empty")),
+                Ok(entries) => self.index_jar_inner(&file, &entries, &mut conn).await,
+                Err(err) => Err(err),
+            };
+
+            match res {
+                Ok(_) => count += 1,
+
+                Err(err) => {
+                    warn!("Could not index JAR {}: {err}", file.path);
+
+                    self.mark_index_failure_inner(&file, "jar", err, &mut conn)
+                        .await?;
+                }
+            }
+        }
+
+        Ok(count)
+    }
+
+    pub async fn get_jar_entries(&self, file: &MavenFile) -> Result<Vec<MavenJarEntry>> {
+        Ok(MavenJarEntry::belonging_to(file)
+            .select(MavenJarEntry::as_select())
+            .order(jar_entries::name.asc())
+            .load(&mut self.pool.get().await?)
+            .await?)
+    }
+
+    /// Search JAR entries by class name, or by entry path if `classes_only` is false.
+    pub async fn search_jar_entries(
+        &self,
+        query: impl AsRef<str>,
+        classes_only: bool,
+    ) -> Result<Vec<(MavenJarEntry, MavenFile)>> {
+        let pattern = format!("%{}%", escape_like(query));
+
+        let mut query = jar_entries::table
+            .inner_join(files::table)
+            .select((MavenJarEntry::as_select(), MavenFile::as_select()))
+            .into_boxed();
+
+        query = if classes_only {
+            query.filter(jar_entries::class_name.ilike(pattern))
+        } else {
+            query.filter(jar_entries::name.ilike(pattern))
+        };
+
+        Ok(query
+            .order((jar_entries::name.asc(), files::path.asc()))
+            .limit(SEARCH_LIMIT)
+            .load(&mut self.pool.get().await?)
+            .await?)
+    }
+
+    /// Get the POM for some coordinates. If there are several (e.g. timestamped
+    /// snapshots), the most recently indexed one wins.
+    pub async fn get_pom(
+        &self,
+        group: impl AsRef<str>,
+        artifact: impl AsRef<str>,
+        version: impl AsRef<str>,
+    ) -> Result<MavenPom> {
+        Ok(poms::table
+            .filter(poms::group_id.eq(group.as_ref()))
+            .filter(poms::artifact_id.eq(artifact.as_ref()))
+            .filter(poms::version.eq(version.as_ref()))
+            .order(poms::indexed.desc())
+            .select(MavenPom::as_select())
+            .first(&mut self.pool.get().await?)
+            .await?)
+    }
+
+    /// Like [`RouteContext::get_pom`], but `None` if there isn't one.
+    pub async fn find_pom(
+        &self,
+        group: impl AsRef<str>,
+        artifact: impl AsRef<str>,
+        version: impl AsRef<str>,
+    ) -> Result<Option<MavenPom>> {
+        Ok(poms::table
+            .filter(poms::group_id.eq(group.as_ref()))
+            .filter(poms::artifact_id.eq(artifact.as_ref()))
+            .filter(poms::version.eq(version.as_ref()))
+            .order(poms::indexed.desc())
+            .select(MavenPom::as_select())
+            .first(&mut self.pool.get().await?)
+            .await
+            .optional()?)
+    }
+
+    pub async fn get_pom_for_file(&self, file: i32) -> Result<MavenPom> {
+        Ok(poms::table
+            .filter(poms::file.eq(file))
+            .select(MavenPom::as_select())
+            .first(&mut self.pool.get().await?)
+            .await?)
+    }
+
+    pub async fn get_pom_versions(
+        &self,
+        group: impl AsRef<str>,
+        artifact: impl AsRef<str>,
+    ) -> Result<Vec<MavenPom>> {
+        Ok(poms::table
+            .filter(poms::group_id.eq(group.as_ref()))
+            .filter(poms::artifact_id.eq(artifact.as_ref()))
+            .select(MavenPom::as_select())
+            .load(&mut self.pool.get().await?)
+            .await?)
+    }
+
+    /// Get every POM that declares these coordinates as its parent.
+    pub async fn get_child_poms(
+        &self,
+        group: impl AsRef<str>,
+        artifact: impl AsRef<str>,
+        version: impl AsRef<str>,
+    ) -> Result<Vec<MavenPom>> {
+        Ok(poms::table
+            .filter(poms::parent_group_id.eq(group.as_ref()))
+            .filter(poms::parent_artifact_id.eq(artifact.as_ref()))
+            .filter(poms::parent_version.eq(version.as_ref()))
+            .select(MavenPom::as_select())
+            .load(&mut self.pool.get().await?)
+            .await?)
+    }
+
+    pub async fn get_pom_dependencies(&self, pom: &MavenPom) -> Result<Vec<MavenPomDependency>> {
+        Ok(MavenPomDependency::belonging_to(pom)
+            .select(MavenPomDependency::as_select())
+            .order(pom_dependencies::id.asc())
+            .load(&mut self.pool.get().await?)
+            .await?)
+    }
+
+    /// Search indexed POMs. Every word in `text` has to match the group ID, artifact ID,
+    /// name or description; `group` matches by prefix and `artifact` by substring.
+    pub async fn search_poms(
+        &self,
+        text: &[String],
+        group: Option<&str>,
+        artifact: Option<&str>,
+    ) -> Result<Vec<MavenPom>> {
+        let mut query = poms::table.select(MavenPom::as_select()).into_boxed();
+
+        for word in text {
+            let pattern = format!("%{}%", escape_like(word));
+
+            query = query.filter(
+                poms::group_id
+                    .ilike(pattern.clone())
+                    .or(poms::artifact_id.ilike(pattern))
+                    .or(sql::<Bool>(POM_TEXT_MATCH)
+                        .bind::<Text, _>(word.clone())
+                        .sql(")")),
+            );
+        }
+
+        if let Some(group) = group {
+            query = query.filter(poms::group_id.ilike(format!("{}%", escape_like(group))));
+        }
+
+        if let Some(artifact) = artifact {
+            query = query.filter(poms::artifact_id.ilike(format!("%{}%", escape_like(artifact))));
+        }
+
+        Ok(query
+            .order((
+                poms::group_id.asc(),
+                poms::artifact_id.asc(),
+                poms::indexed.desc(),
+            ))
+            .limit(SEARCH_LIMIT)
+            .load(&mut self.pool.get().await?)
+            .await?)
+    }
+
+    /// Find every POM that declares a direct (non-managed) dependency on an artifact.
+    pub async fn get_dependents(
+        &self,
+        group: impl AsRef<str>,
+        artifact: impl AsRef<str>,
+    ) -> Result<Vec<(MavenPom, MavenPomDependency)>> {
+        Ok(pom_dependencies::table
+            .inner_join(poms::table)
+            .filter(pom_dependencies::group_id.eq(group.as_ref()))
+            .filter(pom_dependencies::artifact_id.eq(artifact.as_ref()))
+            .filter(pom_dependencies::managed.eq(false))
+            .select((MavenPom::as_select(), MavenPomDependency::as_select()))
+            .load(&mut self.pool.get().await?)
+            .await?)
+    }
+
+    /// Rebuild a [`Pom`] from its stored records.
+    pub async fn load_pom(&self, pom: &MavenPom) -> Result<Pom> {
+        let mut conn = self.pool.get().await?;
+
+        let deps = MavenPomDependency::belonging_to(pom)
+            .select(MavenPomDependency::as_select())
+            .order(pom_dependencies::id.asc())
+            .load(&mut conn)
+            .await?;
+
+        let props = MavenPomProperty::belonging_to(pom)
+            .select(MavenPomProperty::as_select())
+            .load(&mut conn)
+            .await?;
+
+        let licenses = MavenPomLicense::belonging_to(pom)
+            .select(MavenPomLicense::as_select())
+            .order(pom_licenses::id.asc())
+            .load(&mut conn)
+            .await?;
+
+        let developers = MavenPomDeveloper::belonging_to(pom)
+            .select(MavenPomDeveloper::as_select())
+            .order(pom_developers::id.asc())
+            .load(&mut conn)
+            .await?;
+
+        let (managed, direct): (Vec<_>, Vec<_>) = deps
+            .into_iter()
+            .map(|it| {
+                (
+                    it.managed,
+                    PomDependency {
+                        group_id: it.group_id,
+                        artifact_id: it.artifact_id,
+                        version: it.version,
+                        scope: it.scope,
+                        kind: Some(it.kind),
+                        classifier: it.classifier,
+                        optional: it.optional.then(|| "true".into()),
+                    },
+                )
+            })
+            .partition(|(managed, _)| *managed);
+
+        let parent = match (
+            &pom.parent_group_id,
+            &pom.parent_artifact_id,
+            &pom.parent_version,
+        ) {
+            (Some(group_id), Some(artifact_id), Some(version)) => Some(PomParent {
+                group_id: group_id.clone(),
+                artifact_id: artifact_id.clone(),
+                version: version.clone(),
+                relative_path: None,
+            }),
+
+            _ => None,
+        };
+
+        Ok(Pom {
+            group_id: Some(pom.group_id.clone()),
+            artifact_id: pom.artifact_id.clone(),
+            version: Some(pom.version.clone()),
+            packaging: Some(pom.packaging.clone()),
+            name: pom.name.clone(),
+            description: pom.description.clone(),
+            url: pom.url.clone(),
+            parent,
+            properties: props.into_iter().map(|it| (it.name, it.value)).collect(),
+            dependencies: PomDependencies {
+                items: direct.into_iter().map(|(_, it)| it).collect(),
+            },
+            dependency_management: PomDependencyManagement {
+                dependencies: PomDependencies {
+                    items: managed.into_iter().map(|(_, it)| it).collect(),
+                },
+            },
+            licenses: PomLicenses {
+                items: licenses
+                    .into_iter()
+                    .map(|it| PomLicense {
+                        name: it.name,
+                        url: it.url,
+                        distribution: it.distribution,
+                        comments: it.comments,
+                    })
+                    .collect(),
+            },
+            scm: pom.scm_url.as_ref().map(|_| PomScm {
+                url: pom.scm_url.clone(),
+                connection: pom.scm_connection.clone(),
+                developer_connection: pom.scm_developer_connection.clone(),
+                tag: pom.scm_tag.clone(),
+            }),
+            developers: PomDevelopers {
+                items: developers
+                    .into_iter()
+                    .map(|it| PomDeveloper {
+                        id: it.developer_id,
+                        name: it.name,
+                        email: it.email,
+                        url: it.url,
+                        organization: it.organization,
+                    })
+                    .collect(),
+            },
+        })
+    }
+}
@@ -0,0 +1,64 @@
+//! Reading the list of classes and resources out of a JAR.
+
+use anyhow::Result;
+use std::io::Cursor;
+use zip::ZipArchive;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JarEntry {
+    /// The full path of the entry inside the JAR, like `com/example/Thing.class`.
+    pub name: String,
+
+    /// The fully qualified class name, like `com.example.Thing`, if this is a class.
+    pub class_name: Option<String>,
+
+    /// The uncompressed size.
+    pub size: u64,
+}
+
+impl JarEntry {
+    pub fn new(name: impl AsRef<str>, size: u64) -> Self {
+        let name = name.as_ref();
+
+        Self {
+            name: name.into(),
+            class_name: class_name(name),
+            size,
+        }
+    }
+}
+
+/// List every file in a JAR. Directories are skipped.
+pub fn scan_jar(bytes: impl AsRef<[u8]>) -> Result<Vec<JarEntry>> {
+    let mut zip = ZipArchive::new(Cursor::new(bytes.as_ref()))?;
+    let mut entries = Vec::with_capacity(zip.len());
+
+    for i in 0..zip.len() {
+        let entry = zip.by_index_raw(i)?;
+
+        if entry.is_dir() {
+            continue;
+        }
+
+        entries.push(JarEntry::new(entry.name(), entry.size()));
+    }
+
+    Ok(entries)
+}
+
+/// Turn a `.class` entry path into a class name. Multi-release JARs keep versioned
+/// classes under `META-INF/versions/<n>/`, which isn't part of the name.
+fn class_name(name: &str) -> Option<String> {
+    let path = name.strip_suffix(".class")?;
+
+    let path = match path.strip_prefix("META-INF/versions/") {
+        Some(rest) => rest.split_once('/')?.1,
+        None => path,
+    };
+
+    if path.is_empty() || path.starts_with("META-INF/") {
+        return None;
+    }
+
+    Some(path.replace('/', "."))
+}
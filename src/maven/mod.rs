@@ -0,0 +1,9 @@
+pub mod coords;
+pub mod cx;
+pub mod jar;
+pub mod models;
+pub mod models_in;
+pub mod pom;
+pub mod range;
+pub mod resolve;
+pub mod version;
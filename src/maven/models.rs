@@ -0,0 +1,147 @@
+use crate::files::models::MavenFile;
+use chrono::NaiveDateTime;
+
+#[derive(
+    Debug,
+    Clone,
+    Serialize,
+    Deserialize,
+    Insertable,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Associations,
+)]
+#[diesel(table_name = crate::schema::poms)]
+#[diesel(belongs_to(MavenFile, foreign_key = file))]
+pub struct MavenPom {
+    pub id: i32,
+    pub file: i32,
+    pub group_id: String,
+    pub artifact_id: String,
+    pub version: String,
+    pub packaging: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    pub parent_group_id: Option<String>,
+    pub parent_artifact_id: Option<String>,
+    pub parent_version: Option<String>,
+    pub scm_url: Option<String>,
+    pub scm_connection: Option<String>,
+    pub scm_developer_connection: Option<String>,
+    pub scm_tag: Option<String>,
+    pub indexed: NaiveDateTime,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Serialize,
+    Deserialize,
+    Insertable,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Associations,
+)]
+#[diesel(table_name = crate::schema::pom_dependencies)]
+#[diesel(belongs_to(MavenPom, foreign_key = pom))]
+pub struct MavenPomDependency {
+    pub id: i32,
+    pub pom: i32,
+    pub group_id: String,
+    pub artifact_id: String,
+    pub version: Option<String>,
+    pub scope: Option<String>,
+    pub kind: String,
+    pub classifier: Option<String>,
+    pub optional: bool,
+    pub managed: bool,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Serialize,
+    Deserialize,
+    Insertable,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Associations,
+)]
+#[diesel(table_name = crate::schema::pom_properties)]
+#[diesel(belongs_to(MavenPom, foreign_key = pom))]
+pub struct MavenPomProperty {
+    pub id: i32,
+    pub pom: i32,
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Serialize,
+    Deserialize,
+    Insertable,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Associations,
+)]
+#[diesel(table_name = crate::schema::pom_licenses)]
+#[diesel(belongs_to(MavenPom, foreign_key = pom))]
+pub struct MavenPomLicense {
+    pub id: i32,
+    pub pom: i32,
+    pub name: Option<String>,
+    pub url: Option<String>,
+    pub distribution: Option<String>,
+    pub comments: Option<String>,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Serialize,
+    Deserialize,
+    Insertable,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Associations,
+)]
+#[diesel(table_name = crate::schema::pom_developers)]
+#[diesel(belongs_to(MavenPom, foreign_key = pom))]
+pub struct MavenPomDeveloper {
+    pub id: i32,
+    pub pom: i32,
+    pub developer_id: Option<String>,
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub url: Option<String>,
+    pub organization: Option<String>,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Serialize,
+    Deserialize,
+    Insertable,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Associations,
+)]
+#[diesel(table_name = crate::schema::jar_entries)]
+#[diesel(belongs_to(MavenFile, foreign_key = file))]
+pub struct MavenJarEntry {
+    pub id: i32,
+    pub file: i32,
+    pub name: String,
+    pub class_name: Option<String>,
+    pub size: i64,
+}
@@ -0,0 +1,169 @@
+use super::{
+    jar::JarEntry,
+    pom::{Pom, PomDependency, PomDeveloper, PomLicense},
+};
+use anyhow::{Result, anyhow};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::poms)]
+pub struct MavenPomIn {
+    pub file: i32,
+    pub group_id: String,
+    pub artifact_id: String,
+    pub version: String,
+    pub packaging: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    pub parent_group_id: Option<String>,
+    pub parent_artifact_id: Option<String>,
+    pub parent_version: Option<String>,
+    pub scm_url: Option<String>,
+    pub scm_connection: Option<String>,
+    pub scm_developer_connection: Option<String>,
+    pub scm_tag: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::pom_dependencies)]
+pub struct MavenPomDependencyIn {
+    pub pom: i32,
+    pub group_id: String,
+    pub artifact_id: String,
+    pub version: Option<String>,
+    pub scope: Option<String>,
+    pub kind: String,
+    pub classifier: Option<String>,
+    pub optional: bool,
+    pub managed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::pom_properties)]
+pub struct MavenPomPropertyIn {
+    pub pom: i32,
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::pom_licenses)]
+pub struct MavenPomLicenseIn {
+    pub pom: i32,
+    pub name: Option<String>,
+    pub url: Option<String>,
+    pub distribution: Option<String>,
+    pub comments: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::pom_developers)]
+pub struct MavenPomDeveloperIn {
+    pub pom: i32,
+    pub developer_id: Option<String>,
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub url: Option<String>,
+    pub organization: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::jar_entries)]
+pub struct MavenJarEntryIn {
+    pub file: i32,
+    pub name: String,
+    pub class_name: Option<String>,
+    pub size: i64,
+}
+
+/// Marks a file that couldn't be indexed, so it isn't tried again on every startup.
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable, AsChangeset)]
+#[diesel(table_name = crate::schema::index_failures)]
+pub struct IndexFailureIn {
+    pub file: i32,
+
+    /// `pom` or `jar`.
+    pub kind: String,
+    pub error: String,
+}
+
+impl MavenPomIn {
+    pub fn new(file: i32, pom: &Pom) -> Result<Self> {
+        let group_id = pom.group_id().ok_or(anyhow!("POM is missing a groupId!"))?;
+
+        let version = pom.version().ok_or(anyhow!("POM is missing a version!"))?;
+        let scm = pom.scm.clone().unwrap_or_default();
+
+        Ok(Self {
+            file,
+            group_id: pom.interpolate(group_id),
+            artifact_id: pom.interpolate(&pom.artifact_id),
+            version: pom.interpolate(version),
+            packaging: pom.interpolate(pom.packaging()),
+            name: pom.name.clone(),
+            description: pom.description.as_ref().map(|it| it.trim().to_string()),
+            url: pom.url.clone(),
+            parent_group_id: pom.parent.as_ref().map(|it| it.group_id.clone()),
+            parent_artifact_id: pom.parent.as_ref().map(|it| it.artifact_id.clone()),
+            parent_version: pom.parent.as_ref().map(|it| it.version.clone()),
+            scm_url: scm.url,
+            scm_connection: scm.connection,
+            scm_developer_connection: scm.developer_connection,
+            scm_tag: scm.tag,
+        })
+    }
+}
+
+impl MavenPomDependencyIn {
+    /// Coordinates are interpolated with the POM's own properties so reverse lookups
+    /// can match them. Everything else is stored as declared.
+    pub fn new(pom: i32, owner: &Pom, dep: &PomDependency, managed: bool) -> Self {
+        Self {
+            pom,
+            group_id: owner.interpolate(&dep.group_id),
+            artifact_id: owner.interpolate(&dep.artifact_id),
+            version: dep.version.clone(),
+            scope: dep.scope.clone(),
+            kind: dep.kind.clone().unwrap_or_else(|| "jar".into()),
+            classifier: dep.classifier.clone(),
+            optional: dep.optional.as_deref().map(str::trim) == Some("true"),
+            managed,
+        }
+    }
+}
+
+impl MavenPomLicenseIn {
+    pub fn new(pom: i32, license: &PomLicense) -> Self {
+        Self {
+            pom,
+            name: license.name.clone(),
+            url: license.url.clone(),
+            distribution: license.distribution.clone(),
+            comments: license.comments.clone(),
+        }
+    }
+}
+
+impl MavenPomDeveloperIn {
+    pub fn new(pom: i32, developer: &PomDeveloper) -> Self {
+        Self {
+            pom,
+            developer_id: developer.id.clone(),
+            name: developer.name.clone(),
+            email: developer.email.clone(),
+            url: developer.url.clone(),
+            organization: developer.organization.clone(),
+        }
+    }
+}
+
+impl MavenJarEntryIn {
+    pub fn new(file: i32, entry: &JarEntry) -> Self {
+        Self {
+            file,
+            name: entry.name.clone(),
+            class_name: entry.class_name.clone(),
+            size: entry.size as i64,
+        }
+    }
+}
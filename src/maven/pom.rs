@@ -0,0 +1,168 @@
+use anyhow::Result;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PomParent {
+    pub group_id: String,
+    pub artifact_id: String,
+    pub version: String,
+    pub relative_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PomDependency {
+    pub group_id: String,
+    pub artifact_id: String,
+    pub version: Option<String>,
+    pub scope: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub classifier: Option<String>,
+    pub optional: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PomDependencies {
+    #[serde(default, rename = "dependency")]
+    pub items: Vec<PomDependency>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PomDependencyManagement {
+    #[serde(default)]
+    pub dependencies: PomDependencies,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PomLicense {
+    pub name: Option<String>,
+    pub url: Option<String>,
+    pub distribution: Option<String>,
+    pub comments: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PomLicenses {
+    #[serde(default, rename = "license")]
+    pub items: Vec<PomLicense>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PomScm {
+    pub url: Option<String>,
+    pub connection: Option<String>,
+    pub developer_connection: Option<String>,
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PomDeveloper {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub url: Option<String>,
+    pub organization: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PomDevelopers {
+    #[serde(default, rename = "developer")]
+    pub items: Vec<PomDeveloper>,
+}
+
+/// The parts of a `pom.xml` we care about. Anything else is ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pom {
+    pub group_id: Option<String>,
+    pub artifact_id: String,
+    pub version: Option<String>,
+    pub packaging: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    pub parent: Option<PomParent>,
+    #[serde(default)]
+    pub properties: HashMap<String, String>,
+    #[serde(default)]
+    pub dependencies: PomDependencies,
+    #[serde(default)]
+    pub dependency_management: PomDependencyManagement,
+    #[serde(default)]
+    pub licenses: PomLicenses,
+    pub scm: Option<PomScm>,
+    #[serde(default)]
+    pub developers: PomDevelopers,
+}
+
+impl Pom {
+    pub fn parse(bytes: impl AsRef<[u8]>) -> Result<Self> {
+        let text = String::from_utf8_lossy(bytes.as_ref());
+
+        Ok(quick_xml::de::from_str(text.trim_start_matches('\u{feff}'))?)
+    }
+
+    /// The group ID, inherited from the parent if it isn't declared.
+    pub fn group_id(&self) -> Option<String> {
+        self.group_id
+            .clone()
+            .or_else(|| self.parent.as_ref().map(|it| it.group_id.clone()))
+    }
+
+    /// The version, inherited from the parent if it isn't declared.
+    pub fn version(&self) -> Option<String> {
+        self.version
+            .clone()
+            .or_else(|| self.parent.as_ref().map(|it| it.version.clone()))
+    }
+
+    pub fn packaging(&self) -> String {
+        self.packaging.clone().unwrap_or_else(|| "jar".into())
+    }
+
+    /// Replace `${...}` references with this POM's own properties and coordinates.
+    /// Unknown references are left as-is.
+    pub fn interpolate(&self, value: impl AsRef<str>) -> String {
+        let mut value = value.as_ref().to_string();
+        let mut cursor = 0;
+        let mut passes = 0;
+
+        // Bound the number of substitutions so self-referencing properties can't loop forever.
+        while passes < 32 {
+            let Some(start) = value[cursor..].find("${").map(|it| it + cursor) else {
+                break;
+            };
+
+            let Some(len) = value[start..].find('}') else {
+                break;
+            };
+
+            match self.property(&value[start + 2..start + len]) {
+                Some(resolved) => {
+                    value.replace_range(start..start + len + 1, &resolved);
+                    passes += 1;
+                }
+
+                None => cursor = start + len + 1,
+            }
+        }
+
+        value
+    }
+
+    fn property(&self, key: &str) -> Option<String> {
+        match key {
+            "project.groupId" | "pom.groupId" | "groupId" => self.group_id(),
+            "project.artifactId" | "pom.artifactId" | "artifactId" => {
+                Some(self.artifact_id.clone())
+            }
+            "project.version" | "pom.version" | "version" => self.version(),
+            "project.parent.groupId" => self.parent.as_ref().map(|it| it.group_id.clone()),
+            "project.parent.version" => self.parent.as_ref().map(|it| it.version.clone()),
+            other => self.properties.get(other).cloned(),
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Maven version ranges, like `[1.0,2.0)` or `(,1.0],[1.2,)`.
+
+use super::version::MavenVersion;
+use anyhow::{Result, anyhow};
+
+#[derive(Debug, Clone)]
+pub struct Bound {
+    pub version: MavenVersion,
+    pub inclusive: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Restriction {
+    pub lower: Option<Bound>,
+    pub upper: Option<Bound>,
+}
+
+impl Restriction {
+    pub fn exact(version: MavenVersion) -> Self {
+        Self {
+            lower: Some(Bound {
+                version: version.clone(),
+                inclusive: true,
+            }),
+            upper: Some(Bound {
+                version,
+                inclusive: true,
+            }),
+        }
+    }
+
+    pub fn contains(&self, version: &MavenVersion) -> bool {
+        let above = match &self.lower {
+            Some(it) if it.inclusive => version >= &it.version,
+            Some(it) => version > &it.version,
+            None => true,
+        };
+
+        let below = match &self.upper {
+            Some(it) if it.inclusive => version <= &it.version,
+            Some(it) => version < &it.version,
+            None => true,
+        };
+
+        above && below
+    }
+
+    fn parse(spec: &str) -> Result<Self> {
+        let lower_inclusive = spec.starts_with('[');
+        let upper_inclusive = spec.ends_with(']');
+        let inner = spec[1..spec.len() - 1].trim();
+
+        let Some((lower, upper)) = inner.split_once(',') else {
+            if !lower_inclusive || !upper_inclusive || inner.is_empty() {
+                return Err(anyhow!("Single versions must be enclosed in []: {spec}"));
+            }
+
+            return Ok(Self::exact(MavenVersion::parse(inner)));
+        };
+
+        let bound = |value: &str, inclusive: bool| {
+            let value = value.trim();
+
+            (!value.is_empty()).then(|| Bound {
+                version: MavenVersion::parse(value),
+                inclusive,
+            })
+        };
+
+        let lower = bound(lower, lower_inclusive);
+        let upper = bound(upper, upper_inclusive);
+
+        if let (Some(lower), Some(upper)) = (&lower, &upper) {
+            if upper.version < lower.version {
+                return Err(anyhow!(
+                    "Range upper bound is below its lower bound: {spec}"
+                ));
+            }
+        }
+
+        Ok(Self { lower, upper })
+    }
+}
+
+/// A set of version restrictions. A version matches if any restriction contains it.
+#[derive(Debug, Clone)]
+pub struct VersionRange {
+    pub restrictions: Vec<Restriction>,
+}
+
+impl VersionRange {
+    /// Does this look like a range rather than a plain version?
+    pub fn is_range(spec: impl AsRef<str>) -> bool {
+        spec.as_ref().trim().starts_with(['[', '('])
+    }
+
+    /// Parse a range spec. A plain version (like `1.0`) only matches itself, rather than
+    /// being treated as Maven's "soft" requirement.
+    pub fn parse(spec: impl AsRef<str>) -> Result<Self> {
+        let spec = spec.as_ref().trim();
+
+        if !Self::is_range(spec) {
+            return Ok(Self {
+                restrictions: vec![Restriction::exact(MavenVersion::parse(spec))],
+            });
+        }
+
+        let mut restrictions = Vec::new();
+        let mut rest = spec;
+
+        while !rest.is_empty() {
+            let Some(end) = rest.find([']', ')']) else {
+                return Err(anyhow!("Unterminated version range: {spec}"));
+            };
+
+            restrictions.push(Restriction::parse(&rest[..=end])?);
+
+            rest = rest[end + 1..].trim_start_matches(',').trim();
+
+            if !rest.is_empty() && !rest.starts_with(['[', '(']) {
+                return Err(anyhow!("Invalid version range: {spec}"));
+            }
+        }
+
+        Ok(Self { restrictions })
+    }
+
+    pub fn contains(&self, version: &MavenVersion) -> bool {
+        self.restrictions.iter().any(|it| it.contains(version))
+    }
+
+    /// Pick the highest version in this range out of some candidates.
+    pub fn best<'a>(
+        &self,
+        versions: impl IntoIterator<Item = &'a MavenVersion>,
+    ) -> Option<&'a MavenVersion> {
+        versions.into_iter().filter(|it| self.contains(it)).max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VersionRange;
+    use crate::maven::version::MavenVersion;
+
+    fn contains(spec: &str, version: &str) -> bool {
+        VersionRange::parse(spec)
+            .unwrap()
+            .contains(&MavenVersion::parse(version))
+    }
+
+    #[test]
+    fn inclusive_bounds() {
+        assert!(contains("[1.0,2.0]", "1.0"));
+        assert!(contains("[1.0,2.0]", "2.0"));
+        assert!(!contains("[1.0,2.0]", "0.9"));
+        assert!(!contains("[1.0,2.0]", "2.0.1"));
+    }
+
+    #[test]
+    fn exclusive_bounds() {
+        assert!(!contains("(1.0,2.0)", "1.0"));
+        assert!(!contains("(1.0,2.0)", "2.0"));
+        assert!(contains("(1.0,2.0)", "1.5"));
+        assert!(contains("(1.0,2.0)", "1.0.1"));
+
+        // Snapshots come before their release, so they're still under it.
+        assert!(contains("[1.0,2.0)", "2.0-SNAPSHOT"));
+        assert!(!contains("(1.0,2.0)", "1.0-SNAPSHOT"));
+    }
+
+    #[test]
+    fn open_ended_bounds() {
+        assert!(contains("(,1.0]", "0.1"));
+        assert!(contains("(,1.0]", "1.0"));
+        assert!(!contains("(,1.0]", "1.0.1"));
+        assert!(contains("[1.5,)", "99"));
+        assert!(!contains("[1.5,)", "1.4"));
+    }
+
+    #[test]
+    fn unions() {
+        assert!(contains("(,1.0],[1.2,)", "1.0"));
+        assert!(!contains("(,1.0],[1.2,)", "1.1"));
+        assert!(contains("(,1.0],[1.2,)", "1.2"));
+    }
+
+    #[test]
+    fn exact_versions() {
+        assert!(contains("[1.0]", "1.0"));
+        assert!(contains("[1.0]", "1.0.0"));
+        assert!(!contains("[1.0]", "1.0.1"));
+        assert!(contains("1.0", "1.0"));
+        assert!(!contains("1.0", "1.1"));
+    }
+
+    #[test]
+    fn invalid_ranges() {
+        for spec in ["(1.0)", "[2.0,1.0]", "[1.0,2.0", "[1.0],x"] {
+            let result = VersionRange::parse(spec);
+
+            assert!(result.is_err(), "{spec} should be invalid");
+        }
+    }
+
+    #[test]
+    fn best_picks_the_highest_match() {
+        let versions = ["1.0", "1.5", "2.0"].map(MavenVersion::parse);
+        let range = VersionRange::parse("[1.0,2.0)").unwrap();
+
+        assert_eq!(range.best(&versions).map(|it| it.as_str()), Some("1.5"));
+    }
+}
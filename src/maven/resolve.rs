@@ -0,0 +1,524 @@
+//! Dependency resolution against the POMs hosted here.
+//!
+//! This follows Maven's rules closely enough to answer "what does this pull in?" and
+//! "what pulls this in?": parents are inherited, `dependencyManagement` (including
+//! imported BOMs) fills in versions and scopes, and the nearest declaration wins.
+//! Exclusions aren't indexed, so they aren't applied.
+
+use super::{
+    coords::Coordinates,
+    pom::{Pom, PomDependency},
+    range::VersionRange,
+    version::MavenVersion,
+};
+use crate::{cx::RouteContext, router::checks::AccessChecker};
+use anyhow::Result;
+use futures_util::future::BoxFuture;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// How far up a parent chain we're willing to go.
+const MAX_PARENTS: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedDependency {
+    pub group: String,
+    pub artifact: String,
+
+    /// The version we resolved to, if we could work one out.
+    pub version: Option<String>,
+
+    /// The version (or range) as it was declared.
+    pub declared: Option<String>,
+
+    pub scope: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub classifier: Option<String>,
+    pub optional: bool,
+
+    /// 1 for direct dependencies, 2 for their dependencies, and so on.
+    pub depth: usize,
+
+    /// The artifact that pulled this one in, if it isn't a direct dependency.
+    pub via: Option<Coordinates>,
+
+    /// Is this artifact's POM hosted here?
+    pub hosted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependencyGraph {
+    pub root: Coordinates,
+    pub direct: Vec<ResolvedDependency>,
+    pub transitive: Vec<ResolvedDependency>,
+
+    /// Things we couldn't resolve, like parents or BOMs that aren't hosted here.
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dependent {
+    #[serde(flatten)]
+    pub coords: Coordinates,
+
+    /// The version (or range) of the target this artifact asks for.
+    pub declared: Option<String>,
+
+    pub scope: String,
+
+    /// 1 for artifacts depending on the target directly, 2 for their dependents, etc.
+    pub depth: usize,
+
+    /// The dependent that led us here, if this isn't a direct one.
+    pub via: Option<Coordinates>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependentsList {
+    pub group: String,
+    pub artifact: String,
+    pub version: Option<String>,
+    pub dependents: Vec<Dependent>,
+    pub warnings: Vec<String>,
+}
+
+pub struct Resolver<'a> {
+    cx: &'a RouteContext,
+    checker: &'a AccessChecker,
+    models: HashMap<Coordinates, Option<Pom>>,
+    versions: HashMap<(String, String), Vec<MavenVersion>>,
+    warnings: Vec<String>,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(cx: &'a RouteContext, checker: &'a AccessChecker) -> Self {
+        Self {
+            cx,
+            checker,
+            models: HashMap::new(),
+            versions: HashMap::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Resolve the direct and transitive dependencies of an artifact.
+    /// Returns `None` if its POM isn't hosted here (or can't be read).
+    pub async fn resolve(mut self, root: &Coordinates) -> Result<Option<DependencyGraph>> {
+        let Some(model) = self.effective(root).await? else {
+            return Ok(None);
+        };
+
+        let management = model.dependency_management.dependencies.items.clone();
+        let mut seen = HashSet::from([(root.group.clone(), root.artifact.clone())]);
+        let mut resolved = Vec::new();
+
+        let mut queue = model
+            .dependencies
+            .items
+            .into_iter()
+            .map(|it| (it, 1, None::<Coordinates>, None::<String>))
+            .collect::<VecDeque<_>>();
+
+        // Breadth-first, so the first declaration we see is the nearest one.
+        while let Some((dep, depth, via, parent_scope)) = queue.pop_front() {
+            let declared_scope = dep.scope.clone().unwrap_or_else(|| "compile".into());
+            let optional = dep.optional.as_deref() == Some("true");
+
+            if depth > 1
+                && (optional || matches!(declared_scope.as_str(), "test" | "provided" | "system"))
+            {
+                continue;
+            }
+
+            if !seen.insert((dep.group_id.clone(), dep.artifact_id.clone())) {
+                continue;
+            }
+
+            let scope = match parent_scope {
+                Some(parent) if parent != "compile" => parent,
+                _ => declared_scope,
+            };
+
+            // The root's dependencyManagement wins over whatever transitive POMs declare.
+            let declared = match managed(&management, &dep) {
+                Some(it) if depth > 1 && it.version.is_some() => it.version.clone(),
+                _ => dep.version.clone(),
+            };
+
+            let version = match &declared {
+                Some(it) => self.pick_version(&dep, it).await?,
+                None => None,
+            };
+
+            let coords = version
+                .as_ref()
+                .map(|it| Coordinates::new(&dep.group_id, &dep.artifact_id, it));
+
+            let model = match &coords {
+                Some(it) => self.effective(it).await?,
+                None => None,
+            };
+
+            let hosted = model.is_some();
+
+            if let (Some(model), Some(coords)) = (model, &coords) {
+                for child in model.dependencies.items {
+                    queue.push_back((child, depth + 1, Some(coords.clone()), Some(scope.clone())));
+                }
+            }
+
+            resolved.push(ResolvedDependency {
+                group: dep.group_id,
+                artifact: dep.artifact_id,
+                version,
+                declared,
+                scope,
+                kind: dep.kind.unwrap_or_else(|| "jar".into()),
+                classifier: dep.classifier,
+                optional,
+                depth,
+                via,
+                hosted,
+            });
+        }
+
+        let (direct, transitive): (Vec<_>, Vec<_>) =
+            resolved.into_iter().partition(|it| it.depth == 1);
+
+        Ok(Some(DependencyGraph {
+            root: root.clone(),
+            direct,
+            transitive,
+            warnings: self.warnings,
+        }))
+    }
+
+    /// Find the hosted artifacts that depend on `group:artifact`, optionally only the
+    /// ones whose declared version (or range) matches `version`.
+    pub async fn dependents(
+        mut self,
+        group: impl AsRef<str>,
+        artifact: impl AsRef<str>,
+        version: Option<String>,
+        transitive: bool,
+    ) -> Result<DependentsList> {
+        let group = group.as_ref().to_string();
+        let artifact = artifact.as_ref().to_string();
+        let mut seen = HashSet::new();
+        let mut dependents = Vec::new();
+        let mut queue =
+            VecDeque::from([(group.clone(), artifact.clone(), version.clone(), 1, None)]);
+
+        while let Some((target_group, target_artifact, target_version, depth, via)) =
+            queue.pop_front()
+        {
+            let mut candidates = self
+                .cx
+                .get_dependents(&target_group, &target_artifact)
+                .await?
+                .into_iter()
+                .map(|(pom, _)| Coordinates::new(pom.group_id, pom.artifact_id, pom.version))
+                .collect::<VecDeque<_>>();
+
+            let mut checked = HashSet::new();
+
+            while let Some(coords) = candidates.pop_front() {
+                if !checked.insert(coords.clone()) {
+                    continue;
+                }
+
+                let Some(model) = self.effective(&coords).await? else {
+                    continue;
+                };
+
+                let Some(dep) =
+                    model.dependencies.items.iter().find(|it| {
+                        it.group_id == target_group && it.artifact_id == target_artifact
+                    })
+                else {
+                    continue;
+                };
+
+                if let (Some(wanted), Some(declared)) = (&target_version, &dep.version) {
+                    if !version_matches(declared, wanted) {
+                        continue;
+                    }
+                }
+
+                // Children inherit their parent's dependencies, so they're affected too.
+                for child in self
+                    .cx
+                    .get_child_poms(&coords.group, &coords.artifact, &coords.version)
+                    .await?
+                {
+                    candidates.push_back(Coordinates::new(
+                        child.group_id,
+                        child.artifact_id,
+                        child.version,
+                    ));
+                }
+
+                if !seen.insert(coords.clone()) {
+                    continue;
+                }
+
+                if transitive {
+                    queue.push_back((
+                        coords.group.clone(),
+                        coords.artifact.clone(),
+                        Some(coords.version.clone()),
+                        depth + 1,
+                        Some(coords.clone()),
+                    ));
+                }
+
+                dependents.push(Dependent {
+                    declared: dep.version.clone(),
+                    scope: dep.scope.clone().unwrap_or_else(|| "compile".into()),
+                    coords,
+                    depth,
+                    via: via.clone(),
+                });
+            }
+        }
+
+        Ok(DependentsList {
+            group,
+            artifact,
+            version,
+            dependents,
+            warnings: self.warnings,
+        })
+    }
+
+    /// Build the effective model for some coordinates: the POM with its parents merged
+    /// in, properties interpolated, BOMs imported, and managed versions applied.
+    pub fn effective<'b>(
+        &'b mut self,
+        coords: &'b Coordinates,
+    ) -> BoxFuture<'b, Result<Option<Pom>>> {
+        // Boxed, since importing a BOM means building its model too.
+        Box::pin(async move {
+            if let Some(model) = self.models.get(coords) {
+                return Ok(model.clone());
+            }
+
+            // Reserve the slot first, so BOMs importing each other can't recurse forever.
+            self.models.insert(coords.clone(), None);
+
+            let model = self.build(coords).await?;
+
+            self.models.insert(coords.clone(), model.clone());
+
+            Ok(model)
+        })
+    }
+
+    async fn build(&mut self, coords: &Coordinates) -> Result<Option<Pom>> {
+        let Some(pom) = self.load(coords).await? else {
+            return Ok(None);
+        };
+
+        let mut chain = vec![pom];
+
+        while chain.len() < MAX_PARENTS {
+            let Some(parent) = chain.last().and_then(|it| it.parent.clone()) else {
+                break;
+            };
+
+            let parent = Coordinates::new(parent.group_id, parent.artifact_id, parent.version);
+
+            match self.load(&parent).await? {
+                Some(it) => chain.push(it),
+
+                None => {
+                    self.warn(format!("Parent {parent} of {coords} isn't hosted here"));
+                    break;
+                }
+            }
+        }
+
+        let mut model = chain.pop().unwrap();
+
+        while let Some(child) = chain.pop() {
+            model = inherit(model, child);
+        }
+
+        let context = model.clone();
+
+        for dep in model
+            .dependencies
+            .items
+            .iter_mut()
+            .chain(model.dependency_management.dependencies.items.iter_mut())
+        {
+            interpolate(&context, dep);
+        }
+
+        let (imports, mut management): (Vec<_>, Vec<_>) = model
+            .dependency_management
+            .dependencies
+            .items
+            .drain(..)
+            .partition(|it| {
+                it.scope.as_deref() == Some("import") && it.kind.as_deref() == Some("pom")
+            });
+
+        for import in imports {
+            let Some(version) = import.version else {
+                self.warn(format!(
+                    "BOM {}:{} imported by {coords} has no version",
+                    import.group_id, import.artifact_id
+                ));
+
+                continue;
+            };
+
+            let bom = Coordinates::new(import.group_id, import.artifact_id, version);
+
+            match self.effective(&bom).await? {
+                Some(it) => {
+                    for dep in it.dependency_management.dependencies.items {
+                        if managed(&management, &dep).is_none() {
+                            management.push(dep);
+                        }
+                    }
+                }
+
+                None => self.warn(format!("BOM {bom} imported by {coords} isn't hosted here")),
+            }
+        }
+
+        for dep in &mut model.dependencies.items {
+            if let Some(it) = managed(&management, dep) {
+                dep.version = dep.version.take().or_else(|| it.version.clone());
+                dep.scope = dep.scope.take().or_else(|| it.scope.clone());
+            }
+        }
+
+        model.dependency_management.dependencies.items = management;
+
+        Ok(Some(model))
+    }
+
+    /// Load a single POM as it was uploaded, if it's hosted here and readable.
+    async fn load(&mut self, coords: &Coordinates) -> Result<Option<Pom>> {
+        if !self.checker.check(coords.version_folder()).read {
+            return Ok(None);
+        }
+
+        match self
+            .cx
+            .find_pom(&coords.group, &coords.artifact, &coords.version)
+            .await?
+        {
+            Some(it) => Ok(Some(self.cx.load_pom(&it).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Turn a declared version into a concrete one. Ranges pick the highest hosted
+    /// version they contain.
+    async fn pick_version(
+        &mut self,
+        dep: &PomDependency,
+        declared: &str,
+    ) -> Result<Option<String>> {
+        if !VersionRange::is_range(declared) {
+            return Ok(Some(declared.into()));
+        }
+
+        let range = match VersionRange::parse(declared) {
+            Ok(it) => it,
+
+            Err(err) => {
+                self.warn(format!("{}:{}: {err}", dep.group_id, dep.artifact_id));
+                return Ok(None);
+            }
+        };
+
+        let key = (dep.group_id.clone(), dep.artifact_id.clone());
+
+        if !self.versions.contains_key(&key) {
+            let versions = self
+                .cx
+                .get_pom_versions(&dep.group_id, &dep.artifact_id)
+                .await?
+                .into_iter()
+                .map(|it| Coordinates::new(it.group_id, it.artifact_id, it.version))
+                .filter(|it| self.checker.check(it.version_folder()).read)
+                .map(|it| MavenVersion::parse(it.version))
+                .collect();
+
+            self.versions.insert(key.clone(), versions);
+        }
+
+        Ok(range.best(&self.versions[&key]).map(|it| it.to_string()))
+    }
+
+    fn warn(&mut self, message: String) {
+        if !self.warnings.contains(&message) {
+            self.warnings.push(message);
+        }
+    }
+}
+
+/// Merge a parent model into its child, with the child's declarations winning.
+fn inherit(parent: Pom, mut child: Pom) -> Pom {
+    let mut properties = parent.properties;
+
+    properties.extend(child.properties);
+    child.properties = properties;
+
+    child.dependencies.items = merge(parent.dependencies.items, child.dependencies.items);
+
+    child.dependency_management.dependencies.items = merge(
+        parent.dependency_management.dependencies.items,
+        child.dependency_management.dependencies.items,
+    );
+
+    child
+}
+
+fn merge(parent: Vec<PomDependency>, child: Vec<PomDependency>) -> Vec<PomDependency> {
+    let mut merged = parent
+        .into_iter()
+        .filter(|it| managed(&child, it).is_none())
+        .collect::<Vec<_>>();
+
+    merged.extend(child);
+    merged
+}
+
+fn interpolate(pom: &Pom, dep: &mut PomDependency) {
+    dep.group_id = pom.interpolate(&dep.group_id);
+    dep.artifact_id = pom.interpolate(&dep.artifact_id);
+    dep.version = dep.version.as_ref().map(|it| pom.interpolate(it));
+    dep.scope = dep.scope.as_ref().map(|it| pom.interpolate(it));
+    dep.kind = dep.kind.as_ref().map(|it| pom.interpolate(it));
+    dep.classifier = dep.classifier.as_ref().map(|it| pom.interpolate(it));
+}
+
+/// Find the entry for a dependency in a list, matching on Maven's management key
+/// (`groupId:artifactId:type:classifier`).
+fn managed<'a>(list: &'a [PomDependency], dep: &PomDependency) -> Option<&'a PomDependency> {
+    let kind = |it: &PomDependency| it.kind.clone().unwrap_or_else(|| "jar".into());
+
+    list.iter().find(|it| {
+        it.group_id == dep.group_id
+            && it.artifact_id == dep.artifact_id
+            && kind(it) == kind(dep)
+            && it.classifier == dep.classifier
+    })
+}
+
+/// Does a declared version (or range) cover `wanted`?
+fn version_matches(declared: &str, wanted: &str) -> bool {
+    if VersionRange::is_range(declared) {
+        VersionRange::parse(declared)
+            .map(|it| it.contains(&MavenVersion::parse(wanted)))
+            .unwrap_or(false)
+    } else {
+        MavenVersion::parse(declared) == MavenVersion::parse(wanted)
+    }
+}
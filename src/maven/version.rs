@@ -0,0 +1,316 @@
+//! An implementation of Maven's `ComparableVersion` ordering.
+
+use std::{cmp::Ordering, fmt::Display};
+
+const QUALIFIERS: &[&str] = &["alpha", "beta", "milestone", "rc", "snapshot", "", "sp"];
+const RELEASE_INDEX: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Item {
+    Int(String),
+    Str(String),
+    List(Vec<Item>),
+}
+
+impl Item {
+    fn int(value: &str) -> Self {
+        let trimmed = value.trim_start_matches('0');
+
+        Self::Int(if trimmed.is_empty() { "0" } else { trimmed }.into())
+    }
+
+    fn string(value: &str, followed_by_digit: bool) -> Self {
+        let value = if followed_by_digit && value.len() == 1 {
+            match value {
+                "a" => "alpha",
+                "b" => "beta",
+                "m" => "milestone",
+                other => other,
+            }
+        } else {
+            value
+        };
+
+        Self::Str(
+            match value {
+                "ga" | "final" | "release" => "",
+                "cr" => "rc",
+                other => other,
+            }
+            .into(),
+        )
+    }
+
+    fn parse(value: &str, is_digit: bool) -> Self {
+        if is_digit {
+            Self::int(value)
+        } else {
+            Self::string(value, false)
+        }
+    }
+
+    fn is_null(&self) -> bool {
+        match self {
+            Self::Int(it) => it == "0",
+            Self::Str(it) => it.is_empty(),
+            Self::List(it) => it.is_empty(),
+        }
+    }
+
+    fn comparable_qualifier(value: &str) -> String {
+        match QUALIFIERS.iter().position(|it| *it == value) {
+            Some(idx) => idx.to_string(),
+            None => format!("{}-{}", QUALIFIERS.len(), value),
+        }
+    }
+
+    fn compare(&self, other: Option<&Item>) -> Ordering {
+        match (self, other) {
+            (Self::Int(it), None) => {
+                if it == "0" {
+                    Ordering::Equal
+                } else {
+                    Ordering::Greater
+                }
+            }
+
+            (Self::Int(a), Some(Self::Int(b))) => a.len().cmp(&b.len()).then_with(|| a.cmp(b)),
+            (Self::Int(_), Some(_)) => Ordering::Greater,
+
+            (Self::Str(it), None) => {
+                Self::comparable_qualifier(it).cmp(&RELEASE_INDEX.to_string())
+            }
+
+            (Self::Str(a), Some(Self::Str(b))) => {
+                Self::comparable_qualifier(a).cmp(&Self::comparable_qualifier(b))
+            }
+
+            (Self::Str(_), Some(_)) => Ordering::Less,
+
+            (Self::List(items), None) => match items.first() {
+                Some(first) => first.compare(None),
+                None => Ordering::Equal,
+            },
+
+            (Self::List(_), Some(Self::Int(_))) => Ordering::Less,
+            (Self::List(_), Some(Self::Str(_))) => Ordering::Greater,
+
+            (Self::List(a), Some(Self::List(b))) => {
+                let mut a = a.iter();
+                let mut b = b.iter();
+
+                loop {
+                    let result = match (a.next(), b.next()) {
+                        (None, None) => return Ordering::Equal,
+                        (None, Some(r)) => r.compare(None).reverse(),
+                        (Some(l), r) => l.compare(r),
+                    };
+
+                    if result != Ordering::Equal {
+                        return result;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Remove trailing null items (`0`, `""` and empty lists), skipping over non-empty lists.
+fn normalize(items: &mut Vec<Item>) {
+    for i in (0..items.len()).rev() {
+        if items[i].is_null() {
+            items.remove(i);
+        } else if !matches!(items[i], Item::List(_)) {
+            break;
+        }
+    }
+}
+
+/// A Maven version, ordered the same way Maven orders versions.
+#[derive(Debug, Clone)]
+pub struct MavenVersion {
+    raw: String,
+    items: Item,
+}
+
+impl MavenVersion {
+    pub fn parse(version: impl AsRef<str>) -> Self {
+        let raw = version.as_ref().to_string();
+        let lower = raw.to_lowercase();
+        let chars = lower.chars().collect::<Vec<_>>();
+
+        // Every `-` (and every digit/letter transition) opens a new sub-list, so
+        // we keep a stack of lists and fold them back together at the end.
+        let mut stack: Vec<Vec<Item>> = vec![Vec::new()];
+        let mut is_digit = false;
+        let mut start = 0;
+
+        for (i, c) in chars.iter().enumerate() {
+            let current = || chars[start..i].iter().collect::<String>();
+
+            match c {
+                '.' => {
+                    let item = if i == start {
+                        Item::int("0")
+                    } else {
+                        Item::parse(&current(), is_digit)
+                    };
+
+                    stack.last_mut().unwrap().push(item);
+                    start = i + 1;
+                }
+
+                '-' => {
+                    let item = if i == start {
+                        Item::int("0")
+                    } else {
+                        Item::parse(&current(), is_digit)
+                    };
+
+                    stack.last_mut().unwrap().push(item);
+                    start = i + 1;
+                    stack.push(Vec::new());
+                }
+
+                c if c.is_ascii_digit() => {
+                    if !is_digit && i > start {
+                        stack
+                            .last_mut()
+                            .unwrap()
+                            .push(Item::string(&current(), true));
+
+                        start = i;
+                        stack.push(Vec::new());
+                    }
+
+                    is_digit = true;
+                }
+
+                _ => {
+                    if is_digit && i > start {
+                        stack.last_mut().unwrap().push(Item::int(&current()));
+                        start = i;
+                        stack.push(Vec::new());
+                    }
+
+                    is_digit = false;
+                }
+            }
+        }
+
+        if chars.len() > start {
+            let rest = chars[start..].iter().collect::<String>();
+
+            stack.last_mut().unwrap().push(Item::parse(&rest, is_digit));
+        }
+
+        while stack.len() > 1 {
+            let mut list = stack.pop().unwrap();
+
+            normalize(&mut list);
+            stack.last_mut().unwrap().push(Item::List(list));
+        }
+
+        let mut root = stack.pop().unwrap();
+
+        normalize(&mut root);
+
+        Self {
+            raw,
+            items: Item::List(root),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    pub fn is_snapshot(&self) -> bool {
+        self.raw.ends_with("-SNAPSHOT")
+    }
+}
+
+impl Display for MavenVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl PartialEq for MavenVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for MavenVersion {}
+
+impl PartialOrd for MavenVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MavenVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.items.compare(Some(&other.items))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MavenVersion;
+
+    fn v(version: &str) -> MavenVersion {
+        MavenVersion::parse(version)
+    }
+
+    fn assert_ascending(versions: &[&str]) {
+        for pair in versions.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+
+            assert!(v(a) < v(b), "{a} should be below {b}");
+        }
+    }
+
+    #[test]
+    fn qualifiers_sort_in_maven_order() {
+        assert_ascending(&[
+            "1.0-alpha",
+            "1.0-beta",
+            "1.0-milestone",
+            "1.0-rc",
+            "1.0-SNAPSHOT",
+            "1.0",
+            "1.0-sp",
+        ]);
+    }
+
+    #[test]
+    fn unknown_qualifiers_sort_after_known_ones() {
+        assert_ascending(&["1.0", "1.0-sp", "1.0-abc", "1.0-xyz"]);
+    }
+
+    #[test]
+    fn snapshots_come_before_their_release() {
+        assert_ascending(&["1.0-SNAPSHOT", "1.0", "1.0.1-SNAPSHOT", "1.0.1"]);
+        assert!(v("1.0-SNAPSHOT").is_snapshot());
+        assert!(!v("1.0").is_snapshot());
+    }
+
+    #[test]
+    fn numbers_compare_as_numbers() {
+        assert_ascending(&["1.2", "1.9", "1.10", "1.100", "2"]);
+        assert_ascending(&["1-1", "1.1"]);
+    }
+
+    #[test]
+    fn equivalent_spellings_are_equal() {
+        assert_eq!(v("1"), v("1.0.0"));
+        assert_eq!(v("1.0-ga"), v("1.0"));
+        assert_eq!(v("1.0-final"), v("1.0"));
+        assert_eq!(v("1.0-a1"), v("1.0-alpha-1"));
+        assert_eq!(v("1.0-b2"), v("1.0-beta-2"));
+        assert_eq!(v("1.0-cr1"), v("1.0-rc1"));
+        assert_eq!(v("1.0-RC1"), v("1.0-rc1"));
+    }
+}
@@ -12,7 +12,20 @@ pub async fn worker_thread(notify: Arc<Notify>, cx: Arc<RouteContext>) -> Result
     info!("Started upload worker thread!");
 
     loop {
-        notify.notified().await;
+        tokio::select! {
+            _ = notify.notified() => {}
+
+            _ = cx.drain.stopped() => {
+                // Anything published while draining might not have been indexed yet.
+                info!("Finishing up indexing...");
+
+                cx.index_dirs(&mut conn).await?;
+
+                info!("Stopped upload worker thread!");
+
+                return Ok(());
+            }
+        }
 
         debug!("Re-indexing dirs...");
 
@@ -0,0 +1,275 @@
+use super::models::{QuotaUsage, RouteQuota, TokenQuota, UploadAllowance};
+use crate::{
+    cx::RouteContext,
+    err::MavenError,
+    files::hashes::HASH_TYPES,
+    schema::{files, route_quotas, token_quotas, tokens},
+    staging::models::StagedFile,
+    tokens::models::MavenToken,
+    util::escape_like,
+};
+use anyhow::Result;
+use diesel::{
+    ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper, TextExpressionMethods,
+    dsl::sql, pg::Pg, sql_types::BigInt,
+};
+use diesel_async::{AsyncConnection, RunQueryDsl};
+
+/// `SUM` of a `BIGINT` is a `NUMERIC` in Postgres, so cast it back.
+const SUM_SIZE: &str = "CAST(COALESCE(SUM(size), 0) AS BIGINT)";
+
+impl RouteContext {
+    /// Get the quota for the most specific route a path is under.
+    pub async fn get_route_quota_inner(
+        &self,
+        path: impl AsRef<str>,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<Option<RouteQuota>> {
+        let path = path.as_ref();
+
+        Ok(route_quotas::table
+            .select(RouteQuota::as_select())
+            .load(conn)
+            .await?
+            .into_iter()
+            .filter(|it| path.starts_with(&it.path))
+            .max_by_key(|it| it.path.len()))
+    }
+
+    pub async fn get_token_quota_inner(
+        &self,
+        token: i32,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<Option<TokenQuota>> {
+        Ok(token_quotas::table
+            .filter(token_quotas::token.eq(token))
+            .select(TokenQuota::as_select())
+            .first(conn)
+            .await
+            .optional()?)
+    }
+
+    /// The total size of every file under a route.
+    pub async fn get_route_usage_inner(
+        &self,
+        prefix: impl AsRef<str>,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<i64> {
+        Ok(files::table
+            .filter(files::path.like(format!("{}%", escape_like(prefix))))
+            .select(sql::<BigInt>(SUM_SIZE))
+            .get_result(conn)
+            .await?)
+    }
+
+    /// The total size of every file uploaded with a token.
+    pub async fn get_token_usage_inner(
+        &self,
+        token: i32,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<i64> {
+        Ok(files::table
+            .filter(files::uploader.eq(token))
+            .select(sql::<BigInt>(SUM_SIZE))
+            .get_result(conn)
+            .await?)
+    }
+
+    /// Work out how big an upload to a path can be, counting whatever it replaces as
+    /// freed. This also takes the upload out of the token's rate limit, so only call it
+    /// once per upload.
+    pub async fn upload_allowance(
+        &self,
+        token: &MavenToken,
+        path: impl AsRef<str>,
+    ) -> Result<UploadAllowance> {
+        let mut conn = self.pool.get().await?;
+        let path = format!("/{}", path.as_ref()).replace("//", "/");
+        let mut allowance = UploadAllowance::default();
+
+        let token_quota = self.get_token_quota_inner(token.id, &mut conn).await?;
+        let route_quota = self.get_route_quota_inner(&path, &mut conn).await?;
+
+        // Checksums get sent for every file and aren't stored, so they'd just eat
+        // into the rate limit for nothing.
+        let checksum = HASH_TYPES
+            .iter()
+            .any(|it| path.ends_with(&format!(".{it}")));
+
+        if let Some(per_minute) = token_quota.as_ref().and_then(|it| it.uploads_per_minute) {
+            if !checksum {
+                if let Err(wait) = self.upload_limiter.check(token.id, per_minute as u32) {
+                    return Err(MavenError::TooManyRequests(
+                        format!(
+                            "Token {} is limited to {per_minute} uploads a minute",
+                            token.name
+                        ),
+                        wait.as_secs().max(1),
+                    )
+                    .into());
+                }
+            }
+        }
+
+        let existing = self.get_file_inner(&path, &mut conn).await.ok();
+
+        if let Some(rules) = self.get_route_rules_inner(&path, &mut conn).await? {
+            if let Some(max) = rules.max_size {
+                allowance.limit(max, format!("the file size limit for {}", rules.path));
+            }
+        }
+
+        if let Some(quota) = &route_quota {
+            if let Some(max) = quota.max_storage {
+                let used = self.get_route_usage_inner(&quota.path, &mut conn).await?
+                    - existing.as_ref().map(|it| it.size).unwrap_or_default();
+
+                allowance.limit(max - used, format!("the storage quota for {}", quota.path));
+            }
+        }
+
+        if let Some(quota) = &token_quota {
+            if let Some(max) = quota.max_file_size {
+                allowance.limit(max, format!("the file size limit for token {}", token.name));
+            }
+
+            if let Some(max) = quota.max_storage {
+                let used = self.get_token_usage_inner(token.id, &mut conn).await?
+                    - existing
+                        .as_ref()
+                        .filter(|it| it.uploader == Some(token.id))
+                        .map(|it| it.size)
+                        .unwrap_or_default();
+
+                allowance.limit(
+                    max - used,
+                    format!("the storage quota for token {}", token.name),
+                );
+            }
+        }
+
+        Ok(allowance)
+    }
+
+    /// Check that publishing staged files all at once keeps their token and routes
+    /// within their storage quotas, counting whatever they replace as freed. Staged
+    /// files are only held to the quotas one at a time until this.
+    pub async fn check_staging_quotas_inner(
+        &self,
+        token: i32,
+        staged: &[StagedFile],
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<()> {
+        let mut replaced = Vec::new();
+        let mut quotas = Vec::<RouteQuota>::new();
+
+        for file in staged {
+            if let Ok(existing) = self.get_file_inner(&file.path, conn).await {
+                replaced.push(existing);
+            }
+
+            if let Some(quota) = self.get_route_quota_inner(&file.path, conn).await? {
+                if !quotas.iter().any(|it| it.id == quota.id) {
+                    quotas.push(quota);
+                }
+            }
+        }
+
+        for quota in quotas {
+            let Some(max) = quota.max_storage else {
+                continue;
+            };
+
+            let added = staged
+                .iter()
+                .filter(|it| it.path.starts_with(&quota.path))
+                .map(|it| it.size)
+                .sum::<i64>();
+
+            let freed = replaced
+                .iter()
+                .filter(|it| it.path.starts_with(&quota.path))
+                .map(|it| it.size)
+                .sum::<i64>();
+
+            if self.get_route_usage_inner(&quota.path, conn).await? - freed + added > max {
+                return Err(MavenError::PayloadTooLarge(format!(
+                    "Promoting this would go over the storage quota for {}",
+                    quota.path
+                ))
+                .into());
+            }
+        }
+
+        let max = self
+            .get_token_quota_inner(token, conn)
+            .await?
+            .and_then(|it| it.max_storage);
+
+        if let Some(max) = max {
+            let added = staged.iter().map(|it| it.size).sum::<i64>();
+
+            let freed = replaced
+                .iter()
+                .filter(|it| it.uploader == Some(token))
+                .map(|it| it.size)
+                .sum::<i64>();
+
+            if self.get_token_usage_inner(token, conn).await? - freed + added > max {
+                return Err(MavenError::PayloadTooLarge(
+                    "Promoting this would go over the token's storage quota".into(),
+                )
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the usage of every route quota.
+    pub async fn get_route_quota_usage(&self) -> Result<Vec<QuotaUsage>> {
+        let mut conn = self.pool.get().await?;
+        let mut usage = Vec::new();
+
+        for quota in route_quotas::table
+            .order(route_quotas::path.asc())
+            .select(RouteQuota::as_select())
+            .load(&mut conn)
+            .await?
+        {
+            usage.push(QuotaUsage {
+                used: self.get_route_usage_inner(&quota.path, &mut conn).await?,
+                name: quota.path,
+                max_storage: quota.max_storage,
+                max_file_size: None,
+                uploads_per_minute: None,
+            });
+        }
+
+        Ok(usage)
+    }
+
+    /// Get the usage of every token, with its quota if it has one.
+    pub async fn get_token_quota_usage(&self) -> Result<Vec<QuotaUsage>> {
+        let mut conn = self.pool.get().await?;
+        let mut usage = Vec::new();
+
+        for (token, quota) in tokens::table
+            .left_join(token_quotas::table)
+            .order(tokens::name.asc())
+            .select((MavenToken::as_select(), Option::<TokenQuota>::as_select()))
+            .load::<(MavenToken, Option<TokenQuota>)>(&mut conn)
+            .await?
+        {
+            usage.push(QuotaUsage {
+                used: self.get_token_usage_inner(token.id, &mut conn).await?,
+                name: token.name,
+                max_storage: quota.as_ref().and_then(|it| it.max_storage),
+                max_file_size: quota.as_ref().and_then(|it| it.max_file_size),
+                uploads_per_minute: quota.as_ref().and_then(|it| it.uploads_per_minute),
+            });
+        }
+
+        Ok(usage)
+    }
+}
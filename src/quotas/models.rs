@@ -0,0 +1,104 @@
+use crate::err::MavenError;
+use chrono::NaiveDateTime;
+use humansize::{WINDOWS, format_size};
+
+/// Storage limits for everything under a route.
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable, Identifiable, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::route_quotas)]
+pub struct RouteQuota {
+    pub id: i32,
+    pub path: String,
+
+    /// The most data that can be stored under the route, in bytes. Per-file limits are
+    /// part of the route's rules instead.
+    pub max_storage: Option<i64>,
+    pub created: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable, AsChangeset)]
+#[diesel(table_name = crate::schema::route_quotas)]
+#[diesel(treat_none_as_null = true)]
+pub struct RouteQuotaIn {
+    pub path: String,
+
+    #[serde(default)]
+    pub max_storage: Option<i64>,
+}
+
+/// Storage and rate limits for everything uploaded with a token.
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable, Identifiable, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::token_quotas)]
+pub struct TokenQuota {
+    pub id: i32,
+    pub token: i32,
+
+    /// The most data the token's uploads can add up to, in bytes.
+    pub max_storage: Option<i64>,
+
+    /// The largest file allowed, in bytes.
+    pub max_file_size: Option<i64>,
+
+    /// How many files the token can upload per minute. Checksum files don't count.
+    pub uploads_per_minute: Option<i32>,
+    pub created: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable, AsChangeset)]
+#[diesel(table_name = crate::schema::token_quotas)]
+#[diesel(treat_none_as_null = true)]
+pub struct TokenQuotaIn {
+    pub token: i32,
+    pub max_storage: Option<i64>,
+    pub max_file_size: Option<i64>,
+    pub uploads_per_minute: Option<i32>,
+}
+
+/// How much of a quota is in use.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaUsage {
+    /// The route path or token name.
+    pub name: String,
+
+    /// Bytes stored.
+    pub used: i64,
+    pub max_storage: Option<i64>,
+    pub max_file_size: Option<i64>,
+    pub uploads_per_minute: Option<i32>,
+}
+
+/// How big a single upload can be, and why.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UploadAllowance {
+    /// The most bytes the upload can have, if there's a limit.
+    pub max: Option<u64>,
+
+    /// What set the limit, for error messages.
+    pub reason: String,
+}
+
+impl UploadAllowance {
+    /// Lower the limit to `max` bytes, if it isn't already lower.
+    pub fn limit(&mut self, max: i64, reason: impl Into<String>) {
+        let max = max.max(0) as u64;
+
+        if self.max.is_none_or(|it| max < it) {
+            self.max = Some(max);
+            self.reason = reason.into();
+        }
+    }
+
+    pub fn check(&self, size: u64) -> Result<(), MavenError> {
+        match self.max {
+            Some(max) if size > max => Err(self.too_large()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn too_large(&self) -> MavenError {
+        MavenError::PayloadTooLarge(format!(
+            "Upload is over {} ({})",
+            format_size(self.max.unwrap_or_default(), WINDOWS.decimal_places(2)),
+            self.reason
+        ))
+    }
+}
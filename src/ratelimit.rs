@@ -0,0 +1,263 @@
+//! Token buckets and lockouts, for keeping clients from hammering the server.
+
+use crate::err::MavenError;
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    net::IpAddr,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+/// Once a map holds this many keys, stale entries get dropped.
+const PRUNE_AT: usize = 10_000;
+
+/// Entries that haven't been touched for this long are stale.
+const STALE_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// Failures further apart than this don't count as being in a row.
+const FAILURE_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+tokio::task_local! {
+    /// Where the request being handled came from, if the auth throttle could tell.
+    pub static CLIENT_IP: IpAddr;
+}
+
+/// The IP of the client behind the current request, if there is one.
+pub fn current_client_ip() -> Option<IpAddr> {
+    CLIENT_IP.try_with(|it| *it).ok()
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|it| it.into_inner())
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// A token bucket per key.
+#[derive(Debug)]
+pub struct RateLimiter<K> {
+    buckets: Mutex<HashMap<K, Bucket>>,
+}
+
+impl<K> Default for RateLimiter<K> {
+    fn default() -> Self {
+        Self {
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<K: Hash + Eq> RateLimiter<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take one from a key's bucket, which holds `per_minute` and refills at that rate.
+    /// If it's empty, get how long until the next one is free.
+    pub fn check(&self, key: K, per_minute: u32) -> Result<(), Duration> {
+        let capacity = per_minute.max(1) as f64;
+        let rate = capacity / 60.0;
+        let now = Instant::now();
+        let mut buckets = lock(&self.buckets);
+
+        if buckets.len() >= PRUNE_AT {
+            buckets.retain(|_, it| now.duration_since(it.updated) < STALE_AFTER);
+        }
+
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+        }
+    }
+
+    /// Forget a key's bucket, like when its limit changes.
+    pub fn reset(&self, key: &K) {
+        lock(&self.buckets).remove(key);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Failures {
+    count: u32,
+    until: Option<Instant>,
+    updated: Instant,
+}
+
+/// Locks keys out after too many failures in a row, for twice as long each time.
+#[derive(Debug)]
+pub struct Lockout<K> {
+    threshold: u32,
+    base: Duration,
+    max: Duration,
+    failures: Mutex<HashMap<K, Failures>>,
+}
+
+impl<K: Hash + Eq> Lockout<K> {
+    pub fn new(threshold: u32, base: Duration, max: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            base,
+            max,
+            failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// If a key is locked out, get how long it has left.
+    pub fn check(&self, key: &K) -> Result<(), Duration> {
+        let now = Instant::now();
+
+        match lock(&self.failures).get(key).and_then(|it| it.until) {
+            Some(until) if until > now => Err(until - now),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn fail(&self, key: K) {
+        let now = Instant::now();
+        let mut failures = lock(&self.failures);
+
+        if failures.len() >= PRUNE_AT {
+            failures.retain(|_, it| {
+                now.duration_since(it.updated) < STALE_AFTER || it.until.is_some_and(|it| it > now)
+            });
+        }
+
+        let entry = failures.entry(key).or_insert(Failures {
+            count: 0,
+            until: None,
+            updated: now,
+        });
+
+        // Counted from the end of the last lockout, so long lockouts keep doubling.
+        let last = entry
+            .until
+            .map_or(entry.updated, |it| it.max(entry.updated));
+
+        if now.saturating_duration_since(last) > FAILURE_WINDOW {
+            entry.count = 0;
+            entry.until = None;
+        }
+
+        entry.count += 1;
+        entry.updated = now;
+
+        if entry.count >= self.threshold {
+            let doublings = (entry.count - self.threshold).min(31);
+            let wait = self.base.saturating_mul(1 << doublings).min(self.max);
+
+            entry.until = Some(now + wait);
+        }
+    }
+
+    pub fn succeed(&self, key: &K) {
+        lock(&self.failures).remove(key);
+    }
+}
+
+/// Limits on authentication attempts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthLimits {
+    /// Authenticated requests each client IP can make per minute. Successful ones count
+    /// too, so this should be well above what a busy build sends.
+    pub per_minute: u32,
+
+    /// Failures in a row before a lockout.
+    pub lockout_threshold: u32,
+
+    /// How long the first lockout lasts. Each failure after that doubles it.
+    pub lockout: Duration,
+
+    /// The longest a lockout can last.
+    pub max_lockout: Duration,
+
+    /// Take client IPs from `X-Forwarded-For`, for running behind a reverse proxy.
+    pub trust_forwarded_for: bool,
+}
+
+impl Default for AuthLimits {
+    fn default() -> Self {
+        Self {
+            per_minute: 6000,
+            lockout_threshold: 5,
+            lockout: Duration::from_secs(30),
+            max_lockout: Duration::from_secs(60 * 60),
+            trust_forwarded_for: false,
+        }
+    }
+}
+
+/// Rate limits and lockouts for authentication by client IP, and lockouts by token name
+/// (from each IP). Every attempt costs an Argon2 hash, so these keep it from being
+/// brute-forced or used to tie up the server. Token lockouts are per IP, so nobody can
+/// lock a token out for everyone else by getting its password wrong.
+#[derive(Debug)]
+pub struct AuthLimiter {
+    pub limits: AuthLimits,
+    ips: RateLimiter<IpAddr>,
+    ip_lockout: Lockout<IpAddr>,
+    token_lockout: Lockout<(String, Option<IpAddr>)>,
+}
+
+fn too_many(what: String, wait: Duration) -> MavenError {
+    MavenError::TooManyRequests(
+        format!("Too many attempts for {what}, try again later"),
+        wait.as_secs().max(1),
+    )
+}
+
+impl AuthLimiter {
+    pub fn new(limits: AuthLimits) -> Self {
+        let lockout = || Lockout::new(limits.lockout_threshold, limits.lockout, limits.max_lockout);
+
+        Self {
+            ips: RateLimiter::new(),
+            ip_lockout: lockout(),
+            token_lockout: lockout(),
+            limits,
+        }
+    }
+
+    pub fn check_ip(&self, ip: IpAddr) -> Result<(), MavenError> {
+        self.ip_lockout
+            .check(&ip)
+            .and_then(|_| self.ips.check(ip, self.limits.per_minute))
+            .map_err(|wait| too_many(ip.to_string(), wait))
+    }
+
+    /// IPs don't get a matching `ip_succeeded`, since a response that isn't a 401
+    /// doesn't mean the credentials were actually checked. Failures expire instead.
+    pub fn ip_failed(&self, ip: IpAddr) {
+        self.ip_lockout.fail(ip);
+    }
+
+    pub fn check_token(&self, name: &str, ip: Option<IpAddr>) -> Result<(), MavenError> {
+        self.token_lockout
+            .check(&(name.to_string(), ip))
+            .map_err(|wait| too_many(format!("token {name}"), wait))
+    }
+
+    pub fn token_failed(&self, name: &str, ip: Option<IpAddr>) {
+        self.token_lockout.fail((name.to_string(), ip));
+    }
+
+    pub fn token_succeeded(&self, name: &str, ip: Option<IpAddr>) {
+        self.token_lockout.succeed(&(name.to_string(), ip));
+    }
+}
@@ -0,0 +1,225 @@
+use super::{
+    models::{ReplicaStatus, ReplicationEvent, ReplicationEventIn},
+    target::Replica,
+};
+use crate::{cx::RouteContext, schema::replication_outbox, util::retry_backoff};
+use anyhow::{Result, anyhow};
+use chrono::Utc;
+use diesel::{
+    ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper, delete, dsl::min,
+    insert_into, pg::Pg, update,
+};
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+/// How many events to deliver between checking the outbox again.
+const BATCH_SIZE: i64 = 100;
+
+/// How long to wait between checks when nothing wakes the worker up.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+pub const UPLOAD_EVENT: &str = "upload";
+pub const DELETE_EVENT: &str = "delete";
+
+impl RouteContext {
+    /// Queue a change to a path for every replica. This should happen in the same
+    /// transaction as the change itself, so nothing gets lost.
+    pub async fn record_replication_inner(
+        &self,
+        event: &str,
+        path: impl AsRef<str>,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<()> {
+        if self.replicas.is_empty() {
+            return Ok(());
+        }
+
+        let path = path.as_ref();
+
+        insert_into(replication_outbox::table)
+            .values(
+                self.replicas
+                    .iter()
+                    .map(|it| ReplicationEventIn {
+                        target: it.name.clone(),
+                        event: event.into(),
+                        path: path.into(),
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Deliver whatever is due in the outbox. Returns how many events were looked at, so
+    /// a full batch means there's probably more waiting.
+    pub async fn replicate_pending(&self) -> Result<usize> {
+        let mut conn = self.pool.get().await?;
+        let now = Utc::now().naive_utc();
+
+        let events = replication_outbox::table
+            .select(ReplicationEvent::as_select())
+            .filter(replication_outbox::next_attempt.le(now))
+            .filter(
+                replication_outbox::target
+                    .eq_any(self.replicas.iter().map(|it| &it.name).collect::<Vec<_>>()),
+            )
+            .order_by(replication_outbox::id.asc())
+            .limit(BATCH_SIZE)
+            .load(&mut conn)
+            .await?;
+
+        // Delivering a path copies whatever is there now, so once it's been done, any
+        // other events for that path in the batch are already taken care of.
+        let mut done = HashSet::new();
+
+        for event in &events {
+            let key = (event.target.clone(), event.path.clone());
+
+            if done.contains(&key) {
+                continue;
+            }
+
+            let Some(replica) = self.replicas.iter().find(|it| it.name == event.target) else {
+                continue;
+            };
+
+            done.insert(key);
+
+            match self.deliver(replica, event).await {
+                Ok(()) => {
+                    delete(replication_outbox::table)
+                        .filter(replication_outbox::target.eq(&event.target))
+                        .filter(replication_outbox::path.eq(&event.path))
+                        .filter(replication_outbox::id.le(event.id))
+                        .execute(&mut conn)
+                        .await?;
+                }
+
+                Err(err) => {
+                    warn!(
+                        "Could not replicate {} to {}: {err}",
+                        event.path, event.target
+                    );
+
+                    update(replication_outbox::table)
+                        .filter(replication_outbox::target.eq(&event.target))
+                        .filter(replication_outbox::path.eq(&event.path))
+                        .filter(replication_outbox::id.le(event.id))
+                        .set((
+                            replication_outbox::attempts.eq(replication_outbox::attempts + 1),
+                            replication_outbox::next_attempt
+                                .eq(Utc::now().naive_utc() + retry_backoff(event.attempts)),
+                            replication_outbox::last_error.eq(err.to_string()),
+                        ))
+                        .execute(&mut conn)
+                        .await?;
+                }
+            }
+        }
+
+        Ok(events.len())
+    }
+
+    /// Make a path on a replica match what's here right now. Events can come out of
+    /// order after a failure, so they're only a hint about which paths changed.
+    async fn deliver(&self, replica: &Replica, event: &ReplicationEvent) -> Result<()> {
+        match self.get_file(&event.path).await {
+            Ok(file) => {
+                if file.quarantined {
+                    return Err(anyhow!("{} is quarantined", file.path));
+                }
+
+                let bytes = file.get_bytes(&self.storage).await?;
+
+                replica.put(&file, bytes).await
+            }
+
+            // Anything else (like the database being down) is retried, rather than
+            // taken to mean the file's gone.
+            Err(err)
+                if matches!(
+                    err.downcast_ref::<diesel::result::Error>(),
+                    Some(diesel::result::Error::NotFound)
+                ) =>
+            {
+                replica.delete(&event.path).await
+            }
+
+            Err(err) => Err(err),
+        }
+    }
+
+    pub async fn get_replication_status(&self) -> Result<Vec<ReplicaStatus>> {
+        let mut conn = self.pool.get().await?;
+        let now = Utc::now().naive_utc();
+        let mut statuses = Vec::new();
+
+        for replica in &self.replicas {
+            let pending = replication_outbox::table
+                .filter(replication_outbox::target.eq(&replica.name))
+                .count()
+                .get_result::<i64>(&mut conn)
+                .await?;
+
+            let failing = replication_outbox::table
+                .filter(replication_outbox::target.eq(&replica.name))
+                .filter(replication_outbox::attempts.gt(0))
+                .count()
+                .get_result::<i64>(&mut conn)
+                .await?;
+
+            let oldest_pending = replication_outbox::table
+                .filter(replication_outbox::target.eq(&replica.name))
+                .select(min(replication_outbox::created))
+                .get_result(&mut conn)
+                .await?;
+
+            let last_error = replication_outbox::table
+                .filter(replication_outbox::target.eq(&replica.name))
+                .filter(replication_outbox::last_error.is_not_null())
+                .select(replication_outbox::last_error)
+                .order_by(replication_outbox::id.desc())
+                .first::<Option<String>>(&mut conn)
+                .await
+                .optional()?
+                .flatten();
+
+            statuses.push(ReplicaStatus {
+                name: replica.name.clone(),
+                kind: replica.kind.into(),
+                pending,
+                failing,
+                oldest_pending,
+                lag_seconds: oldest_pending.map_or(0, |it| (now - it).num_seconds().max(0)),
+                last_error,
+            });
+        }
+
+        Ok(statuses)
+    }
+}
+
+/// Deliver outbox events as they come in, for as long as the server runs.
+pub async fn replication_thread(cx: Arc<RouteContext>) {
+    info!("Replicating to {} target(s)!", cx.replicas.len());
+
+    loop {
+        if cx.drain.is_draining() {
+            return;
+        }
+
+        match cx.replicate_pending().await {
+            Ok(count) if count as i64 >= BATCH_SIZE => continue,
+            Ok(_) => {}
+            Err(err) => warn!("Could not replicate: {err}"),
+        }
+
+        tokio::select! {
+            _ = cx.replication_notify.notified() => {}
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+        }
+    }
+}
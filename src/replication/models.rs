@@ -0,0 +1,48 @@
+use chrono::NaiveDateTime;
+
+/// Something that changed and still has to be copied to a replica. These are deleted
+/// once they've been delivered.
+#[derive(Debug, Clone, Serialize, Deserialize, Identifiable, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::replication_outbox)]
+pub struct ReplicationEvent {
+    pub id: i32,
+
+    /// The name of the replica this is for.
+    pub target: String,
+
+    /// `upload` or `delete`.
+    pub event: String,
+    pub path: String,
+    pub created: NaiveDateTime,
+    pub attempts: i32,
+    pub next_attempt: NaiveDateTime,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::replication_outbox)]
+pub struct ReplicationEventIn {
+    pub target: String,
+    pub event: String,
+    pub path: String,
+}
+
+/// How far behind a replica is.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicaStatus {
+    pub name: String,
+    pub kind: String,
+
+    /// Events that haven't been delivered yet.
+    pub pending: i64,
+
+    /// Pending events that have failed at least once.
+    pub failing: i64,
+
+    /// When the oldest pending event happened.
+    pub oldest_pending: Option<NaiveDateTime>,
+
+    /// How long the oldest pending event has been waiting, in seconds.
+    pub lag_seconds: i64,
+    pub last_error: Option<String>,
+}
@@ -0,0 +1,192 @@
+use crate::{
+    files::{hashes::HASH_TYPES, models::MavenFile},
+    s3::S3Config,
+};
+use anyhow::{Result, anyhow};
+use object_store::{ObjectStore, PutPayload, aws::AmazonS3, path::Path};
+use reqwest::{Client, Url};
+
+/// Somewhere uploads and deletes get copied to, as it appears in the config file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ReplicationTarget {
+    /// Another instance of this server, written to through its normal PUT and DELETE
+    /// API with a token that can write everywhere.
+    Server {
+        name: String,
+        url: String,
+        token_name: String,
+        token_value: String,
+    },
+
+    /// A bucket, written in the usual maven layout (with checksum files) so it can be
+    /// served as-is.
+    Store {
+        name: String,
+        region: String,
+        bucket: String,
+        access_key_id: String,
+        access_key_secret: String,
+        url: Option<String>,
+    },
+}
+
+impl ReplicationTarget {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Server { name, .. } | Self::Store { name, .. } => name,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Server { .. } => "server",
+            Self::Store { .. } => "store",
+        }
+    }
+}
+
+pub enum ReplicaClient {
+    Server {
+        http: Client,
+        url: Url,
+        token_name: String,
+        token_value: String,
+    },
+
+    Store(AmazonS3),
+}
+
+/// A replication target that's ready to be written to.
+pub struct Replica {
+    pub name: String,
+    pub kind: &'static str,
+    pub client: ReplicaClient,
+}
+
+impl Replica {
+    pub fn new(target: ReplicationTarget) -> Result<Self> {
+        let name = target.name().to_owned();
+        let kind = target.kind();
+
+        let client = match target {
+            ReplicationTarget::Server {
+                url,
+                token_name,
+                token_value,
+                ..
+            } => ReplicaClient::Server {
+                http: Client::builder()
+                    .user_agent(concat!("mvn/", env!("CARGO_PKG_VERSION")))
+                    .build()?,
+                url: Url::parse(&format!("{}/", url.trim_end_matches('/')))?,
+                token_name,
+                token_value,
+            },
+
+            ReplicationTarget::Store {
+                region,
+                bucket,
+                access_key_id,
+                access_key_secret,
+                url,
+                ..
+            } => ReplicaClient::Store(
+                S3Config {
+                    region,
+                    bucket,
+                    access_key_id,
+                    access_key_secret,
+                    url,
+                }
+                .build()?,
+            ),
+        };
+
+        Ok(Self { name, kind, client })
+    }
+
+    /// Write a file (and, for buckets, its checksums) to the replica.
+    pub async fn put(&self, file: &MavenFile, bytes: Vec<u8>) -> Result<()> {
+        match &self.client {
+            ReplicaClient::Server {
+                http,
+                url,
+                token_name,
+                token_value,
+            } => {
+                // The other end works out the checksums itself, and checks this one.
+                http.put(url.join(file.path.trim_start_matches('/'))?)
+                    .basic_auth(token_name, Some(token_value))
+                    .header("X-Checksum-Sha1", &file.sha1)
+                    .body(bytes)
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+
+            ReplicaClient::Store(store) => {
+                store
+                    .put(&store_path(&file.path), PutPayload::from(bytes))
+                    .await?;
+
+                for (alg, hash) in
+                    HASH_TYPES
+                        .iter()
+                        .zip([&file.md5, &file.sha1, &file.sha256, &file.sha512])
+                {
+                    store
+                        .put(
+                            &store_path(format!("{}.{alg}", file.path)),
+                            PutPayload::from(hash.clone().into_bytes()),
+                        )
+                        .await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remove a file from the replica. Files that are already gone are fine.
+    pub async fn delete(&self, path: impl AsRef<str>) -> Result<()> {
+        let path = path.as_ref();
+
+        match &self.client {
+            ReplicaClient::Server {
+                http,
+                url,
+                token_name,
+                token_value,
+            } => {
+                let res = http
+                    .delete(url.join(path.trim_start_matches('/'))?)
+                    .basic_auth(token_name, Some(token_value))
+                    .send()
+                    .await?;
+
+                if !res.status().is_success() && res.status() != 404 {
+                    return Err(anyhow!("{} returned {}", res.url(), res.status()));
+                }
+            }
+
+            ReplicaClient::Store(store) => {
+                let paths = std::iter::once(path.to_owned())
+                    .chain(HASH_TYPES.iter().map(|alg| format!("{path}.{alg}")));
+
+                for path in paths {
+                    match store.delete(&store_path(path)).await {
+                        Ok(()) | Err(object_store::Error::NotFound { .. }) => {}
+                        Err(err) => return Err(err.into()),
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn store_path(path: impl AsRef<str>) -> Path {
+    Path::from(path.as_ref().trim_start_matches('/'))
+}
@@ -1,10 +1,22 @@
+use super::models::{RouteData, RouteDataIn};
+use crate::{cx::RouteContext, schema::route_data};
 use anyhow::{Result, anyhow};
+use diesel::{
+    ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper, delete, insert_into, update,
+};
+use diesel_async::RunQueryDsl;
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[repr(i16)]
 pub enum RouteAccess {
+    #[serde(alias = "public")]
     Public = 0,
+
+    #[serde(alias = "hidden")]
     Hidden = 1,
+
+    #[serde(alias = "private")]
     Private = 2,
 }
 
@@ -33,6 +45,19 @@ impl Into<i16> for RouteAccess {
     }
 }
 
+impl FromStr for RouteAccess {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "public" => Ok(Self::Public),
+            "hidden" => Ok(Self::Hidden),
+            "private" => Ok(Self::Private),
+            _ => Err(anyhow!("Unknown access level: {s}")),
+        }
+    }
+}
+
 impl TryFrom<i16> for RouteAccess {
     type Error = anyhow::Error;
 
@@ -40,3 +65,57 @@ impl TryFrom<i16> for RouteAccess {
         Self::from_value(value)
     }
 }
+
+impl RouteContext {
+    pub async fn get_routes(&self) -> Result<Vec<RouteData>> {
+        Ok(route_data::table
+            .select(RouteData::as_select())
+            .order_by(route_data::path)
+            .load(&mut self.pool.get().await?)
+            .await?)
+    }
+
+    /// Set a route's access level. Its signing setting is only changed if one is given.
+    pub async fn set_route_access(&self, data: RouteDataIn) -> Result<RouteData> {
+        let mut conn = self.pool.get().await?;
+
+        let existing = route_data::table
+            .filter(route_data::path.eq(&data.path))
+            .select(RouteData::as_select())
+            .get_result(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(match existing {
+            Some(existing) => {
+                update(route_data::table)
+                    .filter(route_data::id.eq(existing.id))
+                    .set((
+                        route_data::visibility.eq(data.visibility),
+                        route_data::sign.eq(data.sign.unwrap_or(existing.sign)),
+                    ))
+                    .returning(RouteData::as_returning())
+                    .get_result(&mut conn)
+                    .await?
+            }
+
+            None => {
+                insert_into(route_data::table)
+                    .values(data)
+                    .returning(RouteData::as_returning())
+                    .get_result(&mut conn)
+                    .await?
+            }
+        })
+    }
+
+    /// Remove a route's settings, so it inherits its parent's. Returns whether there
+    /// were any.
+    pub async fn delete_route_access(&self, path: impl AsRef<str>) -> Result<bool> {
+        Ok(delete(route_data::table)
+            .filter(route_data::path.eq(path.as_ref()))
+            .execute(&mut self.pool.get().await?)
+            .await?
+            > 0)
+    }
+}
@@ -1,18 +1,17 @@
 use std::sync::Arc;
 
-use super::models::{RouteData, RouteDataIn};
+use super::models::{RouteData, RouteDataIn, RouteRules, RouteRulesIn};
 use crate::{
     auth::AnyAuth,
     cx::RouteContext,
-    err::AxumResponse,
+    err::{AxumError, AxumResponse, BASIC_CHALLENGE, HasCode, MavenError},
     router::{
         dash::AdminDashboard,
-        request::{DashboardQuery, DeleteRouteAccessData},
+        request::{DashboardQuery, DeleteRouteAccessData, DeleteRouteRulesData},
         stats::InstanceStats,
     },
-    schema::route_data,
+    schema::route_rules,
 };
-use anyhow::anyhow;
 use askama::Template;
 use axum::{
     Json,
@@ -22,8 +21,9 @@ use axum::{
 };
 use axum_auth::AuthBearer;
 use axum_extra::TypedHeader;
-use diesel::{ExpressionMethods, QueryDsl, SelectableHelper, delete, insert_into, update};
+use diesel::{ExpressionMethods, QueryDsl, SelectableHelper, delete, insert_into};
 use diesel_async::RunQueryDsl;
+use regex::Regex;
 
 #[axum::debug_handler]
 pub async fn set_route_access(
@@ -32,49 +32,100 @@ pub async fn set_route_access(
     Json(data): Json<RouteDataIn>,
 ) -> Result<Json<RouteData>, Response> {
     if !cx.validate_master_key(key).await.into_axum()? {
-        return Err(anyhow!("Invalid token!")).into_axum();
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
     }
 
-    let mut db = cx.pool.get().await.unwrap();
+    Ok(Json(cx.set_route_access(data).await.into_axum()?))
+}
 
-    let existing = route_data::table
-        .filter(route_data::path.eq(&data.path))
-        .select(RouteData::as_select())
-        .get_result(&mut db)
-        .await;
+#[axum::debug_handler]
+pub async fn delete_route_access(
+    State(cx): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+    Json(data): Json<DeleteRouteAccessData>,
+) -> Result<Response, Response> {
+    if !cx.validate_master_key(key).await.into_axum()? {
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
+    }
 
-    Ok(Json(if let Ok(_) = existing {
-        update(route_data::table)
-            .filter(route_data::path.eq(data.path))
-            .set(route_data::visibility.eq(data.visibility))
-            .returning(RouteData::as_returning())
-            .get_result(&mut db)
+    cx.delete_route_access(data.path).await.into_axum()?;
+
+    Ok(Response::builder()
+        .status(200)
+        .body("Success".into())
+        .unwrap())
+}
+
+#[axum::debug_handler]
+pub async fn get_route_rules(
+    State(cx): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+) -> Result<Json<Vec<RouteRules>>, Response> {
+    if !cx.validate_master_key(key).await.into_axum()? {
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
+    }
+
+    let mut db = cx.pool.get().await.into_axum()?;
+
+    Ok(Json(
+        route_rules::table
+            .order(route_rules::path.asc())
+            .select(RouteRules::as_select())
+            .load(&mut db)
             .await
-            .into_axum()?
-    } else {
-        insert_into(route_data::table)
-            .values(data)
-            .returning(RouteData::as_returning())
+            .into_axum()?,
+    ))
+}
+
+#[axum::debug_handler]
+pub async fn set_route_rules(
+    State(cx): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+    Json(data): Json<RouteRulesIn>,
+) -> Result<Json<RouteRules>, Response> {
+    if !cx.validate_master_key(key).await.into_axum()? {
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
+    }
+
+    // Catch bad patterns now, rather than on every upload.
+    if let Some(pattern) = &data.version_pattern {
+        if let Err(err) = Regex::new(pattern) {
+            return Err(MavenError::BadRequest(format!(
+                "Invalid version pattern: {err}"
+            )))
+            .into_axum();
+        }
+    }
+
+    let mut db = cx.pool.get().await.into_axum()?;
+
+    Ok(Json(
+        insert_into(route_rules::table)
+            .values(&data)
+            .on_conflict(route_rules::path)
+            .do_update()
+            .set(&data)
+            .returning(RouteRules::as_returning())
             .get_result(&mut db)
             .await
-            .into_axum()?
-    }))
+            .into_axum()?,
+    ))
 }
 
 #[axum::debug_handler]
-pub async fn delete_route_access(
+pub async fn delete_route_rules(
     State(cx): State<Arc<RouteContext>>,
     AuthBearer(key): AuthBearer,
-    Json(data): Json<DeleteRouteAccessData>,
+    Json(data): Json<DeleteRouteRulesData>,
 ) -> Result<Response, Response> {
     if !cx.validate_master_key(key).await.into_axum()? {
-        return Err(anyhow!("Invalid token!")).into_axum();
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
     }
 
-    let mut db = cx.pool.get().await.unwrap();
+    let mut db = cx.pool.get().await.into_axum()?;
 
-    delete(route_data::table)
-        .filter(route_data::path.eq(data.path))
+    delete(route_rules::table)
+        .filter(route_rules::path.eq(data.path))
         .execute(&mut db)
         .await
         .into_axum()?;
@@ -92,21 +143,23 @@ pub async fn auth_route(
 ) -> Response {
     let err = Response::builder()
         .status(401)
-        .header(WWW_AUTHENTICATE, "Basic realm=\"Maven\", charset=\"UTF-8\"")
+        .header(WWW_AUTHENTICATE, BASIC_CHALLENGE)
         .header(CONTENT_TYPE, "text/plain")
         .body("Authorizing...".into())
         .unwrap();
 
     if let Some(auth) = auth {
-        if let Ok(_) = auth.get_token(&cx).await {
-            Response::builder()
+        match auth.get_token(&cx).await {
+            Ok(_) => Response::builder()
                 .status(307)
                 .header(LOCATION, "/")
                 .header(CONTENT_TYPE, "text/plain")
                 .body("Authorized!".into())
-                .unwrap()
-        } else {
-            err
+                .unwrap(),
+
+            // Asking again won't help while it's throttled.
+            Err(it) if it.code() == 429 => it.into_axum(),
+            Err(_) => err,
         }
     } else {
         err
@@ -129,7 +182,7 @@ pub async fn stats_route(
     AuthBearer(key): AuthBearer,
 ) -> Result<Json<InstanceStats>, Response> {
     if !cx.validate_master_key(key).await.into_axum()? {
-        return Err(anyhow!("Invalid token!")).into_axum();
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
     }
 
     Ok(Json(cx.stats().await.into_axum()?))
@@ -141,7 +194,7 @@ pub async fn admin_dashboard_route(
     Query(query): Query<DashboardQuery>,
 ) -> Result<Response, Response> {
     if !cx.validate_master_key(&query.key).await.into_axum()? {
-        return Err(anyhow!("Invalid token!")).into_axum();
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
     }
 
     Ok(Response::builder()
@@ -0,0 +1,272 @@
+use super::{
+    checks::{AccessChecker, check_route_access},
+    common::resp_404,
+    templates::{
+        ArtifactTemplate, FileInfo, IndexTemplate, JarEntryInfo, JarTemplate, PomInfo,
+        SignatureInfo, VersionInfo, VersionTemplate,
+    },
+};
+use crate::{
+    auth::AnyAuth,
+    cx::RouteContext,
+    err::AxumResponse,
+    files::models::MavenFile,
+    maven::{coords::Coordinates, pom::Pom, version::MavenVersion},
+};
+use anyhow::Result;
+use askama::Template;
+use axum::response::Response;
+use axum_extra::TypedHeader;
+use itertools::Itertools;
+use std::{collections::HashMap, sync::Arc};
+
+/// Where the artifact pages live. It's outside anything maven could put in a
+/// repository, so it can't hide real files.
+pub const BROWSE_PREFIX: &str = "/-/browse";
+
+pub async fn browse_handler(
+    state: Arc<RouteContext>,
+    path: impl AsRef<str>,
+    auth: Option<TypedHeader<AnyAuth>>,
+) -> Result<Response, Response> {
+    if path.as_ref().ends_with(".jar") {
+        return jar_page(&state, path.as_ref(), &auth).await;
+    }
+
+    let folder = format!("/{}/", path.as_ref().trim_matches('/')).replace("//", "/");
+
+    debug!("Checking route access...");
+
+    let access = check_route_access(&state, &folder, &auth)
+        .await
+        .into_axum()?;
+
+    if !access.index {
+        return resp_404();
+    }
+
+    let checker = AccessChecker::new(&state, &auth).await.into_axum()?;
+
+    debug!("Fetching files under {folder}...");
+
+    let files = state.get_files_under(&folder).await.into_axum()?;
+
+    let direct = files
+        .iter()
+        .filter(|it| it.parent == folder && checker.check(&it.path).read)
+        .collect_vec();
+
+    if let Some(coords) = Coordinates::from_version_folder(&folder) {
+        let prefix = format!("{}-", coords.artifact);
+
+        let poms = direct
+            .iter()
+            .filter(|it| it.path.ends_with(".pom") && file_name(&it.path).starts_with(&prefix))
+            .collect_vec();
+
+        if !poms.is_empty() {
+            debug!("Found version folder!");
+
+            // Snapshots have timestamped POMs, so fall back to the newest one.
+            let pom = poms
+                .iter()
+                .find(|it| it.path == coords.pom_path())
+                .or_else(|| poms.iter().max_by_key(|it| it.uploaded))
+                .unwrap();
+
+            return version_page(&state, folder, coords, pom, &direct).await;
+        }
+    }
+
+    let Some((group, artifact)) = group_and_artifact(&folder) else {
+        return resp_404();
+    };
+
+    debug!("Collecting versions...");
+
+    let mut versions = HashMap::<String, &MavenFile>::new();
+
+    for file in &files {
+        let Some((version, name)) = file.path[folder.len()..].split_once("/") else {
+            continue;
+        };
+
+        if name.contains("/") || !name.ends_with(".pom") {
+            continue;
+        }
+
+        if !checker.check(format!("{folder}{version}/")).index {
+            continue;
+        }
+
+        let entry = versions.entry(version.to_string()).or_insert(file);
+
+        if file.uploaded > entry.uploaded {
+            *entry = file;
+        }
+    }
+
+    if versions.is_empty() {
+        return resp_404();
+    }
+
+    let mut latest_seen = false;
+
+    let versions = versions
+        .into_iter()
+        .map(|(version, pom)| (MavenVersion::parse(version), pom))
+        .sorted_by(|a, b| b.0.cmp(&a.0))
+        .map(|(version, pom)| {
+            let snapshot = version.is_snapshot();
+            let latest = !snapshot && !latest_seen;
+
+            latest_seen |= latest;
+
+            VersionInfo {
+                version: version.to_string(),
+                snapshot,
+                latest,
+                updated: pom.uploaded.format("%Y-%m-%d %I:%M %p UTC").to_string(),
+            }
+        })
+        .collect_vec();
+
+    let data = ArtifactTemplate {
+        title: format!("{group}:{artifact}"),
+        parts: IndexTemplate::breadcrumbs(&folder),
+        path: folder,
+        group,
+        artifact,
+        versions,
+    };
+
+    Ok(Response::builder()
+        .status(200)
+        .body(data.render().into_axum()?.into())
+        .into_axum()?)
+}
+
+async fn version_page(
+    state: &RouteContext,
+    folder: String,
+    coords: Coordinates,
+    pom: &MavenFile,
+    files: &[&MavenFile],
+) -> Result<Response, Response> {
+    debug!("Reading POM: {}", pom.path);
+
+    let pom = match read_pom(state, pom).await {
+        Ok(it) => Some(PomInfo::new(&it)),
+
+        Err(err) => {
+            warn!("Could not read POM {}: {err}", pom.path);
+            None
+        }
+    };
+
+    let by_id = files
+        .iter()
+        .map(|it| (it.id, *it))
+        .collect::<HashMap<_, _>>();
+
+    let signatures = state
+        .get_signatures(&by_id.keys().copied().collect_vec())
+        .await
+        .into_axum()?;
+
+    let mut signatures = SignatureInfo::by_file(&signatures, &by_id);
+
+    let files = files
+        .iter()
+        .map(|it| {
+            FileInfo::new(&it.path, it, it.size as u64)
+                .with_signatures(signatures.remove(&it.id).unwrap_or_default())
+        })
+        .sorted_by_key(|it| it.name.clone())
+        .collect_vec();
+
+    let data = VersionTemplate {
+        title: coords.to_string(),
+        parts: IndexTemplate::breadcrumbs(&folder),
+        path: folder,
+        snippets: coords.snippets(),
+        coords,
+        pom,
+        files,
+    };
+
+    Ok(Response::builder()
+        .status(200)
+        .body(data.render().into_axum()?.into())
+        .into_axum()?)
+}
+
+async fn jar_page(
+    state: &RouteContext,
+    path: &str,
+    auth: &Option<TypedHeader<AnyAuth>>,
+) -> Result<Response, Response> {
+    let access = check_route_access(state, path, auth).await.into_axum()?;
+
+    if !access.read {
+        return resp_404();
+    }
+
+    let Ok(jar) = state.get_file(path).await else {
+        return resp_404();
+    };
+
+    debug!("Fetching JAR entries...");
+
+    let (classes, resources): (Vec<_>, Vec<_>) = state
+        .get_jar_entries(&jar)
+        .await
+        .into_axum()?
+        .iter()
+        .map(JarEntryInfo::new)
+        .partition(|it| !it.class_name.is_empty());
+
+    let name = file_name(&jar.path).to_string();
+
+    let data = JarTemplate {
+        title: name.clone(),
+        parts: IndexTemplate::breadcrumbs(&jar.parent),
+        browse: format!("{BROWSE_PREFIX}{}", jar.parent),
+        path: jar.path,
+        name,
+        classes: classes
+            .into_iter()
+            .sorted_by(|a, b| a.class_name.cmp(&b.class_name))
+            .collect(),
+        resources,
+    };
+
+    Ok(Response::builder()
+        .status(200)
+        .body(data.render().into_axum()?.into())
+        .into_axum()?)
+}
+
+/// Read a POM from its indexed records, falling back to parsing the file itself.
+async fn read_pom(state: &RouteContext, file: &MavenFile) -> Result<Pom> {
+    match state.get_pom_for_file(file.id).await {
+        Ok(record) => state.load_pom(&record).await,
+        Err(_) => Pom::parse(file.get_bytes(&state.storage).await?),
+    }
+}
+
+fn file_name(path: &str) -> &str {
+    path.split("/").last().unwrap_or_default()
+}
+
+fn group_and_artifact(folder: &str) -> Option<(String, String)> {
+    let mut parts = folder.split("/").filter(|it| !it.is_empty()).collect_vec();
+
+    let artifact = parts.pop()?;
+
+    if parts.is_empty() {
+        return None;
+    }
+
+    Some((parts.join("."), artifact.into()))
+}
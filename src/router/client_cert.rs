@@ -0,0 +1,42 @@
+use crate::{
+    auth::{AnyAuth, CERTIFICATE_SCHEME},
+    https::ClientCertificate,
+};
+use axum::{
+    body::Body,
+    http::{Request, header::AUTHORIZATION},
+    middleware::Next,
+    response::Response,
+};
+
+/// Pass a connection's verified client certificate on to handlers as auth, unless the
+/// request has credentials of its own.
+pub async fn client_cert_middleware(mut req: Request<Body>, next: Next) -> Response {
+    // Clients don't get to claim a certificate just by sending the header.
+    let spoofed = req.headers().get_all(AUTHORIZATION).iter().any(|it| {
+        it.as_bytes()
+            .get(..CERTIFICATE_SCHEME.len())
+            .is_some_and(|it| it.eq_ignore_ascii_case(CERTIFICATE_SCHEME.as_bytes()))
+    });
+
+    if spoofed {
+        req.headers_mut().remove(AUTHORIZATION);
+    }
+
+    let cert = req
+        .extensions()
+        .get::<Option<ClientCertificate>>()
+        .cloned()
+        .flatten();
+
+    if let Some(cert) = cert {
+        if !req.headers().contains_key(AUTHORIZATION) {
+            let mut value = AnyAuth::Certificate(cert.fingerprint).encode();
+
+            value.set_sensitive(true);
+            req.headers_mut().insert(AUTHORIZATION, value);
+        }
+    }
+
+    next.run(req).await
+}
@@ -1,6 +1,82 @@
-use axum::{http::header::CONTENT_TYPE, response::Response};
+use axum::{
+    body::Bytes,
+    extract::{ConnectInfo, Request},
+    http::{
+        HeaderMap,
+        header::{ACCEPT, CONTENT_LENGTH, CONTENT_TYPE},
+    },
+    response::Response,
+};
+use http_body_util::{BodyExt, LengthLimitError, Limited};
+use std::net::{IpAddr, SocketAddr};
 
-use crate::err::AxumResponse;
+use crate::{
+    err::{AxumResponse, MavenError},
+    quotas::models::UploadAllowance,
+};
+
+/// Does the `Accept` header ask for JSON?
+pub fn accepts_json(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|it| it.to_str().ok())
+        .flat_map(|it| it.split(','))
+        .any(|it| it.trim().starts_with("application/json"))
+}
+
+/// The `Content-Length` a request says it has, if it says.
+pub fn content_length(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(CONTENT_LENGTH)
+        .and_then(|it| it.to_str().ok())
+        .and_then(|it| it.trim().parse().ok())
+}
+
+/// Read an upload, turning it away as soon as it's bigger than it's allowed to be.
+pub async fn read_upload(req: Request, allowance: &UploadAllowance) -> Result<Bytes, Response> {
+    // Turn away uploads that say they're too big before reading any of them.
+    if let Some(size) = content_length(req.headers()) {
+        allowance.check(size).into_axum()?;
+    }
+
+    let body = req.into_body();
+
+    // Lengths can be left out or lied about, so count what actually comes in too.
+    Ok(match allowance.max {
+        Some(max) => Limited::new(body, max as usize)
+            .collect()
+            .await
+            .map(|it| it.to_bytes())
+            .map_err(|err| {
+                if err.is::<LengthLimitError>() {
+                    allowance.too_large()
+                } else {
+                    MavenError::BadRequest(format!("Could not read upload: {err}"))
+                }
+            })
+            .into_axum()?,
+
+        None => body.collect().await.into_axum()?.to_bytes(),
+    })
+}
+
+/// Where a request came from. With `forwarded`, this trusts the first address in
+/// `X-Forwarded-For`, which only a reverse proxy should be setting.
+pub fn client_ip<B>(req: &Request<B>, forwarded: bool) -> Option<IpAddr> {
+    let header = forwarded
+        .then(|| req.headers().get("x-forwarded-for"))
+        .flatten()
+        .and_then(|it| it.to_str().ok())
+        .and_then(|it| it.split(',').next())
+        .and_then(|it| it.trim().parse().ok());
+
+    header.or_else(|| {
+        req.extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|it| it.0.ip())
+    })
+}
 
 pub fn resp_404() -> Result<Response, Response> {
     Ok(Response::builder()
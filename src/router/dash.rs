@@ -1,17 +1,19 @@
 use anyhow::Result;
 use askama::Template;
-use diesel::{QueryDsl, SelectableHelper};
-use diesel_async::RunQueryDsl;
+use humansize::{WINDOWS, format_size};
 use itertools::Itertools;
 
 use crate::{
     cx::RouteContext,
+    quotas::models::QuotaUsage,
+    replication::models::ReplicaStatus,
+    scrub::models::ScrubReport,
     router::{access::RouteAccess, models::RouteData, stats::InstanceStats},
-    schema::{route_data, token_paths, tokens},
     tokens::{
         models::{MavenToken, MavenTokenPath},
         perms::MavenTokenPermissions,
     },
+    webhooks::models::WebhookInfo,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
@@ -39,6 +41,7 @@ impl Into<HumanTokenPath> for MavenTokenPath {
 pub struct RouteInfo {
     pub path: String,
     pub access: String,
+    pub signed: bool,
 }
 
 impl Into<RouteInfo> for RouteData {
@@ -52,6 +55,30 @@ impl Into<RouteInfo> for RouteData {
                 _ => "Unknown",
             }
             .into(),
+            signed: self.sign,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct QuotaInfo {
+    pub name: String,
+    pub used: String,
+    pub max_storage: Option<String>,
+    pub max_file_size: Option<String>,
+    pub uploads_per_minute: Option<i32>,
+}
+
+impl Into<QuotaInfo> for QuotaUsage {
+    fn into(self) -> QuotaInfo {
+        let size = |it: i64| format_size(it.max(0) as u64, WINDOWS.decimal_places(2));
+
+        QuotaInfo {
+            name: self.name,
+            used: size(self.used),
+            max_storage: self.max_storage.map(size),
+            max_file_size: self.max_file_size.map(size),
+            uploads_per_minute: self.uploads_per_minute,
         }
     }
 }
@@ -63,39 +90,24 @@ pub struct AdminDashboard {
     pub tokens: Vec<(MavenToken, Vec<HumanTokenPath>)>,
     pub stats: InstanceStats,
     pub routes: Vec<RouteInfo>,
+    pub route_quotas: Vec<QuotaInfo>,
+    pub token_quotas: Vec<QuotaInfo>,
+    pub scrub: ScrubReport,
+    pub replicas: Vec<ReplicaStatus>,
+    pub webhooks: Vec<WebhookInfo>,
 }
 
 impl AdminDashboard {
     pub async fn get(master_key: String, cx: &RouteContext) -> Result<Self> {
-        let mut conn = cx.pool.get().await?;
-
-        let tokens = tokens::table
-            .left_join(token_paths::table)
-            .select((
-                MavenToken::as_select(),
-                Option::<MavenTokenPath>::as_select(),
-            ))
-            .load::<(MavenToken, Option<MavenTokenPath>)>(&mut conn)
+        let tokens = cx
+            .get_tokens_with_paths()
             .await?
             .into_iter()
-            .into_group_map()
-            .into_iter()
-            .map(|(token, paths)| {
-                (
-                    token,
-                    paths
-                        .into_iter()
-                        .filter_map(|it| it)
-                        .map(Into::into)
-                        .collect_vec(),
-                )
-            })
-            .sorted_by_cached_key(|it| it.0.created)
+            .map(|(token, paths)| (token, paths.into_iter().map(Into::into).collect_vec()))
             .collect_vec();
 
-        let routes = route_data::table
-            .select(RouteData::as_select())
-            .load(&mut conn)
+        let routes = cx
+            .get_routes()
             .await?
             .into_iter()
             .map(Into::into)
@@ -106,6 +118,21 @@ impl AdminDashboard {
             tokens,
             stats: cx.stats().await?,
             routes,
+            route_quotas: cx
+                .get_route_quota_usage()
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect_vec(),
+            token_quotas: cx
+                .get_token_quota_usage()
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect_vec(),
+            scrub: cx.get_scrub_report().await?,
+            replicas: cx.get_replication_status().await?,
+            webhooks: cx.get_webhooks().await?,
         })
     }
 }
@@ -0,0 +1,83 @@
+use super::{checks::AccessChecker, common::resp_404};
+use crate::{
+    auth::AnyAuth,
+    cx::RouteContext,
+    err::AxumResponse,
+    maven::{coords::Coordinates, resolve::Resolver},
+};
+use anyhow::Result;
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::{IntoResponse, Response},
+};
+use axum_extra::TypedHeader;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependentsQuery {
+    /// Also include the dependents of dependents.
+    #[serde(default)]
+    pub transitive: bool,
+}
+
+#[axum::debug_handler]
+pub async fn deps_route(
+    State(state): State<Arc<RouteContext>>,
+    Path((group, artifact, version)): Path<(String, String, String)>,
+    auth: Option<TypedHeader<AnyAuth>>,
+) -> Result<Response, Response> {
+    let coords = Coordinates::new(group, artifact, version);
+    let checker = AccessChecker::new(&state, &auth).await.into_axum()?;
+
+    debug!("Resolving dependencies of {coords}...");
+
+    match Resolver::new(&state, &checker)
+        .resolve(&coords)
+        .await
+        .into_axum()?
+    {
+        Some(graph) => Ok(Json(graph).into_response()),
+        None => resp_404(),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn artifact_dependents_route(
+    State(state): State<Arc<RouteContext>>,
+    Path((group, artifact)): Path<(String, String)>,
+    Query(query): Query<DependentsQuery>,
+    auth: Option<TypedHeader<AnyAuth>>,
+) -> Result<Response, Response> {
+    dependents(state, group, artifact, None, query, auth).await
+}
+
+#[axum::debug_handler]
+pub async fn version_dependents_route(
+    State(state): State<Arc<RouteContext>>,
+    Path((group, artifact, version)): Path<(String, String, String)>,
+    Query(query): Query<DependentsQuery>,
+    auth: Option<TypedHeader<AnyAuth>>,
+) -> Result<Response, Response> {
+    dependents(state, group, artifact, Some(version), query, auth).await
+}
+
+async fn dependents(
+    state: Arc<RouteContext>,
+    group: String,
+    artifact: String,
+    version: Option<String>,
+    query: DependentsQuery,
+    auth: Option<TypedHeader<AnyAuth>>,
+) -> Result<Response, Response> {
+    let checker = AccessChecker::new(&state, &auth).await.into_axum()?;
+
+    debug!("Finding dependents of {group}:{artifact}...");
+
+    let list = Resolver::new(&state, &checker)
+        .dependents(group, artifact, version, query.transitive)
+        .await
+        .into_axum()?;
+
+    Ok(Json(list).into_response())
+}
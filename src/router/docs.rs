@@ -114,7 +114,10 @@ pub async fn docs_handler(
             }
 
             let jar_file = state.get_file(&jar_path).await.into_axum()?;
-            let jar = jar_file.get_bytes(&state.storage).await.into_axum()?;
+            let jar = jar_file
+                .get_content(&jar_path, &state.storage)
+                .await
+                .into_axum()?;
             let mut zip = ZipArchive::new(Cursor::new(jar)).into_axum()?;
             let mut target = zip.by_name(&path).into_axum()?;
             let mut buf = Vec::new();
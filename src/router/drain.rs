@@ -0,0 +1,33 @@
+use crate::{
+    cx::RouteContext,
+    err::{AxumResponse, MavenError},
+};
+use axum::{
+    body::Body,
+    extract::State,
+    http::{Method, Request},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+/// Keep track of writes, so shutdown can wait for them, and refuse new ones once it's
+/// started. Reads carry on as usual until the server stops.
+pub async fn drain_middleware(
+    State(cx): State<Arc<RouteContext>>,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response, Response> {
+    if matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return Ok(next.run(req).await);
+    }
+
+    let Some(_guard) = cx.drain.enter() else {
+        return Err(MavenError::Unavailable(
+            "The server is shutting down, try again soon!".into(),
+        ))
+        .into_axum();
+    };
+
+    Ok(next.run(req).await)
+}
@@ -0,0 +1,73 @@
+use super::common::accepts_json;
+use crate::err::{BASIC_CHALLENGE, BEARER_CHALLENGE, ErrorBody, error_kind};
+use axum::{
+    body::{Body, to_bytes},
+    http::{
+        HeaderValue, Request,
+        header::{AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, WWW_AUTHENTICATE},
+    },
+    middleware::Next,
+    response::Response,
+};
+
+/// Error bodies are short messages. Anything bigger is left alone.
+const MAX_ERROR_BODY: usize = 64 * 1024;
+
+/// Make error responses consistent: 401s always carry a challenge, and clients that
+/// ask for JSON get an [`ErrorBody`] instead of plain text.
+pub async fn error_format_middleware(req: Request<Body>, next: Next) -> Response {
+    let json = accepts_json(req.headers());
+
+    // Master key routes use bearer auth, and everything else uses tokens over basic auth.
+    let challenge = match req.headers().get(AUTHORIZATION).map(|it| it.as_bytes()) {
+        Some(it) if it.len() > 7 && it[..7].eq_ignore_ascii_case(b"bearer ") => BEARER_CHALLENGE,
+        _ => BASIC_CHALLENGE,
+    };
+
+    let res = next.run(req).await;
+    let status = res.status();
+
+    if !status.is_client_error() && !status.is_server_error() {
+        return res;
+    }
+
+    let (mut parts, body) = res.into_parts();
+
+    if status.as_u16() == 401 && !parts.headers.contains_key(WWW_AUTHENTICATE) {
+        parts
+            .headers
+            .insert(WWW_AUTHENTICATE, HeaderValue::from_static(challenge));
+    }
+
+    let content_type = parts
+        .headers
+        .get(CONTENT_TYPE)
+        .and_then(|it| it.to_str().ok())
+        .unwrap_or("text/plain")
+        .to_string();
+
+    if !json || !content_type.starts_with("text/plain") {
+        return Response::from_parts(parts, body);
+    }
+
+    let Ok(bytes) = to_bytes(body, MAX_ERROR_BODY).await else {
+        return Response::from_parts(parts, Body::empty());
+    };
+
+    let body = ErrorBody {
+        status: status.as_u16(),
+        error: error_kind(status.as_u16()).into(),
+        message: String::from_utf8_lossy(&bytes).trim().into(),
+    };
+
+    parts.headers.remove(CONTENT_LENGTH);
+
+    parts
+        .headers
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+    Response::from_parts(
+        parts,
+        serde_json::to_string(&body).unwrap_or_default().into(),
+    )
+}
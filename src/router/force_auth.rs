@@ -1,4 +1,8 @@
-use crate::{auth::AnyAuth, cx::RouteContext};
+use crate::{
+    auth::AnyAuth,
+    cx::RouteContext,
+    err::{AxumError, BASIC_CHALLENGE, HasCode},
+};
 use axum::{
     body::Body,
     extract::{FromRequestParts, State},
@@ -41,7 +45,7 @@ pub async fn force_auth_middleware(
 
     let auth_resp = Response::builder()
         .status(401)
-        .header(WWW_AUTHENTICATE, "Basic realm=\"Maven\", charset=\"UTF-8\"")
+        .header(WWW_AUTHENTICATE, BASIC_CHALLENGE)
         .header(CONTENT_TYPE, "text/plain")
         .body("Authorizing...".into())
         .unwrap();
@@ -66,12 +70,15 @@ pub async fn force_auth_middleware(
         debug!("Checking auth...");
 
         if let Some(auth) = auth {
-            if let Ok(_) = auth.get_token(&cx).await {
-                debug!("Passing through...");
+            match auth.get_token(&cx).await {
+                Ok(_) => {
+                    debug!("Passing through...");
+
+                    next.run(req).await
+                }
 
-                next.run(req).await
-            } else {
-                auth_resp
+                Err(err) if err.code() == 429 => err.into_axum(),
+                Err(_) => auth_resp,
             }
         } else {
             auth_resp
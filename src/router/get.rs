@@ -1,9 +1,11 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use super::{
+    browse::BROWSE_PREFIX,
     checks::{AccessChecker, check_route_access},
     common::resp_404,
-    templates::{FileInfo, IndexTemplate},
+    listing::{DirListing, ListingEntry, ListingQuery},
+    templates::{FileInfo, IndexTemplate, SignatureInfo},
 };
 use crate::{auth::AnyAuth, cx::RouteContext, err::AxumResponse};
 use anyhow::Result;
@@ -15,6 +17,8 @@ pub async fn get_handler(
     state: Arc<RouteContext>,
     path: impl AsRef<str>,
     auth: Option<TypedHeader<AnyAuth>>,
+    query: ListingQuery,
+    json: bool,
 ) -> Result<Response, Response> {
     debug!("Checking route access...");
 
@@ -41,7 +45,9 @@ pub async fn get_handler(
                 .status(200)
                 .header(
                     CONTENT_TYPE,
-                    if path.ends_with(".md5")
+                    if path.ends_with(".asc") {
+                        "application/pgp-signature"
+                    } else if path.ends_with(".md5")
                         || path.ends_with(".sha1")
                         || path.ends_with(".sha256")
                         || path.ends_with(".sha512")
@@ -90,74 +96,139 @@ pub async fn get_handler(
 
                 let checker = AccessChecker::new(&state, &auth).await.into_axum()?;
 
+                debug!("Fetching folder contents...");
+
+                let stored = state
+                    .get_files_in_folder(path)
+                    .await
+                    .into_axum()?
+                    .into_iter()
+                    .map(|it| (it.path.clone(), it))
+                    .collect::<HashMap<_, _>>();
+
+                let by_id = stored
+                    .values()
+                    .map(|it| (it.id, it))
+                    .collect::<HashMap<_, _>>();
+
+                let signatures = state
+                    .get_signatures(&by_id.keys().copied().collect::<Vec<_>>())
+                    .await
+                    .into_axum()?;
+
+                let mut signatures = SignatureInfo::by_file(&signatures, &by_id);
+
                 for item in entries.iter() {
                     debug!("Checking: {item}");
 
-                    let acc = checker.check(item);
-
                     if item.ends_with("/") {
-                        if !acc.index {
+                        if !checker.check(item).index {
                             continue;
                         }
 
                         debug!("Is folder - OK");
 
-                        folders.push(
-                            item.trim_start_matches(&path)
-                                .trim_start_matches('/')
-                                .trim_end_matches('/')
-                                .into(),
-                        );
+                        folders.push(item.clone());
                     } else {
-                        if !acc.read {
+                        let file_path = format!("{}/{}", path, item).replace("//", "/");
+
+                        if !checker.check(&file_path).read {
                             continue;
                         }
 
                         debug!("Is file - OK");
 
-                        let file_path = format!("{}/{}", path, item).replace("//", "/");
-
-                        let Ok(file) = state.get_file_for_route(&file_path).await else {
+                        let Some(file) = stored.get(&state.get_path(&file_path)) else {
                             continue;
                         };
 
-                        files.push(FileInfo::new(
-                            &file_path,
-                            &file,
-                            file.get_size(&file_path, &state.storage)
-                                .await
-                                .into_axum()?,
-                        ));
+                        files.push((file_path, file));
                     }
                 }
 
+                if json {
+                    debug!("Building JSON listing...");
+
+                    let entries = folders
+                        .iter()
+                        .map(ListingEntry::folder)
+                        .chain(
+                            files
+                                .iter()
+                                .filter(|(route, file)| *route == file.path)
+                                .map(|(_, file)| {
+                                    let signatures = signatures
+                                        .get(&file.id)
+                                        .map(|sigs| {
+                                            sigs.iter()
+                                                .map(|it| format!("{}{}", file.parent, it.name))
+                                                .collect()
+                                        })
+                                        .unwrap_or_default();
+
+                                    ListingEntry::file(file, signatures)
+                                }),
+                        )
+                        .collect();
+
+                    let path = format!("{}/", path).replace("//", "/");
+
+                    return Ok(Response::builder()
+                        .status(200)
+                        .header(CONTENT_TYPE, "application/json")
+                        .body(
+                            serde_json::to_string(&DirListing::new(path, entries, &query))
+                                .into_axum()?
+                                .into(),
+                        )
+                        .into_axum()?);
+                }
+
                 debug!("Sorting index...");
 
+                let mut folders = folders
+                    .iter()
+                    .map(|it| {
+                        it.trim_start_matches(&path)
+                            .trim_start_matches('/')
+                            .trim_end_matches('/')
+                            .to_string()
+                    })
+                    .collect::<Vec<_>>();
+
+                let mut files = files
+                    .iter()
+                    .map(|(route, file)| {
+                        let info = FileInfo::new(route, file, file.get_stored_size(route)?);
+
+                        Ok(if *route == file.path {
+                            info.with_signatures(signatures.remove(&file.id).unwrap_or_default())
+                        } else {
+                            info
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()
+                    .into_axum()?;
+
                 folders.sort();
                 files.sort_by_key(|f| f.name.clone());
 
                 debug!("Building template...");
 
                 let path = format!("{}/", path).replace("//", "/");
-                let all = path.split("/").collect::<Vec<_>>();
-                let mut parts = Vec::new();
-                let mut seen = Vec::new();
-
-                for part in all {
-                    if part.is_empty() {
-                        continue;
-                    }
 
-                    seen.push(part.to_string());
-                    parts.push((part.to_string(), format!("/{}/", seen.join("/"))))
-                }
+                let browse = stored
+                    .keys()
+                    .any(|it| it.ends_with(".pom") || it.ends_with("/maven-metadata.xml"))
+                    .then(|| format!("{BROWSE_PREFIX}{path}"));
 
                 let data = IndexTemplate {
+                    parts: IndexTemplate::breadcrumbs(&path),
                     path,
                     files,
                     folders,
                     title: "The Broken Script Maven".into(),
-                    parts,
+                    browse,
                 };
 
                 debug!("Responding...");
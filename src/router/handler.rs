@@ -1,15 +1,25 @@
 use std::sync::Arc;
 
-use super::{docs::docs_handler, get::get_handler};
-use crate::{auth::AnyAuth, cx::RouteContext, err::AxumResponse};
-use anyhow::{Result, anyhow};
+use super::{
+    browse::{BROWSE_PREFIX, browse_handler},
+    common::read_upload,
+    docs::docs_handler,
+    get::get_handler,
+    listing::ListingQuery,
+};
+use crate::{
+    auth::AnyAuth,
+    cx::RouteContext,
+    err::{AxumResponse, MavenError},
+    files::hashes::ExpectedChecksums,
+};
+use anyhow::Result;
 use axum::{
-    extract::{Request, State},
+    extract::{Query, Request, State},
     http::Method,
-    response::Response,
+    response::{IntoResponse, Response},
 };
 use axum_extra::TypedHeader;
-use http_body_util::BodyExt;
 
 #[axum::debug_handler]
 pub async fn route_handler(
@@ -21,6 +31,14 @@ pub async fn route_handler(
 
     let path = req.uri().path().to_string();
 
+    if let Some(rest) = path.strip_prefix(BROWSE_PREFIX) {
+        if rest.starts_with('/') && *req.method() == Method::GET {
+            debug!("Serving browse route!");
+
+            return browse_handler(cx, rest, auth).await;
+        }
+    }
+
     if path.starts_with("/docs/") {
         debug!("Serving docs route!");
 
@@ -39,14 +57,25 @@ pub async fn route_handler(
     debug!("Matching method...");
 
     match *req.method() {
-        Method::GET => get_handler(cx, path, auth).await,
+        Method::GET => {
+            // Only listings use this, so a bad query shouldn't break a file download.
+            let query = match Query::<ListingQuery>::try_from_uri(req.uri()) {
+                Ok(it) => it.0,
+                Err(_) if cx.has_file(&path).await => ListingQuery::default(),
+                Err(err) => return Err(err.into_response()),
+            };
+
+            let json = query.wants_json(req.headers());
+
+            get_handler(cx, path, auth, query, json).await
+        }
 
         Method::PUT => {
             debug!("Fetching token...");
 
             let token = auth
                 .clone()
-                .ok_or(anyhow!("Auth is required!"))
+                .ok_or(MavenError::Unauthorized("Auth is required!".into()))
                 .into_axum()?
                 .get_token(&cx)
                 .await
@@ -55,17 +84,21 @@ pub async fn route_handler(
             debug!("Checking token...");
 
             if !token.can_write_to(&cx, &path).await.into_axum()? {
-                return Err(Response::builder()
-                    .status(403)
-                    .body("403 Access Denied".into())
-                    .into_axum()?);
+                return Err(MavenError::forbidden()).into_axum();
             }
 
+            debug!("Checking quotas...");
+
+            let allowance = cx.upload_allowance(&token, &path).await.into_axum()?;
+
             debug!("Queueing upload...");
 
-            let collected = req.into_body().collect().await.into_axum()?;
+            let expected = ExpectedChecksums::from_headers(req.headers());
+            let collected = read_upload(req, &allowance).await?;
 
-            cx.upload(&path, collected.to_bytes()).await.unwrap();
+            cx.upload(&path, collected, &expected, Some(token.id))
+                .await
+                .into_axum()?;
 
             // cx.queue_upload(req.into_body(), &path).await.into_axum()?;
 
@@ -81,17 +114,14 @@ pub async fn route_handler(
             debug!("Checking token...");
 
             let token = auth
-                .ok_or(anyhow!("Auth is required!"))
+                .ok_or(MavenError::Unauthorized("Auth is required!".into()))
                 .into_axum()?
                 .get_token(&cx)
                 .await
                 .into_axum()?;
 
             if !token.can_write_to(&cx, &path).await.into_axum()? {
-                return Err(Response::builder()
-                    .status(403)
-                    .body("403 Access Denied".into())
-                    .into_axum()?);
+                return Err(MavenError::forbidden()).into_axum();
             }
 
             debug!("Checking file...");
@@ -0,0 +1,143 @@
+use crate::{cx::RouteContext, db::has_pending_migrations};
+use anyhow::{Result, anyhow};
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use futures_util::StreamExt;
+use object_store::ObjectStore;
+use std::{
+    future::Future,
+    sync::{Arc, atomic::Ordering},
+    time::{Duration, Instant},
+};
+use tokio::time::timeout;
+
+/// How long any one dependency gets to answer before it counts as down.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Health {
+    pub status: String,
+    pub uptime_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyCheck {
+    pub ok: bool,
+    pub latency_ms: u64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Readiness {
+    /// `ready`, `degraded` or `draining`.
+    pub status: String,
+    pub database: DependencyCheck,
+    pub migrations: DependencyCheck,
+    pub storage: DependencyCheck,
+    pub index: DependencyCheck,
+}
+
+impl Readiness {
+    pub fn is_ready(&self) -> bool {
+        self.status == "ready"
+    }
+}
+
+async fn check(fut: impl Future<Output = Result<()>>) -> DependencyCheck {
+    let start = Instant::now();
+
+    let res = match timeout(CHECK_TIMEOUT, fut).await {
+        Ok(it) => it,
+        Err(_) => Err(anyhow!("Timed out after {}s", CHECK_TIMEOUT.as_secs())),
+    };
+
+    DependencyCheck {
+        ok: res.is_ok(),
+        latency_ms: start.elapsed().as_millis() as u64,
+        error: res.err().map(|it| it.to_string()),
+    }
+}
+
+impl RouteContext {
+    pub async fn check_readiness(&self) -> Readiness {
+        let (database, migrations, storage) = tokio::join!(
+            check(async {
+                // The pool verifies connections as it hands them out.
+                self.pool.get().await?;
+                Ok(())
+            }),
+            check(async {
+                match has_pending_migrations(&self.pool).await? {
+                    true => Err(anyhow!("There are pending migrations")),
+                    false => Ok(()),
+                }
+            }),
+            check(async {
+                // Just the first page, this only cares whether the bucket answers.
+                match self.storage.list(None).next().await {
+                    Some(Err(err)) => Err(err.into()),
+                    _ => Ok(()),
+                }
+            }),
+        );
+
+        let index = check(async {
+            match self.indexed.load(Ordering::SeqCst) {
+                true => Ok(()),
+                false => Err(anyhow!("The initial index hasn't finished yet")),
+            }
+        })
+        .await;
+
+        let ok = [&database, &migrations, &storage, &index]
+            .iter()
+            .all(|it| it.ok);
+
+        let status = if self.drain.is_draining() {
+            "draining"
+        } else if ok {
+            "ready"
+        } else {
+            "degraded"
+        };
+
+        Readiness {
+            status: status.into(),
+            database,
+            migrations,
+            storage,
+            index,
+        }
+    }
+}
+
+/// Whether the process is up at all. This never touches any dependencies.
+#[axum::debug_handler]
+pub async fn healthz_route(State(cx): State<Arc<RouteContext>>) -> Response {
+    Json(Health {
+        status: "ok".into(),
+        uptime_ms: cx.uptime().0,
+    })
+    .into_response()
+}
+
+/// Whether this instance should get traffic. This fails as soon as shutdown starts, so
+/// load balancers can move on before the listener goes away, and whenever the database
+/// or object store can't be reached.
+#[axum::debug_handler]
+pub async fn readyz_route(State(cx): State<Arc<RouteContext>>) -> Response {
+    let readiness = cx.check_readiness().await;
+
+    let status = match readiness.is_ready() {
+        true => StatusCode::OK,
+        false => StatusCode::SERVICE_UNAVAILABLE,
+    };
+
+    (status, Json(readiness)).into_response()
+}
@@ -0,0 +1,50 @@
+use super::request::StartImportData;
+use crate::{
+    cx::RouteContext,
+    err::{AxumResponse, MavenError},
+    import::{models::ImportJob, source::ImportSource},
+};
+use axum::{Json, extract::State, response::Response};
+use axum_auth::AuthBearer;
+use std::sync::Arc;
+
+#[axum::debug_handler]
+pub async fn get_imports_route(
+    State(cx): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+) -> Result<Json<Vec<ImportJob>>, Response> {
+    if !cx.validate_master_key(key).await.into_axum()? {
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
+    }
+
+    Ok(Json(cx.get_imports().await.into_axum()?))
+}
+
+/// Start an import in the background. Its progress shows up in [`get_imports_route`].
+#[axum::debug_handler]
+pub async fn start_import_route(
+    State(cx): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+    Json(data): Json<StartImportData>,
+) -> Result<Json<ImportJob>, Response> {
+    if !cx.validate_master_key(key).await.into_axum()? {
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
+    }
+
+    let source = data
+        .source
+        .parse::<ImportSource>()
+        .map_err(|err| MavenError::BadRequest(err.to_string()))
+        .into_axum()?;
+
+    let job = cx.create_import(&source, &data.prefix).await.into_axum()?;
+    let started = job.clone();
+
+    tokio::task::spawn(async move {
+        if let Err(err) = cx.run_import(job, source).await {
+            warn!("Could not import: {err}");
+        }
+    });
+
+    Ok(Json(started))
+}
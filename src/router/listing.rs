@@ -0,0 +1,208 @@
+use super::common::accepts_json;
+use crate::files::models::MavenFile;
+use axum::http::HeaderMap;
+use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
+
+pub const DEFAULT_PER_PAGE: usize = 100;
+pub const MAX_PER_PAGE: usize = 1000;
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ListingSort {
+    #[default]
+    Name,
+    Size,
+    Uploaded,
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ListingOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ListingQuery {
+    /// The response format, either `html` or `json`.
+    pub format: Option<String>,
+
+    /// The field to sort entries by.
+    #[serde(default)]
+    pub sort: ListingSort,
+
+    /// The sort direction.
+    #[serde(default)]
+    pub order: ListingOrder,
+
+    /// The page to return, starting at 1.
+    pub page: Option<usize>,
+
+    /// The number of entries per page.
+    pub per_page: Option<usize>,
+}
+
+impl ListingQuery {
+    /// Should the listing be sent as JSON, either because of the `format`
+    /// query parameter or the `Accept` header?
+    pub fn wants_json(&self, headers: &HeaderMap) -> bool {
+        match self.format.as_deref() {
+            Some("json") => true,
+            Some(_) => false,
+            None => accepts_json(headers),
+        }
+    }
+
+    pub fn page(&self) -> usize {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    pub fn per_page(&self) -> usize {
+        self.per_page
+            .unwrap_or(DEFAULT_PER_PAGE)
+            .clamp(1, MAX_PER_PAGE)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ListingEntry {
+    Folder {
+        name: String,
+        path: String,
+    },
+
+    File {
+        name: String,
+        path: String,
+        size: u64,
+        kind: String,
+        uploaded: DateTime<Utc>,
+        md5: String,
+        sha1: String,
+        sha256: String,
+        sha512: String,
+
+        /// Paths of detached signatures for this file.
+        signatures: Vec<String>,
+    },
+}
+
+impl ListingEntry {
+    pub fn folder(path: impl AsRef<str>) -> Self {
+        let path = path.as_ref();
+
+        Self::Folder {
+            name: path
+                .trim_end_matches('/')
+                .split("/")
+                .last()
+                .unwrap_or_default()
+                .into(),
+            path: path.into(),
+        }
+    }
+
+    pub fn file(file: &MavenFile, signatures: Vec<String>) -> Self {
+        Self::File {
+            name: file.path.split("/").last().unwrap_or_default().into(),
+            path: file.path.clone(),
+            size: file.size as u64,
+            kind: file.kind.clone(),
+            uploaded: file.uploaded.and_utc(),
+            md5: file.md5.clone(),
+            sha1: file.sha1.clone(),
+            sha256: file.sha256.clone(),
+            sha512: file.sha512.clone(),
+            signatures,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Folder { name, .. } => name,
+            Self::File { name, .. } => name,
+        }
+    }
+
+    pub fn is_folder(&self) -> bool {
+        matches!(self, Self::Folder { .. })
+    }
+
+    fn compare(&self, other: &Self, sort: ListingSort) -> Ordering {
+        match (self, other) {
+            (
+                Self::File {
+                    size: a_size,
+                    uploaded: a_uploaded,
+                    ..
+                },
+                Self::File {
+                    size: b_size,
+                    uploaded: b_uploaded,
+                    ..
+                },
+            ) => match sort {
+                ListingSort::Name => Ordering::Equal,
+                ListingSort::Size => a_size.cmp(b_size),
+                ListingSort::Uploaded => a_uploaded.cmp(b_uploaded),
+            }
+            .then_with(|| self.name().cmp(other.name())),
+
+            _ => self.name().cmp(other.name()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirListing {
+    pub path: String,
+    pub page: usize,
+    pub per_page: usize,
+    pub total: usize,
+    pub entries: Vec<ListingEntry>,
+}
+
+impl DirListing {
+    /// Sort and paginate the given entries. Folders always come before files.
+    pub fn new(
+        path: impl AsRef<str>,
+        mut entries: Vec<ListingEntry>,
+        query: &ListingQuery,
+    ) -> Self {
+        entries.sort_by(|a, b| {
+            b.is_folder().cmp(&a.is_folder()).then_with(|| {
+                let it = a.compare(b, query.sort);
+
+                match query.order {
+                    ListingOrder::Asc => it,
+                    ListingOrder::Desc => it.reverse(),
+                }
+            })
+        });
+
+        let page = query.page();
+        let per_page = query.per_page();
+        let total = entries.len();
+
+        let entries = entries
+            .into_iter()
+            .skip((page - 1).saturating_mul(per_page))
+            .take(per_page)
+            .collect();
+
+        Self {
+            path: path.as_ref().into(),
+            page,
+            per_page,
+            total,
+            entries,
+        }
+    }
+}
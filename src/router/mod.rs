@@ -2,7 +2,8 @@ use std::sync::Arc;
 
 use crate::cx::RouteContext;
 use admin::{
-    admin_dashboard_route, auth_route, delete_route_access, login_route, set_route_access,
+    admin_dashboard_route, auth_route, delete_route_access, delete_route_rules, get_route_rules,
+    login_route, set_route_access, set_route_rules,
 };
 use assets::{
     copy_svg_route, jbm_font_route, page_js_route, plus_svg_route, robots_txt_route,
@@ -10,33 +11,71 @@ use assets::{
 };
 use axum::{
     Router,
+    extract::DefaultBodyLimit,
     middleware::{from_fn, from_fn_with_state},
-    routing::{delete, get, put},
+    routing::{delete, get, post, put},
 };
+use client_cert::client_cert_middleware;
+use deps::{artifact_dependents_route, deps_route, version_dependents_route};
+use drain::drain_middleware;
+use errors::error_format_middleware;
 use force_auth::force_auth_middleware;
 use handler::route_handler;
+use health::{healthz_route, readyz_route};
+use imports::{get_imports_route, start_import_route};
 use logging::logging_middleware;
+use quotas::{
+    delete_route_quota, delete_token_quota, get_quotas, set_route_quota, set_token_quota,
+};
+use replication::get_replication_route;
+use scrub::{get_scrub_route, quarantine_route, release_route, start_scrub_route};
+use search::search_route;
+use signing::signing_key_route;
+use staging::{
+    drop_staging_route, get_staged_file_route, get_staging_route, list_staging_route,
+    open_staging_route, promote_staging_route, stage_file_route, validate_staging_route,
+};
+use throttle::auth_throttle_middleware;
 use tokens::{
-    add_path_route, delete_path_route, delete_token_route, get_token_paths_route, get_token_route,
-    new_token_route,
+    add_cert_route, add_path_route, delete_cert_route, delete_path_route, delete_token_route,
+    get_token_certs_route, get_token_paths_route, get_token_route, new_token_route,
+};
+use webhooks::{
+    create_webhook_route, delete_webhook_route, get_webhooks_route, test_webhook_route,
 };
 
 pub mod access;
 pub mod admin;
 pub mod assets;
+pub mod browse;
 pub mod checks;
+pub mod client_cert;
 pub mod common;
 pub mod dash;
+pub mod deps;
+pub mod drain;
 pub mod docs;
+pub mod errors;
 pub mod force_auth;
 pub mod get;
 pub mod handler;
+pub mod health;
+pub mod imports;
+pub mod listing;
 pub mod logging;
 pub mod models;
+pub mod quotas;
+pub mod replication;
 pub mod request;
+pub mod scrub;
+pub mod search;
+pub mod signing;
+pub mod staging;
 pub mod stats;
 pub mod templates;
+pub mod throttle;
 pub mod tokens;
+pub mod webhooks;
 
 pub fn build_router<S>(cx: Arc<RouteContext>) -> Router<S> {
     Router::new()
@@ -48,8 +87,55 @@ pub fn build_router<S>(cx: Arc<RouteContext>) -> Router<S> {
         .route("/api/token/paths", get(get_token_paths_route))
         .route("/api/token/paths", put(add_path_route))
         .route("/api/token/paths", delete(delete_path_route))
+        .route("/api/token/certs", get(get_token_certs_route))
+        .route("/api/token/certs", put(add_cert_route))
+        .route("/api/token/certs", delete(delete_cert_route))
         .route("/api/access", put(set_route_access))
         .route("/api/access", delete(delete_route_access))
+        .route("/api/rules", get(get_route_rules))
+        .route("/api/rules", put(set_route_rules))
+        .route("/api/rules", delete(delete_route_rules))
+        .route("/api/quotas", get(get_quotas))
+        .route("/api/quotas/routes", put(set_route_quota))
+        .route("/api/quotas/routes", delete(delete_route_quota))
+        .route("/api/quotas/tokens", put(set_token_quota))
+        .route("/api/quotas/tokens", delete(delete_token_quota))
+        .route("/api/replication", get(get_replication_route))
+        .route("/api/scrub", get(get_scrub_route))
+        .route("/api/scrub", post(start_scrub_route))
+        .route("/api/scrub/quarantine", put(quarantine_route))
+        .route("/api/scrub/quarantine", delete(release_route))
+        .route("/api/imports", get(get_imports_route))
+        .route("/api/imports", post(start_import_route))
+        .route("/api/webhooks", get(get_webhooks_route))
+        .route("/api/webhooks", put(create_webhook_route))
+        .route("/api/webhooks", delete(delete_webhook_route))
+        .route("/api/webhooks/test", post(test_webhook_route))
+        .route("/api/deps/{group}/{artifact}/{version}", get(deps_route))
+        .route(
+            "/api/dependents/{group}/{artifact}",
+            get(artifact_dependents_route),
+        )
+        .route(
+            "/api/dependents/{group}/{artifact}/{version}",
+            get(version_dependents_route),
+        )
+        .route("/api/search", get(search_route))
+        .route("/api/signing-key", get(signing_key_route))
+        .route("/api/staging", get(list_staging_route))
+        .route("/api/staging", post(open_staging_route))
+        .route("/api/staging/{key}", get(get_staging_route))
+        .route("/api/staging/{key}", delete(drop_staging_route))
+        .route("/api/staging/{key}/validate", post(validate_staging_route))
+        .route("/api/staging/{key}/promote", post(promote_staging_route))
+        .route(
+            "/api/staging/{key}/files/{*path}",
+            get(get_staged_file_route),
+        )
+        .route(
+            "/api/staging/{key}/files/{*path}",
+            put(stage_file_route).layer(DefaultBodyLimit::disable()),
+        )
         .route("/assets/fonts/jetbrains-mono.woff2", get(jbm_font_route))
         .route("/assets/js/page.js", get(page_js_route))
         .route("/robots.txt", get(robots_txt_route))
@@ -58,7 +144,13 @@ pub fn build_router<S>(cx: Arc<RouteContext>) -> Router<S> {
         .route("/admin/assets/plus.svg", get(plus_svg_route))
         .route("/admin/assets/trash.svg", get(trash_svg_route))
         .route("/login", get(login_route))
+        .route("/healthz", get(healthz_route))
+        .route("/readyz", get(readyz_route))
+        .layer(from_fn_with_state(Arc::clone(&cx), drain_middleware))
         .layer(from_fn(logging_middleware))
         .layer(from_fn_with_state(Arc::clone(&cx), force_auth_middleware))
+        .layer(from_fn_with_state(Arc::clone(&cx), auth_throttle_middleware))
+        .layer(from_fn(error_format_middleware))
+        .layer(from_fn(client_cert_middleware))
         .with_state(cx)
 }
@@ -7,6 +7,9 @@ pub struct RouteData {
     pub path: String,
     pub visibility: i16,
     pub created: NaiveDateTime,
+
+    /// Should uploads here be signed with the server's key?
+    pub sign: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
@@ -14,6 +17,59 @@ pub struct RouteData {
 pub struct RouteDataIn {
     pub path: String,
     pub visibility: i16,
+
+    /// Leaving this out keeps the current setting (or the default, for new routes).
+    #[serde(default)]
+    pub sign: Option<bool>,
+}
+
+/// Checks that uploads under a route have to pass.
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable, Identifiable, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::route_rules)]
+pub struct RouteRules {
+    pub id: i32,
+    pub path: String,
+
+    /// Uploaded POMs must parse and match the coordinates in their path, and
+    /// `maven-metadata.xml` can't list a version that has no POM. (Staging promotions
+    /// always need a POM in every version.)
+    pub require_pom: bool,
+
+    /// A regex that versions must fully match, like `\d+\.\d+\.\d+(-SNAPSHOT)?`.
+    pub version_pattern: Option<String>,
+
+    /// Comma-separated classifiers every JAR version needs on promotion, like
+    /// `sources,javadoc`.
+    pub required_classifiers: Option<String>,
+
+    /// The largest file allowed, in bytes.
+    pub max_size: Option<i64>,
+
+    /// Comma-separated file extensions that can't be uploaded, like `exe,dll`.
+    pub forbidden_extensions: Option<String>,
+    pub created: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable, AsChangeset)]
+#[diesel(table_name = crate::schema::route_rules)]
+#[diesel(treat_none_as_null = true)]
+pub struct RouteRulesIn {
+    pub path: String,
+
+    #[serde(default)]
+    pub require_pom: bool,
+
+    #[serde(default)]
+    pub version_pattern: Option<String>,
+
+    #[serde(default)]
+    pub required_classifiers: Option<String>,
+
+    #[serde(default)]
+    pub max_size: Option<i64>,
+
+    #[serde(default)]
+    pub forbidden_extensions: Option<String>,
 }
 
 impl RouteData {
@@ -0,0 +1,144 @@
+use super::request::{DeleteRouteQuotaData, DeleteTokenQuotaData, SetTokenQuotaData};
+use crate::{
+    cx::RouteContext,
+    err::{AxumResponse, MavenError},
+    quotas::models::{QuotaUsage, RouteQuota, RouteQuotaIn, TokenQuota, TokenQuotaIn},
+    schema::{route_quotas, token_quotas},
+};
+use axum::{Json, extract::State, response::Response};
+use axum_auth::AuthBearer;
+use diesel::{ExpressionMethods, SelectableHelper, delete, insert_into};
+use diesel_async::RunQueryDsl;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaReport {
+    pub routes: Vec<QuotaUsage>,
+    pub tokens: Vec<QuotaUsage>,
+}
+
+#[axum::debug_handler]
+pub async fn get_quotas(
+    State(cx): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+) -> Result<Json<QuotaReport>, Response> {
+    if !cx.validate_master_key(key).await.into_axum()? {
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
+    }
+
+    Ok(Json(QuotaReport {
+        routes: cx.get_route_quota_usage().await.into_axum()?,
+        tokens: cx.get_token_quota_usage().await.into_axum()?,
+    }))
+}
+
+#[axum::debug_handler]
+pub async fn set_route_quota(
+    State(cx): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+    Json(data): Json<RouteQuotaIn>,
+) -> Result<Json<RouteQuota>, Response> {
+    if !cx.validate_master_key(key).await.into_axum()? {
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
+    }
+
+    let mut db = cx.pool.get().await.into_axum()?;
+
+    Ok(Json(
+        insert_into(route_quotas::table)
+            .values(&data)
+            .on_conflict(route_quotas::path)
+            .do_update()
+            .set(&data)
+            .returning(RouteQuota::as_returning())
+            .get_result(&mut db)
+            .await
+            .into_axum()?,
+    ))
+}
+
+#[axum::debug_handler]
+pub async fn delete_route_quota(
+    State(cx): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+    Json(data): Json<DeleteRouteQuotaData>,
+) -> Result<Response, Response> {
+    if !cx.validate_master_key(key).await.into_axum()? {
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
+    }
+
+    let mut db = cx.pool.get().await.into_axum()?;
+
+    delete(route_quotas::table)
+        .filter(route_quotas::path.eq(data.path))
+        .execute(&mut db)
+        .await
+        .into_axum()?;
+
+    Ok(Response::builder()
+        .status(200)
+        .body("Success".into())
+        .unwrap())
+}
+
+#[axum::debug_handler]
+pub async fn set_token_quota(
+    State(cx): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+    Json(data): Json<SetTokenQuotaData>,
+) -> Result<Json<TokenQuota>, Response> {
+    if !cx.validate_master_key(key).await.into_axum()? {
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
+    }
+
+    let token = cx.get_token_by_name(data.token_name).await.into_axum()?;
+    let mut db = cx.pool.get().await.into_axum()?;
+
+    let quota = TokenQuotaIn {
+        token: token.id,
+        max_storage: data.max_storage,
+        max_file_size: data.max_file_size,
+        uploads_per_minute: data.uploads_per_minute,
+    };
+
+    let quota = insert_into(token_quotas::table)
+        .values(&quota)
+        .on_conflict(token_quotas::token)
+        .do_update()
+        .set(&quota)
+        .returning(TokenQuota::as_returning())
+        .get_result(&mut db)
+        .await
+        .into_axum()?;
+
+    cx.upload_limiter.reset(&token.id);
+
+    Ok(Json(quota))
+}
+
+#[axum::debug_handler]
+pub async fn delete_token_quota(
+    State(cx): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+    Json(data): Json<DeleteTokenQuotaData>,
+) -> Result<Response, Response> {
+    if !cx.validate_master_key(key).await.into_axum()? {
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
+    }
+
+    let token = cx.get_token_by_name(data.token_name).await.into_axum()?;
+    let mut db = cx.pool.get().await.into_axum()?;
+
+    delete(token_quotas::table)
+        .filter(token_quotas::token.eq(token.id))
+        .execute(&mut db)
+        .await
+        .into_axum()?;
+
+    cx.upload_limiter.reset(&token.id);
+
+    Ok(Response::builder()
+        .status(200)
+        .body("Success".into())
+        .unwrap())
+}
@@ -0,0 +1,21 @@
+use crate::{
+    cx::RouteContext,
+    err::{AxumResponse, MavenError},
+    replication::models::ReplicaStatus,
+};
+use axum::{Json, extract::State, response::Response};
+use axum_auth::AuthBearer;
+use std::sync::Arc;
+
+/// How far behind each replica is.
+#[axum::debug_handler]
+pub async fn get_replication_route(
+    State(cx): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+) -> Result<Json<Vec<ReplicaStatus>>, Response> {
+    if !cx.validate_master_key(key).await.into_axum()? {
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
+    }
+
+    Ok(Json(cx.get_replication_status().await.into_axum()?))
+}
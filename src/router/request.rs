@@ -1,4 +1,7 @@
-use crate::tokens::{models_in::MavenTokenIn, perms::MavenTokenPermissions};
+use crate::{
+    tokens::{models_in::MavenTokenIn, perms::MavenTokenPermissions},
+    webhooks::models::WebhookEvent,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AddTokenRouteData {
@@ -29,11 +32,95 @@ pub struct RemovePathRouteData {
     pub path: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddCertificateRouteData {
+    pub token_name: String,
+
+    /// The client certificate, as PEM.
+    pub certificate: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoveCertificateRouteData {
+    pub token_name: String,
+    pub fingerprint: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeleteRouteAccessData {
     pub path: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteRouteRulesData {
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteRouteQuotaData {
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetTokenQuotaData {
+    pub token_name: String,
+
+    #[serde(default)]
+    pub max_storage: Option<i64>,
+
+    #[serde(default)]
+    pub max_file_size: Option<i64>,
+
+    #[serde(default)]
+    pub uploads_per_minute: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteTokenQuotaData {
+    pub token_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartScrubQuery {
+    /// Stop serving files that fail.
+    #[serde(default)]
+    pub quarantine: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineData {
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartImportData {
+    /// A directory on the server, or the URL of another maven server.
+    pub source: String,
+
+    /// Where to put the imported files.
+    #[serde(default = "root_prefix")]
+    pub prefix: String,
+}
+
+fn root_prefix() -> String {
+    "/".into()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWebhookData {
+    pub url: String,
+
+    /// Only changes under this path trigger the webhook.
+    #[serde(default = "root_prefix")]
+    pub prefix: String,
+    pub events: Vec<WebhookEvent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookIdData {
+    pub id: i32,
+}
+
 impl Into<MavenTokenIn> for AddTokenRouteData {
     fn into(self) -> MavenTokenIn {
         match self.value {
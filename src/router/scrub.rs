@@ -0,0 +1,82 @@
+use super::request::{QuarantineData, StartScrubQuery};
+use crate::{
+    cx::RouteContext,
+    err::{AxumResponse, MavenError},
+    files::models::MavenFile,
+    scrub::models::ScrubReport,
+};
+use axum::{
+    Json,
+    extract::{Query, State},
+    response::Response,
+};
+use axum_auth::AuthBearer;
+use std::sync::{Arc, atomic::Ordering};
+
+#[axum::debug_handler]
+pub async fn get_scrub_route(
+    State(cx): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+) -> Result<Json<ScrubReport>, Response> {
+    if !cx.validate_master_key(key).await.into_axum()? {
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
+    }
+
+    Ok(Json(cx.get_scrub_report().await.into_axum()?))
+}
+
+/// Start a scrub in the background. It reads everything in storage, so it can take a
+/// while; progress shows up in [`get_scrub_route`].
+#[axum::debug_handler]
+pub async fn start_scrub_route(
+    State(cx): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+    Query(query): Query<StartScrubQuery>,
+) -> Result<Response, Response> {
+    if !cx.validate_master_key(key).await.into_axum()? {
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
+    }
+
+    if cx.scrubbing.load(Ordering::SeqCst) {
+        return Err(MavenError::Conflict("A scrub is already running!".into())).into_axum();
+    }
+
+    tokio::task::spawn(async move {
+        if let Err(err) = cx.scrub(query.quarantine).await {
+            warn!("Could not scrub storage: {err}");
+        }
+    });
+
+    Ok(Response::builder()
+        .status(202)
+        .body("Started".into())
+        .unwrap())
+}
+
+#[axum::debug_handler]
+pub async fn quarantine_route(
+    State(cx): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+    Json(data): Json<QuarantineData>,
+) -> Result<Json<MavenFile>, Response> {
+    if !cx.validate_master_key(key).await.into_axum()? {
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
+    }
+
+    Ok(Json(cx.set_quarantined(data.path, true).await.into_axum()?))
+}
+
+#[axum::debug_handler]
+pub async fn release_route(
+    State(cx): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+    Json(data): Json<QuarantineData>,
+) -> Result<Json<MavenFile>, Response> {
+    if !cx.validate_master_key(key).await.into_axum()? {
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
+    }
+
+    Ok(Json(
+        cx.set_quarantined(data.path, false).await.into_axum()?,
+    ))
+}
@@ -0,0 +1,348 @@
+use super::{browse::BROWSE_PREFIX, checks::AccessChecker};
+use crate::{
+    auth::AnyAuth,
+    cx::RouteContext,
+    err::{AxumResponse, MavenError},
+    maven::{coords::Coordinates, range::VersionRange, version::MavenVersion},
+};
+use anyhow::Result;
+use axum::{
+    Json,
+    extract::{Query, State},
+    response::{IntoResponse, Response},
+};
+use axum_extra::TypedHeader;
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use std::{collections::HashMap, sync::Arc};
+
+pub const DEFAULT_SEARCH_PER_PAGE: usize = 20;
+pub const MAX_SEARCH_PER_PAGE: usize = 100;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchQuery {
+    /// Free text, which can also contain `g:`, `a:`, `v:`, `sha1:` and `sha256:` terms.
+    pub q: Option<String>,
+    pub group: Option<String>,
+    pub artifact: Option<String>,
+
+    /// A version or a Maven version range, like `[1.0,2.0)`.
+    pub version: Option<String>,
+    pub sha1: Option<String>,
+    pub sha256: Option<String>,
+
+    /// A (partial) class name, like `com.example.Thing` or just `Thing`.
+    pub class: Option<String>,
+
+    /// A (partial) path of a file inside a JAR, like `META-INF/services/`.
+    pub resource: Option<String>,
+    pub page: Option<usize>,
+    pub per_page: Option<usize>,
+}
+
+/// A search query, with the terms in `q` split out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchTerms {
+    pub text: Vec<String>,
+    pub group: Option<String>,
+    pub artifact: Option<String>,
+    pub version: Option<String>,
+    pub sha1: Option<String>,
+    pub sha256: Option<String>,
+    pub class: Option<String>,
+    pub resource: Option<String>,
+}
+
+impl SearchTerms {
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+            && self.group.is_none()
+            && self.artifact.is_none()
+            && self.version.is_none()
+            && !self.has_hash()
+            && !self.has_entry()
+    }
+
+    pub fn has_entry(&self) -> bool {
+        self.class.is_some() || self.resource.is_some()
+    }
+
+    pub fn has_hash(&self) -> bool {
+        self.sha1.is_some() || self.sha256.is_some()
+    }
+
+    pub fn has_coordinates(&self) -> bool {
+        !self.text.is_empty()
+            || self.group.is_some()
+            || self.artifact.is_some()
+            || self.version.is_some()
+    }
+}
+
+impl SearchQuery {
+    pub fn page(&self) -> usize {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    pub fn per_page(&self) -> usize {
+        self.per_page
+            .unwrap_or(DEFAULT_SEARCH_PER_PAGE)
+            .clamp(1, MAX_SEARCH_PER_PAGE)
+    }
+
+    /// Split `q` into its terms. Explicit query parameters win over ones in `q`.
+    pub fn terms(&self) -> SearchTerms {
+        let mut terms = SearchTerms::default();
+
+        for word in self.q.as_deref().unwrap_or_default().split_whitespace() {
+            let (key, value) = word.split_once(':').unwrap_or(("", word));
+
+            if value.is_empty() {
+                continue;
+            }
+
+            match key {
+                "g" | "group" => terms.group = Some(value.into()),
+                "a" | "artifact" => terms.artifact = Some(value.into()),
+                "v" | "version" => terms.version = Some(value.into()),
+                "sha1" => terms.sha1 = Some(value.into()),
+                "sha256" => terms.sha256 = Some(value.into()),
+                "c" | "class" => terms.class = Some(value.into()),
+                "r" | "resource" => terms.resource = Some(value.into()),
+
+                // Bare hashes are common enough to be worth guessing at.
+                _ if is_hex(word) && word.len() == 40 => terms.sha1 = Some(word.into()),
+                _ if is_hex(word) && word.len() == 64 => terms.sha256 = Some(word.into()),
+
+                "" => terms.text.push(word.into()),
+
+                // Anything else with a colon is taken as `group:artifact[:version]`.
+                group => {
+                    let mut rest = value.splitn(2, ':');
+
+                    terms.group = Some(group.into());
+                    terms.artifact = rest.next().map(|it| it.into());
+                    terms.version = rest.next().map(|it| it.into());
+                }
+            }
+        }
+
+        terms.group = self.group.clone().or(terms.group);
+        terms.artifact = self.artifact.clone().or(terms.artifact);
+        terms.version = self.version.clone().or(terms.version);
+        terms.sha1 = self.sha1.clone().or(terms.sha1);
+        terms.sha256 = self.sha256.clone().or(terms.sha256);
+        terms.class = self.class.clone().or(terms.class);
+        terms.resource = self.resource.clone().or(terms.resource);
+
+        terms
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactHit {
+    pub group: String,
+    pub artifact: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub packaging: String,
+
+    /// The highest matching version.
+    pub latest: String,
+
+    /// Every matching version, newest first.
+    pub versions: Vec<String>,
+
+    /// The artifact page for this artifact.
+    pub link: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileHit {
+    pub path: String,
+    pub size: u64,
+    pub kind: String,
+    pub uploaded: DateTime<Utc>,
+    pub sha1: String,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryHit {
+    /// The path of the entry inside the JAR.
+    pub name: String,
+    pub class_name: Option<String>,
+    pub size: u64,
+
+    /// The path of the JAR.
+    pub jar: String,
+
+    /// The page listing everything in the JAR.
+    pub link: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchResults {
+    pub page: usize,
+    pub per_page: usize,
+
+    /// The total number of matching artifacts, across all pages.
+    pub total: usize,
+    pub artifacts: Vec<ArtifactHit>,
+    pub files: Vec<FileHit>,
+
+    /// The total number of matching JAR entries, across all pages.
+    pub total_entries: usize,
+    pub entries: Vec<EntryHit>,
+}
+
+#[axum::debug_handler]
+pub async fn search_route(
+    State(state): State<Arc<RouteContext>>,
+    Query(query): Query<SearchQuery>,
+    auth: Option<TypedHeader<AnyAuth>>,
+) -> Result<Response, Response> {
+    let terms = query.terms();
+
+    if terms.is_empty() {
+        return bad_request("Search query is empty!");
+    }
+
+    let range = match terms.version.as_deref().map(VersionRange::parse) {
+        Some(Ok(it)) => Some(it),
+        Some(Err(err)) => return bad_request(format!("Invalid version range: {err}")),
+        None => None,
+    };
+
+    let checker = AccessChecker::new(&state, &auth).await.into_axum()?;
+
+    debug!("Searching: {terms:?}");
+
+    let files = state
+        .get_files_by_hash(terms.sha1.as_deref(), terms.sha256.as_deref())
+        .await
+        .into_axum()?
+        .into_iter()
+        .filter(|it| checker.check(&it.path).read)
+        .map(|it| FileHit {
+            size: it.size as u64,
+            uploaded: it.uploaded.and_utc(),
+            path: it.path,
+            kind: it.kind,
+            sha1: it.sha1,
+            sha256: it.sha256,
+        })
+        .collect_vec();
+
+    let entries = match (&terms.class, &terms.resource) {
+        (Some(class), _) => state.search_jar_entries(class, true).await.into_axum()?,
+        (None, Some(resource)) => state
+            .search_jar_entries(resource, false)
+            .await
+            .into_axum()?,
+        (None, None) => Vec::new(),
+    };
+
+    let entries = entries
+        .into_iter()
+        .filter(|(_, jar)| checker.check(&jar.path).read)
+        .collect_vec();
+
+    let poms = if terms.has_coordinates() {
+        state
+            .search_poms(
+                &terms.text,
+                terms.group.as_deref(),
+                terms.artifact.as_deref(),
+            )
+            .await
+            .into_axum()?
+    } else {
+        Vec::new()
+    };
+
+    let mut artifacts = HashMap::<(String, String), Vec<_>>::new();
+
+    for pom in poms {
+        let coords = Coordinates::new(&pom.group_id, &pom.artifact_id, &pom.version);
+
+        if !checker.check(coords.version_folder()).index {
+            continue;
+        }
+
+        let version = MavenVersion::parse(&pom.version);
+
+        if range.as_ref().is_some_and(|it| !it.contains(&version)) {
+            continue;
+        }
+
+        artifacts
+            .entry((pom.group_id.clone(), pom.artifact_id.clone()))
+            .or_default()
+            .push((version, pom));
+    }
+
+    let total = artifacts.len();
+    let total_entries = entries.len();
+    let per_page = query.per_page();
+    let page = query.page();
+
+    let entries = entries
+        .into_iter()
+        .skip((page - 1).saturating_mul(per_page))
+        .take(per_page)
+        .map(|(entry, jar)| EntryHit {
+            link: format!("{BROWSE_PREFIX}{}", jar.path),
+            name: entry.name,
+            class_name: entry.class_name,
+            size: entry.size as u64,
+            jar: jar.path,
+        })
+        .collect_vec();
+
+    let artifacts = artifacts
+        .into_iter()
+        .sorted_by(|a, b| a.0.cmp(&b.0))
+        .skip((page - 1).saturating_mul(per_page))
+        .take(per_page)
+        .map(|((group, artifact), versions)| {
+            let versions = versions
+                .into_iter()
+                .sorted_by(|a, b| b.0.cmp(&a.0))
+                .dedup_by(|a, b| a.0 == b.0)
+                .collect_vec();
+
+            let (_, newest) = &versions[0];
+
+            ArtifactHit {
+                link: format!("{BROWSE_PREFIX}/{}/{}/", group.replace('.', "/"), artifact),
+                name: newest.name.clone(),
+                description: newest.description.clone(),
+                packaging: newest.packaging.clone(),
+                latest: newest.version.clone(),
+                versions: versions.iter().map(|(it, _)| it.to_string()).collect(),
+                group,
+                artifact,
+            }
+        })
+        .collect_vec();
+
+    Ok(Json(SearchResults {
+        page,
+        per_page,
+        total,
+        artifacts,
+        files,
+        total_entries,
+        entries,
+    })
+    .into_response())
+}
+
+fn bad_request(message: impl Into<String>) -> Result<Response, Response> {
+    Err(MavenError::BadRequest(message.into())).into_axum()
+}
+
+fn is_hex(value: &str) -> bool {
+    value.chars().all(|it| it.is_ascii_hexdigit())
+}
@@ -0,0 +1,26 @@
+use super::common::resp_404;
+use crate::{cx::RouteContext, err::AxumResponse};
+use axum::{
+    extract::State,
+    http::header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    response::Response,
+};
+use std::sync::Arc;
+
+/// The public half of the server's signing key, for verifying the `.asc` files it makes.
+#[axum::debug_handler]
+pub async fn signing_key_route(State(cx): State<Arc<RouteContext>>) -> Result<Response, Response> {
+    let Some(key) = &cx.signing_key else {
+        return resp_404();
+    };
+
+    Ok(Response::builder()
+        .status(200)
+        .header(CONTENT_TYPE, "application/pgp-keys")
+        .header(
+            CONTENT_DISPOSITION,
+            format!("inline; filename=\"{}.asc\"", key.fingerprint()),
+        )
+        .body(key.public_key().to_string().into())
+        .into_axum()?)
+}
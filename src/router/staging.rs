@@ -0,0 +1,230 @@
+use super::{common::read_upload, get::get_handler, listing::ListingQuery};
+use crate::{
+    auth::AnyAuth,
+    cx::RouteContext,
+    err::{AxumResponse, MavenError},
+    files::hashes::{ExpectedChecksums, HASH_TYPES},
+    staging::models::{InvalidStaging, StagedFile, StagingSession},
+    tokens::models::MavenToken,
+};
+use axum::{
+    Json,
+    extract::{Path, Request, State},
+    http::header::CONTENT_TYPE,
+    response::{IntoResponse, Response},
+};
+use axum_extra::TypedHeader;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenStagingData {
+    /// A note about what's being staged, like `my-lib 1.2.0`.
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StagingInfo {
+    #[serde(flatten)]
+    pub session: StagingSession,
+
+    /// Where to point `mvn deploy` (as `altDeploymentRepository`) to stage into this.
+    pub url: String,
+    pub files: Vec<StagedFile>,
+}
+
+async fn token(
+    cx: &RouteContext,
+    auth: Option<TypedHeader<AnyAuth>>,
+) -> Result<MavenToken, Response> {
+    auth.ok_or(MavenError::Unauthorized("Auth is required!".into()))
+        .into_axum()?
+        .get_token(cx)
+        .await
+        .into_axum()
+}
+
+async fn session(
+    cx: &RouteContext,
+    key: impl AsRef<str>,
+    auth: Option<TypedHeader<AnyAuth>>,
+) -> Result<(StagingSession, MavenToken), Response> {
+    let token = token(cx, auth).await?;
+    let session = cx.get_staging(key, &token).await.into_axum()?;
+
+    Ok((session, token))
+}
+
+async fn info(cx: &RouteContext, session: StagingSession) -> Result<StagingInfo, Response> {
+    Ok(StagingInfo {
+        url: format!("/api/staging/{}/files", session.key),
+        files: cx.get_staged_files(&session).await.into_axum()?,
+        session,
+    })
+}
+
+#[axum::debug_handler]
+pub async fn open_staging_route(
+    State(cx): State<Arc<RouteContext>>,
+    auth: Option<TypedHeader<AnyAuth>>,
+    Json(data): Json<OpenStagingData>,
+) -> Result<Json<StagingInfo>, Response> {
+    let token = token(&cx, auth).await?;
+    let session = cx
+        .open_staging(&token, data.description)
+        .await
+        .into_axum()?;
+
+    info!("Opened staging session {} for {}", session.key, token.name);
+
+    Ok(Json(info(&cx, session).await?))
+}
+
+#[axum::debug_handler]
+pub async fn list_staging_route(
+    State(cx): State<Arc<RouteContext>>,
+    auth: Option<TypedHeader<AnyAuth>>,
+) -> Result<Json<Vec<StagingSession>>, Response> {
+    let token = token(&cx, auth).await?;
+
+    Ok(Json(cx.get_staging_sessions(&token).await.into_axum()?))
+}
+
+#[axum::debug_handler]
+pub async fn get_staging_route(
+    State(cx): State<Arc<RouteContext>>,
+    Path(key): Path<String>,
+    auth: Option<TypedHeader<AnyAuth>>,
+) -> Result<Json<StagingInfo>, Response> {
+    let (session, _) = session(&cx, key, auth).await?;
+
+    Ok(Json(info(&cx, session).await?))
+}
+
+#[axum::debug_handler]
+pub async fn stage_file_route(
+    State(cx): State<Arc<RouteContext>>,
+    Path((key, path)): Path<(String, String)>,
+    auth: Option<TypedHeader<AnyAuth>>,
+    req: Request,
+) -> Result<Response, Response> {
+    let (session, token) = session(&cx, key, auth).await?;
+    let path = format!("/{path}");
+
+    if !token.can_write_to(&cx, &path).await.into_axum()? {
+        return Err(MavenError::forbidden()).into_axum();
+    }
+
+    // Each file is held to the same limits as a normal upload, and the whole session
+    // is checked against the storage quotas again when it's promoted.
+    let allowance = cx.upload_allowance(&token, &path).await.into_axum()?;
+    let expected = ExpectedChecksums::from_headers(req.headers());
+    let body = read_upload(req, &allowance).await?;
+
+    debug!("Staging {path} in {}...", session.key);
+
+    cx.stage(&session, &path, body, &expected)
+        .await
+        .into_axum()?;
+
+    Ok(Response::builder()
+        .status(200)
+        .body("Staged!".into())
+        .into_axum()?)
+}
+
+/// Get a file as it'd be after promotion. `mvn deploy` reads `maven-metadata.xml` before
+/// writing it, so anything that isn't staged comes from the live tree.
+#[axum::debug_handler]
+pub async fn get_staged_file_route(
+    State(cx): State<Arc<RouteContext>>,
+    Path((key, path)): Path<(String, String)>,
+    auth: Option<TypedHeader<AnyAuth>>,
+) -> Result<Response, Response> {
+    let (session, _) = session(&cx, key, auth.clone()).await?;
+    let path = format!("/{path}");
+    let real = cx.get_path(&path);
+
+    let Some(file) = cx.get_staged_file(&session, &real).await.into_axum()? else {
+        return get_handler(cx, path, auth, ListingQuery::default(), false).await;
+    };
+
+    if let Some(alg) = HASH_TYPES
+        .iter()
+        .find(|it| path.ends_with(&format!(".{it}")))
+    {
+        return Ok(Response::builder()
+            .status(200)
+            .header(CONTENT_TYPE, "text/plain")
+            .body(file.get_hash(alg).into_axum()?.into())
+            .into_axum()?);
+    }
+
+    Ok(Response::builder()
+        .status(200)
+        .body(file.get_bytes(&cx.storage).await.into_axum()?.into())
+        .into_axum()?)
+}
+
+#[axum::debug_handler]
+pub async fn validate_staging_route(
+    State(cx): State<Arc<RouteContext>>,
+    Path(key): Path<String>,
+    auth: Option<TypedHeader<AnyAuth>>,
+) -> Result<Response, Response> {
+    let (session, _) = session(&cx, key, auth).await?;
+
+    Ok(Json(cx.validate_staging(&session).await.into_axum()?).into_response())
+}
+
+/// Validate a staging session, then publish it all at once. Invalid sessions are left
+/// alone, and the report comes back with a 409.
+#[axum::debug_handler]
+pub async fn promote_staging_route(
+    State(cx): State<Arc<RouteContext>>,
+    Path(key): Path<String>,
+    auth: Option<TypedHeader<AnyAuth>>,
+) -> Result<Response, Response> {
+    let (session, _) = session(&cx, key, auth).await?;
+
+    let published = match cx.promote_staging(&session).await {
+        Ok(it) => it,
+
+        Err(err) => match err.downcast_ref::<InvalidStaging>() {
+            Some(InvalidStaging(report)) => {
+                return Err(Response::builder()
+                    .status(409)
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(serde_json::to_string(report).into_axum()?.into())
+                    .into_axum()?);
+            }
+
+            None => return Err(err).into_axum(),
+        },
+    };
+
+    info!(
+        "Promoted staging session {} ({} file(s))",
+        session.key,
+        published.len()
+    );
+
+    Ok(Json(published.into_iter().map(|it| it.path).collect::<Vec<_>>()).into_response())
+}
+
+#[axum::debug_handler]
+pub async fn drop_staging_route(
+    State(cx): State<Arc<RouteContext>>,
+    Path(key): Path<String>,
+    auth: Option<TypedHeader<AnyAuth>>,
+) -> Result<Response, Response> {
+    let (session, _) = session(&cx, key, auth).await?;
+
+    cx.drop_staging(&session).await.into_axum()?;
+
+    info!("Dropped staging session {}", session.key);
+
+    Ok(Response::builder()
+        .status(200)
+        .body("Dropped!".into())
+        .into_axum()?)
+}
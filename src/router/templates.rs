@@ -1,7 +1,12 @@
 use askama::Template;
 use humansize::WINDOWS;
+use std::collections::HashMap;
 
-use crate::files::models::MavenFile;
+use super::browse::BROWSE_PREFIX;
+use crate::{
+    files::models::{MavenFile, MavenFileSignature},
+    maven::{coords::Coordinates, models::MavenJarEntry, pom::Pom},
+};
 
 #[derive(Template)]
 #[template(path = "index.html")]
@@ -11,6 +16,84 @@ pub struct IndexTemplate {
     pub folders: Vec<String>,
     pub files: Vec<FileInfo>,
     pub parts: Vec<(String, String)>,
+    pub browse: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "artifact.html")]
+pub struct ArtifactTemplate {
+    pub title: String,
+    pub path: String,
+    pub group: String,
+    pub artifact: String,
+    pub parts: Vec<(String, String)>,
+    pub versions: Vec<VersionInfo>,
+}
+
+#[derive(Template)]
+#[template(path = "version.html")]
+pub struct VersionTemplate {
+    pub title: String,
+    pub path: String,
+    pub coords: Coordinates,
+    pub parts: Vec<(String, String)>,
+    pub pom: Option<PomInfo>,
+    pub files: Vec<FileInfo>,
+    pub snippets: Vec<(String, String)>,
+}
+
+#[derive(Template)]
+#[template(path = "jar.html")]
+pub struct JarTemplate {
+    pub title: String,
+    pub path: String,
+    pub name: String,
+    pub browse: String,
+    pub parts: Vec<(String, String)>,
+    pub classes: Vec<JarEntryInfo>,
+    pub resources: Vec<JarEntryInfo>,
+}
+
+#[derive(Debug, Clone)]
+pub struct VersionInfo {
+    pub version: String,
+    pub snapshot: bool,
+    pub latest: bool,
+    pub updated: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct PomInfo {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    pub packaging: String,
+    pub scm: Option<String>,
+    pub licenses: Vec<LicenseInfo>,
+    pub dependencies: Vec<DependencyInfo>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LicenseInfo {
+    pub name: String,
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DependencyInfo {
+    pub group: String,
+    pub artifact: String,
+    pub version: String,
+    pub scope: String,
+    pub optional: bool,
+    pub link: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct JarEntryInfo {
+    pub name: String,
+    pub class_name: String,
+    pub size_str: String,
 }
 
 #[derive(Debug, Clone)]
@@ -20,6 +103,14 @@ pub struct FileInfo {
     pub size_str: String,
     pub updated: String,
     pub docs: Option<String>,
+    pub signatures: Vec<SignatureInfo>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SignatureInfo {
+    pub name: String,
+    pub kind: String,
+    pub verified: bool,
 }
 
 impl IndexTemplate {
@@ -36,6 +127,86 @@ impl IndexTemplate {
 
         if path == "" { "/".into() } else { path }
     }
+
+    /// Split a folder path into `(name, full path)` pairs for the header links.
+    pub fn breadcrumbs(path: impl AsRef<str>) -> Vec<(String, String)> {
+        let mut parts = Vec::new();
+        let mut seen = Vec::new();
+
+        for part in path.as_ref().split("/") {
+            if part.is_empty() {
+                continue;
+            }
+
+            seen.push(part.to_string());
+            parts.push((part.to_string(), format!("/{}/", seen.join("/"))))
+        }
+
+        parts
+    }
+}
+
+impl PomInfo {
+    pub fn new(pom: &Pom) -> Self {
+        let dependencies = pom
+            .dependencies
+            .items
+            .iter()
+            .map(|dep| {
+                let group = pom.interpolate(&dep.group_id);
+                let artifact = pom.interpolate(&dep.artifact_id);
+
+                DependencyInfo {
+                    link: format!("{BROWSE_PREFIX}/{}/{}/", group.replace('.', "/"), artifact),
+                    version: dep
+                        .version
+                        .as_ref()
+                        .map(|it| pom.interpolate(it))
+                        .unwrap_or_else(|| "(managed)".into()),
+                    scope: dep.scope.clone().unwrap_or_else(|| "compile".into()),
+                    optional: dep.optional.as_deref() == Some("true"),
+                    group,
+                    artifact,
+                }
+            })
+            .collect();
+
+        Self {
+            name: pom.name.as_ref().map(|it| pom.interpolate(it)),
+            description: pom.description.as_ref().map(|it| it.trim().to_string()),
+            url: pom.url.as_ref().map(|it| pom.interpolate(it)),
+            packaging: pom.packaging(),
+            scm: pom
+                .scm
+                .as_ref()
+                .and_then(|it| it.url.as_ref())
+                .map(|it| pom.interpolate(it)),
+            licenses: pom
+                .licenses
+                .items
+                .iter()
+                .map(|it| LicenseInfo {
+                    name: it
+                        .name
+                        .clone()
+                        .or_else(|| it.url.clone())
+                        .unwrap_or_else(|| "Unknown".into()),
+                    url: it.url.clone(),
+                })
+                .collect(),
+            dependencies,
+        }
+    }
+}
+
+impl JarEntryInfo {
+    pub fn new(entry: &MavenJarEntry) -> Self {
+        Self {
+            name: entry.name.clone(),
+            class_name: entry.class_name.clone().unwrap_or_default(),
+            size_str: humansize::format_size(entry.size as usize, WINDOWS.decimal_places(2)),
+        }
+    }
 }
 
 impl FileInfo {
@@ -53,6 +224,7 @@ impl FileInfo {
             size_str: size.as_ref().into(),
             type_str: "Text File".into(),
             docs: None,
+            signatures: Vec::new(),
         }
     }
 
@@ -87,6 +259,37 @@ impl FileInfo {
             } else {
                 None
             },
+            signatures: Vec::new(),
         }
     }
+
+    pub fn with_signatures(mut self, signatures: Vec<SignatureInfo>) -> Self {
+        self.signatures = signatures;
+        self
+    }
+}
+
+impl SignatureInfo {
+    /// Group signatures by the file they sign. `files` is used to look up the names of
+    /// the signature files, so signatures not in it are skipped.
+    pub fn by_file(
+        signatures: &[MavenFileSignature],
+        files: &HashMap<i32, &MavenFile>,
+    ) -> HashMap<i32, Vec<SignatureInfo>> {
+        let mut map = HashMap::<i32, Vec<SignatureInfo>>::new();
+
+        for sig in signatures {
+            let Some(file) = files.get(&sig.signature) else {
+                continue;
+            };
+
+            map.entry(sig.file).or_default().push(SignatureInfo {
+                name: file.path.split("/").last().unwrap_or_default().into(),
+                kind: sig.kind.clone(),
+                verified: sig.verified,
+            });
+        }
+
+        map
+    }
 }
@@ -0,0 +1,41 @@
+use super::common::client_ip;
+use crate::{cx::RouteContext, err::AxumError, ratelimit::CLIENT_IP};
+use axum::{
+    body::Body,
+    extract::State,
+    http::{Request, header::AUTHORIZATION},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+/// Throttle requests that carry credentials by client IP, and count the ones that get
+/// a 401 towards a lockout. Requests without credentials never cost a hash, so they're
+/// left alone. The IP is kept around while the request is handled, for token lockouts.
+pub async fn auth_throttle_middleware(
+    State(cx): State<Arc<RouteContext>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    if !req.headers().contains_key(AUTHORIZATION) {
+        return next.run(req).await;
+    }
+
+    let Some(ip) = client_ip(&req, cx.auth_limiter.limits.trust_forwarded_for) else {
+        return next.run(req).await;
+    };
+
+    if let Err(err) = cx.auth_limiter.check_ip(ip) {
+        debug!("Throttling {ip}: {err}");
+
+        return err.into_axum();
+    }
+
+    let res = CLIENT_IP.scope(ip, next.run(req)).await;
+
+    if res.status().as_u16() == 401 {
+        cx.auth_limiter.ip_failed(ip);
+    }
+
+    res
+}
@@ -1,14 +1,17 @@
 use std::sync::Arc;
 
-use super::request::{AddPathRouteData, AddTokenRouteData};
+use super::request::{
+    AddCertificateRouteData, AddPathRouteData, AddTokenRouteData, RemoveCertificateRouteData,
+};
 use crate::{
     auth::AnyAuth,
     cx::RouteContext,
-    err::AxumResponse,
+    err::{AxumResponse, MavenError},
+    https::pem_fingerprint,
     router::request::{RemovePathRouteData, TokenInfoRouteData},
-    tokens::models::{MavenTokenPath, MavenTokenSafe},
+    tokens::models::{MavenTokenCertificate, MavenTokenPath, MavenTokenSafe},
 };
-use anyhow::{Result, anyhow};
+use anyhow::Result;
 use axum::{Json, extract::State, response::Response};
 use axum_auth::AuthBearer;
 use axum_extra::TypedHeader;
@@ -26,7 +29,7 @@ pub async fn new_token_route(
     if !state.validate_master_key(key).await.into_axum()? {
         debug!("Master key invalid!");
 
-        Err(anyhow!("Invalid master key!")).into_axum()
+        Err(MavenError::Unauthorized("Invalid master key!".into())).into_axum()
     } else {
         debug!("Validated! Creating token...");
 
@@ -46,7 +49,7 @@ pub async fn delete_token_route(
     Json(data): Json<TokenInfoRouteData>,
 ) -> Result<Response, Response> {
     if !state.validate_master_key(key).await.into_axum()? {
-        Err(anyhow!("Invalid master key!")).into_axum()
+        Err(MavenError::Unauthorized("Invalid master key!".into())).into_axum()
     } else {
         state.delete_token(data.name).await.into_axum()?;
 
@@ -64,7 +67,7 @@ pub async fn get_token_paths_route(
     Json(data): Json<TokenInfoRouteData>,
 ) -> Result<Json<Vec<MavenTokenPath>>, Response> {
     if !state.validate_master_key(key).await.into_axum()? {
-        Err(anyhow!("Invalid master key!")).into_axum()
+        Err(MavenError::Unauthorized("Invalid master key!".into())).into_axum()
     } else {
         Ok(Json(
             state.get_token_paths_by_name(data.name).await.into_axum()?,
@@ -79,7 +82,7 @@ pub async fn add_path_route(
     Json(data): Json<AddPathRouteData>,
 ) -> Result<Json<MavenTokenPath>, Response> {
     if !state.validate_master_key(key).await.into_axum()? {
-        Err(anyhow!("Invalid token!")).into_axum()
+        Err(MavenError::Unauthorized("Invalid token!".into())).into_axum()
     } else {
         Ok(Json(
             state
@@ -97,7 +100,7 @@ pub async fn delete_path_route(
     Json(data): Json<RemovePathRouteData>,
 ) -> Result<Response, Response> {
     if !state.validate_master_key(key).await.into_axum()? {
-        Err(anyhow!("Invalid master key!")).into_axum()
+        Err(MavenError::Unauthorized("Invalid master key!".into())).into_axum()
     } else {
         state
             .remove_token_path(data.token_name, data.path)
@@ -111,6 +114,67 @@ pub async fn delete_path_route(
     }
 }
 
+#[axum::debug_handler]
+pub async fn get_token_certs_route(
+    State(state): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+    Json(data): Json<TokenInfoRouteData>,
+) -> Result<Json<Vec<MavenTokenCertificate>>, Response> {
+    if !state.validate_master_key(key).await.into_axum()? {
+        Err(MavenError::Unauthorized("Invalid master key!".into())).into_axum()
+    } else {
+        Ok(Json(
+            state
+                .get_token_certificates_by_name(data.name)
+                .await
+                .into_axum()?,
+        ))
+    }
+}
+
+#[axum::debug_handler]
+pub async fn add_cert_route(
+    State(state): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+    Json(data): Json<AddCertificateRouteData>,
+) -> Result<Json<MavenTokenCertificate>, Response> {
+    if !state.validate_master_key(key).await.into_axum()? {
+        Err(MavenError::Unauthorized("Invalid master key!".into())).into_axum()
+    } else {
+        let fingerprint = pem_fingerprint(&data.certificate)
+            .map_err(|err| MavenError::BadRequest(err.to_string()))
+            .into_axum()?;
+
+        Ok(Json(
+            state
+                .add_token_certificate(data.token_name, fingerprint)
+                .await
+                .into_axum()?,
+        ))
+    }
+}
+
+#[axum::debug_handler]
+pub async fn delete_cert_route(
+    State(state): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+    Json(data): Json<RemoveCertificateRouteData>,
+) -> Result<Response, Response> {
+    if !state.validate_master_key(key).await.into_axum()? {
+        Err(MavenError::Unauthorized("Invalid master key!".into())).into_axum()
+    } else {
+        state
+            .remove_token_certificate(data.token_name, data.fingerprint)
+            .await
+            .into_axum()?;
+
+        Ok(Response::builder()
+            .status(200)
+            .body("Success".into())
+            .unwrap())
+    }
+}
+
 #[axum::debug_handler]
 pub async fn get_token_route(
     State(state): State<Arc<RouteContext>>,
@@ -0,0 +1,72 @@
+use super::request::{CreateWebhookData, WebhookIdData};
+use crate::{
+    cx::RouteContext,
+    err::{AxumResponse, MavenError},
+    webhooks::models::{Webhook, WebhookDelivery, WebhookInfo},
+};
+use axum::{Json, extract::State, response::Response};
+use axum_auth::AuthBearer;
+use std::sync::Arc;
+
+#[axum::debug_handler]
+pub async fn get_webhooks_route(
+    State(cx): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+) -> Result<Json<Vec<WebhookInfo>>, Response> {
+    if !cx.validate_master_key(key).await.into_axum()? {
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
+    }
+
+    Ok(Json(cx.get_webhooks().await.into_axum()?))
+}
+
+/// Add a webhook. The response has the secret its deliveries are signed with.
+#[axum::debug_handler]
+pub async fn create_webhook_route(
+    State(cx): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+    Json(data): Json<CreateWebhookData>,
+) -> Result<Json<Webhook>, Response> {
+    if !cx.validate_master_key(key).await.into_axum()? {
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
+    }
+
+    Ok(Json(
+        cx.create_webhook(data.url, data.prefix, data.events)
+            .await
+            .into_axum()?,
+    ))
+}
+
+#[axum::debug_handler]
+pub async fn delete_webhook_route(
+    State(cx): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+    Json(data): Json<WebhookIdData>,
+) -> Result<Response, Response> {
+    if !cx.validate_master_key(key).await.into_axum()? {
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
+    }
+
+    cx.delete_webhook(data.id).await.into_axum()?;
+
+    Ok(Response::builder()
+        .status(200)
+        .body("Success".into())
+        .unwrap())
+}
+
+/// Send a ping to a webhook. It goes through the queue like anything else, so the
+/// result shows up in its deliveries.
+#[axum::debug_handler]
+pub async fn test_webhook_route(
+    State(cx): State<Arc<RouteContext>>,
+    AuthBearer(key): AuthBearer,
+    Json(data): Json<WebhookIdData>,
+) -> Result<Json<WebhookDelivery>, Response> {
+    if !cx.validate_master_key(key).await.into_axum()? {
+        return Err(MavenError::Unauthorized("Invalid token!".into())).into_axum();
+    }
+
+    Ok(Json(cx.test_webhook(data.id).await.into_axum()?))
+}
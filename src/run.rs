@@ -1,24 +1,69 @@
 use crate::{
+    config::Seeds,
     cx::RouteContext,
     db::{connect, migrate},
+    files::{signatures::Keyring, signing::SigningKey},
+    https::{ClientCertAcceptor, TlsOptions, serve_redirect, watch_certificates},
     queue::worker_thread,
+    ratelimit::AuthLimits,
+    replication::{cx::replication_thread, target::ReplicationTarget},
     router::build_router,
     s3::S3Config,
+    scrub::cx::{ScrubOptions, scrub_thread},
     seed::seed_db,
+    shutdown::shutdown_signal,
+    webhooks::cx::webhook_thread,
 };
 use anyhow::{Result, anyhow};
+use axum_server::{Handle, tls_rustls::RustlsConfig};
 use rustls::crypto::ring;
-use std::sync::Arc;
-use tokio::{net::TcpListener, sync::Notify};
-use tracing::info;
-
-pub async fn run(
-    host: impl AsRef<str>,
-    port: u16,
-    db: String,
-    master_key: Option<String>,
-    s3: S3Config,
-) -> Result<()> {
+use std::{
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, atomic::Ordering},
+    time::Duration,
+};
+use tokio::{net::lookup_host, sync::Notify};
+use tracing::{info, warn};
+
+/// Everything the server needs to start, worked out from the CLI and config file.
+pub struct ServeOptions {
+    pub host: String,
+    pub port: u16,
+    pub database_url: String,
+    pub master_key: Option<String>,
+    pub trusted_keys: Option<PathBuf>,
+    pub signing_key: Option<PathBuf>,
+    pub signing_key_password: Option<String>,
+    pub s3: S3Config,
+    pub auth_limits: AuthLimits,
+    pub tls: Option<TlsOptions>,
+
+    /// How long shutdown waits for uploads to finish.
+    pub drain_timeout: Duration,
+    pub scrub: ScrubOptions,
+    pub seeds: Seeds,
+    pub replicas: Vec<ReplicationTarget>,
+}
+
+pub async fn run(options: ServeOptions) -> Result<()> {
+    let ServeOptions {
+        host,
+        port,
+        database_url: db,
+        master_key,
+        trusted_keys,
+        signing_key,
+        signing_key_password,
+        s3,
+        auth_limits,
+        tls,
+        drain_timeout,
+        scrub,
+        seeds,
+        replicas,
+    } = options;
+
     info!("Initializing rustls...");
 
     ring::default_provider()
@@ -41,31 +86,183 @@ pub async fn run(
 
     let notify = Arc::new(Notify::new());
 
+    let keyring = match trusted_keys {
+        Some(path) => {
+            info!("Loading trusted keys...");
+
+            let keyring = Keyring::load(path)?;
+
+            info!("Loaded {} trusted key(s)!", keyring.len());
+
+            Some(keyring)
+        }
+
+        None => None,
+    };
+
+    let signing_key = match signing_key {
+        Some(path) => {
+            info!("Loading signing key...");
+
+            let key = SigningKey::load(path, signing_key_password)?;
+
+            info!("Loaded signing key {}!", key.fingerprint());
+
+            Some(key)
+        }
+
+        None => None,
+    };
+
     info!("Building context...");
 
-    let cx = Arc::new(RouteContext::create(s3, pool, Arc::clone(&notify)).await?);
+    let cx = Arc::new(
+        RouteContext::create(
+            s3,
+            pool,
+            Arc::clone(&notify),
+            keyring,
+            signing_key,
+            auth_limits,
+            replicas,
+        )
+        .await?,
+    );
+
+    info!("Applying seeds from the config...");
+
+    cx.apply_seeds(&seeds).await?;
+
+    info!("Indexing in the background...");
+
+    let cx_clone = Arc::clone(&cx);
+
+    tokio::task::spawn(async move {
+        // Readiness checks fail until this is done, so nothing gets sent here before the
+        // listings are in place.
+        let indexed = match cx_clone.pool.get().await {
+            Ok(mut conn) => cx_clone.index_dirs(&mut conn).await,
+            Err(err) => Err(err.into()),
+        };
+
+        match indexed {
+            Ok(()) => {
+                cx_clone.indexed.store(true, Ordering::SeqCst);
+                info!("Indexed directories!");
+            }
 
-    info!("Indexing...");
+            Err(err) => warn!("Could not index directories: {err}"),
+        }
 
-    cx.index_dirs(&mut cx.pool.get().await?).await?;
+        match cx_clone.index_poms(false).await {
+            Ok(count) => info!("Indexed {count} POM(s)!"),
+            Err(err) => warn!("Could not index POMs: {err}"),
+        }
+
+        match cx_clone.index_jars(false).await {
+            Ok(count) => info!("Indexed {count} JAR(s)!"),
+            Err(err) => warn!("Could not index JARs: {err}"),
+        }
+    });
 
     info!("Starting upload worker thread...");
 
     let cx_clone = Arc::clone(&cx);
 
-    tokio::task::spawn(async move { worker_thread(notify, cx_clone).await });
+    let worker = tokio::task::spawn(async move { worker_thread(notify, cx_clone).await });
+
+    if !scrub.every.is_zero() {
+        tokio::task::spawn(scrub_thread(Arc::clone(&cx), scrub));
+    }
+
+    if !cx.replicas.is_empty() {
+        tokio::task::spawn(replication_thread(Arc::clone(&cx)));
+    }
+
+    tokio::task::spawn(webhook_thread(Arc::clone(&cx)));
 
     info!("Creating app...");
 
-    let app = build_router(cx);
+    let app = build_router(Arc::clone(&cx));
 
     info!("Binding listener...");
 
-    let listener = TcpListener::bind(format!("{}:{}", host.as_ref(), port)).await?;
+    let addr = lookup_host(format!("{host}:{port}"))
+        .await?
+        .next()
+        .ok_or(anyhow!("Could not resolve {host}"))?;
+
+    let handle = Handle::new();
+    let app = app.into_make_service_with_connect_info::<SocketAddr>();
+
+    let cx_clone = Arc::clone(&cx);
+    let handle_clone = handle.clone();
+
+    tokio::task::spawn(async move {
+        shutdown_signal().await;
+
+        info!(
+            "Shutting down, waiting for {} upload(s)...",
+            cx_clone.drain.writes()
+        );
+
+        cx_clone.drain.start();
+
+        if !cx_clone.drain.wait_idle(drain_timeout).await {
+            warn!(
+                "Gave up waiting after {}s, {} upload(s) still running!",
+                drain_timeout.as_secs(),
+                cx_clone.drain.writes()
+            );
+        }
+
+        // Reads get a moment to finish too, but uploads are done (or abandoned) by now.
+        handle_clone.graceful_shutdown(Some(Duration::from_secs(5)));
+    });
+
+    match tls {
+        Some(tls) => {
+            info!("Loading TLS certificates...");
+
+            let config = RustlsConfig::from_config(tls.server_config()?);
+
+            watch_certificates(tls.clone(), config.clone());
+
+            if let Some(redirect_port) = tls.redirect_port {
+                let redirect = SocketAddr::new(addr.ip(), redirect_port);
+                let handle = handle.clone();
+
+                info!("Redirecting http://{redirect} to HTTPS");
+
+                tokio::task::spawn(async move {
+                    if let Err(err) = serve_redirect(redirect, port, handle).await {
+                        warn!("Could not serve HTTPS redirects: {err}");
+                    }
+                });
+            }
+
+            info!("Service started on https://{host}:{port}");
+
+            axum_server::bind(addr)
+                .handle(handle)
+                .acceptor(ClientCertAcceptor::new(config))
+                .serve(app)
+                .await?;
+        }
+
+        None => {
+            info!("Service started on http://{host}:{port}");
+
+            axum_server::bind(addr).handle(handle).serve(app).await?;
+        }
+    }
+
+    info!("Waiting for indexing to finish...");
 
-    info!("Service started on http://{}:{}", host.as_ref(), port);
+    cx.drain.stop();
+    worker.await??;
 
-    axum::serve(listener, app).await?;
+    info!("Goodbye!");
 
     Ok(())
 }
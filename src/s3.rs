@@ -1,3 +1,6 @@
+use anyhow::Result;
+use object_store::aws::{AmazonS3, AmazonS3Builder};
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct S3Config {
     pub region: String,
@@ -6,3 +9,23 @@ pub struct S3Config {
     pub access_key_secret: String,
     pub url: Option<String>,
 }
+
+impl S3Config {
+    pub fn build(self) -> Result<AmazonS3> {
+        let mut builder = AmazonS3Builder::new()
+            .with_region(self.region)
+            .with_bucket_name(self.bucket)
+            .with_access_key_id(self.access_key_id)
+            .with_secret_access_key(self.access_key_secret);
+
+        if let Some(url) = self.url {
+            if url.starts_with("http:") {
+                builder = builder.with_allow_http(true);
+            }
+
+            builder = builder.with_endpoint(url);
+        }
+
+        Ok(builder.build()?)
+    }
+}
@@ -16,6 +16,18 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    file_signatures (id) {
+        id -> Int4,
+        file -> Int4,
+        signature -> Int4,
+        kind -> Text,
+        key_fingerprint -> Nullable<Text>,
+        verified -> Bool,
+        created -> Timestamp,
+    }
+}
+
 diesel::table! {
     files (id) {
         id -> Int4,
@@ -28,6 +40,43 @@ diesel::table! {
         sha512 -> Text,
         kind -> Text,
         parent -> Text,
+        uploader -> Nullable<Int4>,
+        quarantined -> Bool,
+        stored -> Timestamp,
+    }
+}
+
+diesel::table! {
+    import_jobs (id) {
+        id -> Int4,
+        source -> Text,
+        prefix -> Text,
+        started -> Timestamp,
+        finished -> Nullable<Timestamp>,
+        found -> Int4,
+        imported -> Int4,
+        skipped -> Int4,
+        failed -> Int4,
+        error -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    index_failures (file, kind) {
+        file -> Int4,
+        kind -> Text,
+        error -> Text,
+        failed -> Timestamp,
+    }
+}
+
+diesel::table! {
+    jar_entries (id) {
+        id -> Int4,
+        file -> Int4,
+        name -> Text,
+        class_name -> Nullable<Text>,
+        size -> Int8,
     }
 }
 
@@ -40,12 +89,172 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    pom_dependencies (id) {
+        id -> Int4,
+        pom -> Int4,
+        group_id -> Text,
+        artifact_id -> Text,
+        version -> Nullable<Text>,
+        scope -> Nullable<Text>,
+        kind -> Text,
+        classifier -> Nullable<Text>,
+        optional -> Bool,
+        managed -> Bool,
+    }
+}
+
+diesel::table! {
+    pom_developers (id) {
+        id -> Int4,
+        pom -> Int4,
+        developer_id -> Nullable<Text>,
+        name -> Nullable<Text>,
+        email -> Nullable<Text>,
+        url -> Nullable<Text>,
+        organization -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    pom_licenses (id) {
+        id -> Int4,
+        pom -> Int4,
+        name -> Nullable<Text>,
+        url -> Nullable<Text>,
+        distribution -> Nullable<Text>,
+        comments -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    pom_properties (id) {
+        id -> Int4,
+        pom -> Int4,
+        name -> Text,
+        value -> Text,
+    }
+}
+
+diesel::table! {
+    poms (id) {
+        id -> Int4,
+        file -> Int4,
+        group_id -> Text,
+        artifact_id -> Text,
+        version -> Text,
+        packaging -> Text,
+        name -> Nullable<Text>,
+        description -> Nullable<Text>,
+        url -> Nullable<Text>,
+        parent_group_id -> Nullable<Text>,
+        parent_artifact_id -> Nullable<Text>,
+        parent_version -> Nullable<Text>,
+        scm_url -> Nullable<Text>,
+        scm_connection -> Nullable<Text>,
+        scm_developer_connection -> Nullable<Text>,
+        scm_tag -> Nullable<Text>,
+        indexed -> Timestamp,
+    }
+}
+
+diesel::table! {
+    replication_outbox (id) {
+        id -> Int4,
+        target -> Text,
+        event -> Text,
+        path -> Text,
+        created -> Timestamp,
+        attempts -> Int4,
+        next_attempt -> Timestamp,
+        last_error -> Nullable<Text>,
+    }
+}
+
 diesel::table! {
     route_data (id) {
         id -> Int4,
         path -> Text,
         visibility -> Int2,
         created -> Timestamp,
+        sign -> Bool,
+    }
+}
+
+diesel::table! {
+    route_quotas (id) {
+        id -> Int4,
+        path -> Text,
+        max_storage -> Nullable<Int8>,
+        created -> Timestamp,
+    }
+}
+
+diesel::table! {
+    route_rules (id) {
+        id -> Int4,
+        path -> Text,
+        require_pom -> Bool,
+        version_pattern -> Nullable<Text>,
+        required_classifiers -> Nullable<Text>,
+        max_size -> Nullable<Int8>,
+        forbidden_extensions -> Nullable<Text>,
+        created -> Timestamp,
+    }
+}
+
+diesel::table! {
+    scrub_problems (id) {
+        id -> Int4,
+        file -> Int4,
+        kind -> Text,
+        detail -> Text,
+        found -> Timestamp,
+    }
+}
+
+diesel::table! {
+    scrub_runs (id) {
+        id -> Int4,
+        started -> Timestamp,
+        finished -> Nullable<Timestamp>,
+        checked -> Int4,
+        problems -> Int4,
+        error -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    staging_files (id) {
+        id -> Int4,
+        session -> Int4,
+        path -> Text,
+        size -> Int8,
+        uploaded -> Timestamp,
+        md5 -> Text,
+        sha1 -> Text,
+        sha256 -> Text,
+        sha512 -> Text,
+        kind -> Text,
+    }
+}
+
+diesel::table! {
+    staging_sessions (id) {
+        id -> Int4,
+        key -> Text,
+        token -> Int4,
+        description -> Nullable<Text>,
+        created -> Timestamp,
+    }
+}
+
+diesel::table! {
+    token_certificates (id) {
+        id -> Int4,
+        token -> Int4,
+        fingerprint -> Text,
+        added -> Timestamp,
     }
 }
 
@@ -59,6 +268,17 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    token_quotas (id) {
+        id -> Int4,
+        token -> Int4,
+        max_storage -> Nullable<Int8>,
+        max_file_size -> Nullable<Int8>,
+        uploads_per_minute -> Nullable<Int4>,
+        created -> Timestamp,
+    }
+}
+
 diesel::table! {
     tokens (id) {
         id -> Int4,
@@ -68,13 +288,74 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    webhook_deliveries (id) {
+        id -> Int4,
+        webhook -> Int4,
+        event -> Text,
+        payload -> Text,
+        status -> Text,
+        created -> Timestamp,
+        attempts -> Int4,
+        next_attempt -> Timestamp,
+        finished -> Nullable<Timestamp>,
+        response_status -> Nullable<Int4>,
+        last_error -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    webhooks (id) {
+        id -> Int4,
+        url -> Text,
+        secret -> Text,
+        prefix -> Text,
+        events -> Array<Text>,
+        created -> Timestamp,
+    }
+}
+
+diesel::joinable!(files -> tokens (uploader));
+diesel::joinable!(index_failures -> files (file));
+diesel::joinable!(jar_entries -> files (file));
+diesel::joinable!(pom_dependencies -> poms (pom));
+diesel::joinable!(pom_developers -> poms (pom));
+diesel::joinable!(pom_licenses -> poms (pom));
+diesel::joinable!(pom_properties -> poms (pom));
+diesel::joinable!(poms -> files (file));
+diesel::joinable!(scrub_problems -> files (file));
+diesel::joinable!(staging_files -> staging_sessions (session));
+diesel::joinable!(staging_sessions -> tokens (token));
+diesel::joinable!(token_certificates -> tokens (token));
 diesel::joinable!(token_paths -> tokens (token));
+diesel::joinable!(token_quotas -> tokens (token));
+diesel::joinable!(webhook_deliveries -> webhooks (webhook));
 
 diesel::allow_tables_to_appear_in_same_query!(
     deleted_files,
+    file_signatures,
     files,
+    import_jobs,
+    index_failures,
+    jar_entries,
     master_keys,
+    pom_dependencies,
+    pom_developers,
+    pom_licenses,
+    pom_properties,
+    poms,
+    replication_outbox,
     route_data,
+    route_quotas,
+    route_rules,
+    scrub_problems,
+    scrub_runs,
+    staging_files,
+    staging_sessions,
+    token_certificates,
     token_paths,
+    token_quotas,
     tokens,
+    webhook_deliveries,
+    webhooks,
 );
@@ -0,0 +1,300 @@
+use super::models::{ScrubProblem, ScrubProblemIn, ScrubProblemInfo, ScrubReport, ScrubRun};
+use crate::{
+    cx::RouteContext,
+    err::MavenError,
+    files::{
+        hashes::{FileHasher, FileHashes},
+        models::MavenFile,
+    },
+    schema::{files, scrub_problems, scrub_runs},
+};
+use anyhow::Result;
+use chrono::Utc;
+use diesel::{ExpressionMethods, QueryDsl, SelectableHelper, delete, insert_into, update};
+use diesel_async::{AsyncConnection, RunQueryDsl, scoped_futures::ScopedFutureExt};
+use futures_util::TryStreamExt;
+use object_store::{ObjectStore, path::Path};
+use std::{
+    collections::HashMap,
+    sync::{Arc, atomic::Ordering},
+    time::Duration,
+};
+
+impl RouteContext {
+    /// Read every file back from storage and check it against its recorded size and
+    /// hashes. With `quarantine`, files that fail stop being served.
+    pub async fn scrub(&self, quarantine: bool) -> Result<ScrubRun> {
+        if self.scrubbing.swap(true, Ordering::SeqCst) {
+            return Err(MavenError::Conflict("A scrub is already running!".into()).into());
+        }
+
+        let run = insert_into(scrub_runs::table)
+            .default_values()
+            .returning(ScrubRun::as_returning())
+            .get_result(&mut self.pool.get().await?)
+            .await;
+
+        let res = match run {
+            Ok(run) => self.scrub_inner(run, quarantine).await,
+            Err(err) => Err(err.into()),
+        };
+
+        self.scrubbing.store(false, Ordering::SeqCst);
+
+        res
+    }
+
+    async fn scrub_inner(&self, run: ScrubRun, quarantine: bool) -> Result<ScrubRun> {
+        info!("Scrubbing storage...");
+
+        let res = self.scrub_files(quarantine).await;
+        let mut conn = self.pool.get().await?;
+
+        let (checked, problems, error) = match res {
+            Ok((checked, problems)) => {
+                info!("Scrubbed {checked} file(s), found {problems} problem(s)!");
+
+                (checked, problems, None)
+            }
+
+            Err(err) => {
+                warn!("Scrub failed: {err}");
+
+                (0, 0, Some(err.to_string()))
+            }
+        };
+
+        Ok(update(scrub_runs::table)
+            .filter(scrub_runs::id.eq(run.id))
+            .set((
+                scrub_runs::finished.eq(Utc::now().naive_utc()),
+                scrub_runs::checked.eq(checked as i32),
+                scrub_runs::problems.eq(problems as i32),
+                scrub_runs::error.eq(error),
+            ))
+            .returning(ScrubRun::as_returning())
+            .get_result(&mut conn)
+            .await?)
+    }
+
+    async fn scrub_files(&self, quarantine: bool) -> Result<(usize, usize)> {
+        let mut conn = self.pool.get().await?;
+
+        let files = files::table
+            .select(MavenFile::as_select())
+            .order_by(files::id)
+            .load(&mut conn)
+            .await?;
+
+        // Files with the same content share an object, so each one only gets read once.
+        let mut seen = HashMap::<String, Option<FileHashes>>::new();
+        let mut fine = Vec::new();
+        let mut problems = 0;
+
+        for file in &files {
+            let hashes = match seen.get(&file.md5) {
+                Some(it) => it.clone(),
+
+                None => match self.hash_object(&file.md5).await {
+                    Ok(it) => {
+                        seen.insert(file.md5.clone(), it.clone());
+                        it
+                    }
+
+                    // Storage being unreachable doesn't mean the file is bad.
+                    Err(err) => {
+                        warn!("Could not scrub {}: {err}", file.path);
+                        continue;
+                    }
+                },
+            };
+
+            let Some((kind, detail)) = check_hashes(file, hashes.as_ref()) else {
+                fine.push(file.id);
+                continue;
+            };
+
+            warn!("Scrub found a problem with {}: {detail}", file.path);
+
+            let problem = ScrubProblemIn {
+                file: file.id,
+                kind: kind.into(),
+                detail,
+                found: Utc::now().naive_utc(),
+            };
+
+            insert_into(scrub_problems::table)
+                .values(&problem)
+                .on_conflict(scrub_problems::file)
+                .do_update()
+                .set(&problem)
+                .execute(&mut conn)
+                .await?;
+
+            if quarantine {
+                update(files::table)
+                    .filter(files::id.eq(file.id))
+                    .set(files::quarantined.eq(true))
+                    .execute(&mut conn)
+                    .await?;
+            }
+
+            problems += 1;
+        }
+
+        // Anything that checks out now has been fixed since it was last flagged, so it
+        // can be served again. Files that were only quarantined by hand are left alone.
+        let fine_ids = &fine;
+
+        conn.transaction::<_, anyhow::Error, _>(|conn| {
+            async move {
+                let flagged = scrub_problems::table
+                    .filter(scrub_problems::file.eq_any(fine_ids))
+                    .select(scrub_problems::file);
+
+                update(files::table)
+                    .filter(files::id.eq_any(flagged))
+                    .set(files::quarantined.eq(false))
+                    .execute(conn)
+                    .await?;
+
+                delete(scrub_problems::table)
+                    .filter(scrub_problems::file.eq_any(fine_ids))
+                    .execute(conn)
+                    .await?;
+
+                Ok(())
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+        Ok((fine.len() + problems, problems))
+    }
+
+    /// Stream an object and hash it. `None` means it doesn't exist.
+    pub async fn hash_object(&self, md5: impl AsRef<str>) -> Result<Option<FileHashes>> {
+        let mut stream = match self.storage.get(&Path::from(md5.as_ref())).await {
+            Ok(it) => it.into_stream(),
+            Err(object_store::Error::NotFound { .. }) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut hasher = FileHasher::new();
+
+        while let Some(chunk) = stream.try_next().await? {
+            hasher.update(chunk);
+        }
+
+        Ok(Some(hasher.finish()))
+    }
+
+    pub async fn get_scrub_report(&self) -> Result<ScrubReport> {
+        let mut conn = self.pool.get().await?;
+
+        let last_run = scrub_runs::table
+            .select(ScrubRun::as_select())
+            .order_by(scrub_runs::started.desc())
+            .first(&mut conn)
+            .await
+            .ok();
+
+        let problems = scrub_problems::table
+            .inner_join(files::table)
+            .select((ScrubProblem::as_select(), MavenFile::as_select()))
+            .order_by(files::path)
+            .load::<(ScrubProblem, MavenFile)>(&mut conn)
+            .await?
+            .into_iter()
+            .map(|(problem, file)| ScrubProblemInfo {
+                path: file.path,
+                kind: problem.kind,
+                detail: problem.detail,
+                found: problem.found,
+                quarantined: file.quarantined,
+            })
+            .collect();
+
+        Ok(ScrubReport {
+            running: self.scrubbing.load(Ordering::SeqCst),
+            last_run,
+            problems,
+        })
+    }
+
+    /// Stop (or start again) serving a file.
+    pub async fn set_quarantined(
+        &self,
+        path: impl AsRef<str>,
+        quarantined: bool,
+    ) -> Result<MavenFile> {
+        update(files::table)
+            .filter(files::path.eq(path.as_ref()))
+            .set(files::quarantined.eq(quarantined))
+            .returning(MavenFile::as_returning())
+            .get_result(&mut self.pool.get().await?)
+            .await
+            .map_err(|_| MavenError::not_found().into())
+    }
+}
+
+/// What's wrong with a file, if anything, given what its object actually hashes to.
+fn check_hashes(file: &MavenFile, hashes: Option<&FileHashes>) -> Option<(&'static str, String)> {
+    let Some(hashes) = hashes else {
+        return Some(("missing", "The object is missing from storage".into()));
+    };
+
+    if hashes.size != file.size as u64 {
+        return Some((
+            "size",
+            format!("Expected {} bytes, found {}", file.size, hashes.size),
+        ));
+    }
+
+    let checks = [
+        ("md5", &file.md5, &hashes.md5),
+        ("sha1", &file.sha1, &hashes.sha1),
+        ("sha256", &file.sha256, &hashes.sha256),
+        ("sha512", &file.sha512, &hashes.sha512),
+    ];
+
+    checks
+        .into_iter()
+        .find(|(_, expected, actual)| !expected.eq_ignore_ascii_case(actual))
+        .map(|(alg, expected, actual)| {
+            (
+                "hash",
+                format!("{alg} mismatch: expected {expected}, found {actual}"),
+            )
+        })
+}
+
+/// When the server scrubs on its own.
+#[derive(Debug, Clone, Copy)]
+pub struct ScrubOptions {
+    /// How long between scrubs. Zero turns them off.
+    pub every: Duration,
+
+    /// Stop serving files that fail.
+    pub quarantine: bool,
+}
+
+/// Scrub every so often, for as long as the server runs.
+pub async fn scrub_thread(cx: Arc<RouteContext>, options: ScrubOptions) {
+    info!(
+        "Scrubbing storage every {}h!",
+        options.every.as_secs() / 3600
+    );
+
+    loop {
+        tokio::time::sleep(options.every).await;
+
+        if cx.drain.is_draining() {
+            return;
+        }
+
+        if let Err(err) = cx.scrub(options.quarantine).await {
+            warn!("Could not scrub storage: {err}");
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use chrono::NaiveDateTime;
+
+/// One pass over every file in storage.
+#[derive(Debug, Clone, Serialize, Deserialize, Identifiable, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::scrub_runs)]
+pub struct ScrubRun {
+    pub id: i32,
+    pub started: NaiveDateTime,
+
+    /// Still running (or killed part way through) if this isn't set.
+    pub finished: Option<NaiveDateTime>,
+    pub checked: i32,
+    pub problems: i32,
+
+    /// Why the run stopped early, if it did.
+    pub error: Option<String>,
+}
+
+/// A file whose data didn't match what we have on record, the last time it was checked.
+#[derive(Debug, Clone, Serialize, Deserialize, Identifiable, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::scrub_problems)]
+pub struct ScrubProblem {
+    pub id: i32,
+    pub file: i32,
+
+    /// `missing`, `size` or `hash`.
+    pub kind: String,
+    pub detail: String,
+    pub found: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable, AsChangeset)]
+#[diesel(table_name = crate::schema::scrub_problems)]
+pub struct ScrubProblemIn {
+    pub file: i32,
+    pub kind: String,
+    pub detail: String,
+    pub found: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrubProblemInfo {
+    pub path: String,
+    pub kind: String,
+    pub detail: String,
+    pub found: NaiveDateTime,
+    pub quarantined: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrubReport {
+    pub running: bool,
+    pub last_run: Option<ScrubRun>,
+    pub problems: Vec<ScrubProblemInfo>,
+}
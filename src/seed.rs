@@ -1,15 +1,22 @@
 use crate::{
+    config::{RouteSeed, Seeds, TokenSeed},
+    cx::RouteContext,
     db::DbPool,
     router::models::{RouteData, RouteDataIn},
-    schema::{master_keys, route_data},
-    tokens::{models::MasterKey, models_in::MasterKeyIn},
+    schema::{master_keys, route_data, tokens},
+    tokens::{
+        hash::check_password,
+        models::{MasterKey, MavenToken},
+        models_in::{MasterKeyIn, MavenTokenIn},
+    },
 };
 use anyhow::Result;
-use diesel::{ExpressionMethods, QueryDsl, SelectableHelper, dsl::insert_into};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper, dsl::insert_into};
 use diesel_async::RunQueryDsl;
 use random_string::charsets::ALPHANUMERIC;
 use tracing::info;
 
+/// Create the data every server needs, if it isn't there yet.
 pub async fn seed_db(pool: &DbPool, master_key: Option<String>) -> Result<()> {
     create_default_key(pool, master_key).await?;
     create_default_route_data(pool).await?;
@@ -65,6 +72,7 @@ async fn create_default_route_data(pool: &DbPool) -> Result<()> {
             .values(RouteDataIn {
                 path: "/".into(),
                 visibility: 0,
+                sign: None,
             })
             .returning(RouteData::as_returning())
             .get_result(&mut conn)
@@ -75,3 +83,86 @@ async fn create_default_route_data(pool: &DbPool) -> Result<()> {
 
     Ok(())
 }
+
+impl RouteContext {
+    /// Apply the routes and tokens from the config file.
+    pub async fn apply_seeds(&self, seeds: &Seeds) -> Result<()> {
+        for route in &seeds.routes {
+            self.seed_route(route).await?;
+        }
+
+        for token in &seeds.tokens {
+            self.seed_token(token).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Apply a route's settings from the config file, whatever they are right now.
+    async fn seed_route(&self, route: &RouteSeed) -> Result<()> {
+        info!("Setting up route {}...", route.path);
+
+        self.set_route_access(RouteDataIn {
+            path: route.path.clone(),
+            visibility: route.access.value(),
+            sign: route.sign,
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Create a token from the config file if it doesn't exist, and make sure it has the
+    /// paths it lists. Paths added some other way are left alone.
+    async fn seed_token(&self, seed: &TokenSeed) -> Result<()> {
+        let existing = tokens::table
+            .filter(tokens::name.eq(&seed.name))
+            .select(MavenToken::as_select())
+            .get_result(&mut self.pool.get().await?)
+            .await
+            .optional()?;
+
+        let token = match existing {
+            Some(token) => {
+                if let Some(value) = &seed.value {
+                    if !check_password(value, &token.value) {
+                        info!("Updating the value of token {}...", seed.name);
+
+                        self.set_token_value(&token, value).await?;
+                    }
+                }
+
+                token
+            }
+
+            None => {
+                info!("Creating token {}...", seed.name);
+
+                let new = match &seed.value {
+                    Some(value) => MavenTokenIn {
+                        name: seed.name.clone(),
+                        value: value.clone(),
+                    },
+
+                    None => MavenTokenIn::new_random(&seed.name),
+                };
+
+                let created = self.create_token(new, seed.value.is_none()).await?;
+
+                if let Some(value) = created.value {
+                    info!(">> The value of token {} is: {}", seed.name, value);
+                    info!(">> Write it down, it won't be displayed again!");
+                }
+
+                self.get_token_by_name(&seed.name).await?
+            }
+        };
+
+        for path in &seed.paths {
+            self.set_token_path(&token, &path.path, path.permission)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+//! Shutting down without cutting off uploads: stop taking writes, let the ones in
+//! progress finish, then stop.
+
+use std::{
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    time::Duration,
+};
+use tokio::{sync::Notify, time::timeout};
+
+#[derive(Debug, Default)]
+pub struct Drain {
+    draining: AtomicBool,
+    writes: AtomicUsize,
+    idle: Notify,
+    stop: Notify,
+}
+
+/// Held for as long as a write is in progress.
+pub struct WriteGuard<'a>(&'a Drain);
+
+impl Drop for WriteGuard<'_> {
+    fn drop(&mut self) {
+        if self.0.writes.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+impl Drain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::SeqCst)
+    }
+
+    /// Stop taking new writes. Readiness checks fail from here on.
+    pub fn start(&self) {
+        self.draining.store(true, Ordering::SeqCst);
+    }
+
+    /// Start a write, unless we're draining. The count goes up before the check, so a
+    /// write can't sneak in after [`Drain::wait_idle`] has seen zero.
+    pub fn enter(&self) -> Option<WriteGuard<'_>> {
+        self.writes.fetch_add(1, Ordering::SeqCst);
+
+        let guard = WriteGuard(self);
+
+        (!self.is_draining()).then_some(guard)
+    }
+
+    pub fn writes(&self) -> usize {
+        self.writes.load(Ordering::SeqCst)
+    }
+
+    /// Wait for every write to finish, for up to `limit`. Returns whether they did.
+    pub async fn wait_idle(&self, limit: Duration) -> bool {
+        timeout(limit, async {
+            loop {
+                let idle = self.idle.notified();
+
+                if self.writes() == 0 {
+                    break;
+                }
+
+                idle.await;
+            }
+        })
+        .await
+        .is_ok()
+    }
+
+    /// Tell background workers to finish up.
+    pub fn stop(&self) {
+        self.stop.notify_one();
+    }
+
+    pub async fn stopped(&self) {
+        self.stop.notified().await;
+    }
+}
+
+/// Wait for SIGINT (Ctrl+C) or, on Unix, SIGTERM.
+pub async fn shutdown_signal() {
+    let interrupt = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            warn!("Could not listen for Ctrl+C: {err}");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut it) => {
+                it.recv().await;
+            }
+
+            Err(err) => {
+                warn!("Could not listen for SIGTERM: {err}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => info!("Got SIGINT!"),
+        _ = terminate => info!("Got SIGTERM!"),
+    }
+}
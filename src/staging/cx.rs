@@ -0,0 +1,401 @@
+use super::{
+    models::{InvalidStaging, StagedFile, StagingReport, StagingSession},
+    models_in::{StagedFileIn, StagingSessionIn},
+};
+use crate::{
+    cx::RouteContext,
+    err::MavenError,
+    files::{
+        hashes::{ChecksumMismatch, ExpectedChecksums, HASH_TYPES, get_sha1},
+        models::MavenFile,
+        models_in::MavenFileIn,
+        signatures::signature_target,
+        signing::is_signable,
+    },
+    maven::pom::Pom,
+    schema::{staging_files, staging_sessions},
+    tokens::models::MavenToken,
+};
+use anyhow::Result;
+use axum::body::Bytes;
+use diesel::{
+    BoolExpressionMethods, ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper,
+    delete, insert_into, pg::Pg,
+};
+use diesel_async::{AsyncConnection, RunQueryDsl, scoped_futures::ScopedFutureExt};
+use itertools::Itertools;
+use object_store::{ObjectStore, PutPayload};
+
+impl RouteContext {
+    pub async fn open_staging(
+        &self,
+        token: &MavenToken,
+        description: Option<String>,
+    ) -> Result<StagingSession> {
+        Ok(insert_into(staging_sessions::table)
+            .values(StagingSessionIn::new(token.id, description))
+            .returning(StagingSession::as_returning())
+            .get_result(&mut self.pool.get().await?)
+            .await?)
+    }
+
+    /// Get a staging session that belongs to a token.
+    pub async fn get_staging(
+        &self,
+        key: impl AsRef<str>,
+        token: &MavenToken,
+    ) -> Result<StagingSession> {
+        staging_sessions::table
+            .filter(staging_sessions::key.eq(key.as_ref()))
+            .filter(staging_sessions::token.eq(token.id))
+            .select(StagingSession::as_select())
+            .first(&mut self.pool.get().await?)
+            .await
+            .optional()?
+            .ok_or(MavenError::not_found().into())
+    }
+
+    pub async fn get_staging_sessions(&self, token: &MavenToken) -> Result<Vec<StagingSession>> {
+        Ok(staging_sessions::table
+            .filter(staging_sessions::token.eq(token.id))
+            .order(staging_sessions::created.asc())
+            .select(StagingSession::as_select())
+            .load(&mut self.pool.get().await?)
+            .await?)
+    }
+
+    pub async fn get_staged_files(&self, session: &StagingSession) -> Result<Vec<StagedFile>> {
+        Ok(self
+            .get_staged_files_inner(session, &mut self.pool.get().await?)
+            .await?)
+    }
+
+    pub async fn get_staged_files_inner(
+        &self,
+        session: &StagingSession,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<Vec<StagedFile>> {
+        Ok(staging_files::table
+            .filter(staging_files::session.eq(session.id))
+            .order(staging_files::path.asc())
+            .select(StagedFile::as_select())
+            .load(conn)
+            .await?)
+    }
+
+    pub async fn get_staged_file(
+        &self,
+        session: &StagingSession,
+        path: impl AsRef<str>,
+    ) -> Result<Option<StagedFile>> {
+        Ok(self
+            .get_staged_file_inner(session, path, &mut self.pool.get().await?)
+            .await?)
+    }
+
+    pub async fn get_staged_file_inner(
+        &self,
+        session: &StagingSession,
+        path: impl AsRef<str>,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<Option<StagedFile>> {
+        Ok(staging_files::table
+            .filter(
+                staging_files::session
+                    .eq(session.id)
+                    .and(staging_files::path.eq(path.as_ref())),
+            )
+            .select(StagedFile::as_select())
+            .first(conn)
+            .await
+            .optional()?)
+    }
+
+    /// Store a file in a staging session. Like [`RouteContext::upload`], checksum files
+    /// are checked against the file they're for rather than stored.
+    pub async fn stage(
+        &self,
+        session: &StagingSession,
+        path: impl AsRef<str>,
+        bytes: impl AsRef<[u8]>,
+        expected: &ExpectedChecksums,
+    ) -> Result<StagedFile> {
+        let mut conn = self.pool.get().await?;
+        let path = format!("/{}", path.as_ref()).replace("//", "/");
+
+        if let Some(alg) = HASH_TYPES
+            .iter()
+            .find(|it| path.ends_with(&format!(".{it}")))
+        {
+            let real = path.trim_end_matches(&format!(".{alg}"));
+            let given = String::from_utf8(bytes.as_ref().to_vec())?;
+            let given = given.trim();
+
+            let Some(file) = self.get_staged_file_inner(session, real, &mut conn).await? else {
+                return Err(MavenError::Conflict(format!(
+                    "Could not get a staged file for path: {}",
+                    path
+                ))
+                .into());
+            };
+
+            let existing = file.get_hash(alg)?;
+
+            return if existing.eq_ignore_ascii_case(given) {
+                Ok(file)
+            } else {
+                Err(ChecksumMismatch {
+                    algorithm: alg.to_string(),
+                    expected: given.into(),
+                    actual: existing,
+                }
+                .into())
+            };
+        }
+
+        let file = MavenFileIn::new(&path, &bytes).await?;
+
+        expected.verify(&file)?;
+        self.check_rules_inner(&path, bytes.as_ref(), &mut conn)
+            .await?;
+
+        self.storage
+            .put(
+                &file.md5.clone().into(),
+                PutPayload::from(bytes.as_ref().to_vec()),
+            )
+            .await?;
+
+        delete(staging_files::table)
+            .filter(
+                staging_files::session
+                    .eq(session.id)
+                    .and(staging_files::path.eq(&path)),
+            )
+            .execute(&mut conn)
+            .await?;
+
+        Ok(insert_into(staging_files::table)
+            .values(StagedFileIn::new(session.id, file))
+            .returning(StagedFile::as_returning())
+            .get_result(&mut conn)
+            .await?)
+    }
+
+    /// Check that a staging session is fit to be promoted: every version folder has a
+    /// POM, stored files still match their checksums, and signatures have something to
+    /// sign (and check out, if there's a keyring).
+    pub async fn validate_staging(&self, session: &StagingSession) -> Result<StagingReport> {
+        self.validate_staging_inner(session, &mut self.pool.get().await?)
+            .await
+    }
+
+    pub async fn validate_staging_inner(
+        &self,
+        session: &StagingSession,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<StagingReport> {
+        let files = self.get_staged_files_inner(session, conn).await?;
+        let mut problems = Vec::new();
+
+        if files.is_empty() {
+            problems.push("Nothing has been staged.".to_string());
+        }
+
+        // Files are read one at a time, so a big session doesn't all end up in memory.
+        for file in &files {
+            let bytes = file.get_bytes(&self.storage).await?;
+
+            if get_sha1(&bytes) != file.sha1 {
+                problems.push(format!(
+                    "{}: stored data doesn't match its checksum",
+                    file.path
+                ));
+            }
+
+            if file.path.ends_with(".pom") {
+                if let Err(err) = Pom::parse(&bytes) {
+                    problems.push(format!("{}: could not parse POM: {err}", file.path));
+                }
+            }
+        }
+
+        let folders = files
+            .iter()
+            .filter(|it| is_signable(&it.path))
+            .map(|it| it.parent())
+            .unique()
+            .collect_vec();
+
+        for folder in &folders {
+            if files
+                .iter()
+                .any(|it| it.parent() == *folder && it.path.ends_with(".pom"))
+            {
+                continue;
+            }
+
+            let live = self
+                .get_files_in_folder_inner(folder, conn)
+                .await?
+                .iter()
+                .any(|it| it.path.ends_with(".pom"));
+
+            if !live {
+                problems.push(format!("{folder}: no POM"));
+            }
+        }
+
+        for folder in &folders {
+            let Some(rules) = self.get_route_rules_inner(folder, conn).await? else {
+                continue;
+            };
+
+            let live = self.get_files_in_folder_inner(folder, conn).await?;
+
+            let names = files
+                .iter()
+                .filter(|it| it.parent() == *folder)
+                .map(|it| it.path.as_str())
+                .chain(live.iter().map(|it| it.path.as_str()))
+                .map(|it| it.rsplit('/').next().unwrap_or(it))
+                .collect_vec();
+
+            problems.extend(
+                rules
+                    .check_version(folder, &names)
+                    .into_iter()
+                    .map(|it| it.to_string()),
+            );
+        }
+
+        for file in &files {
+            let Some((_, target)) = signature_target(&file.path) else {
+                continue;
+            };
+
+            let staged = match files.iter().find(|it| it.path == target) {
+                Some(it) => Some(it.get_bytes(&self.storage).await?),
+                None => None,
+            };
+
+            if staged.is_none() && !self.has_file_inner(target, conn).await {
+                problems.push(format!("{}: the signed file doesn't exist", file.path));
+                continue;
+            }
+
+            let signature = file.get_bytes(&self.storage).await?;
+
+            if let Err(err) = self
+                .verify_signature_inner(&file.path, &signature, staged.as_deref(), conn)
+                .await
+            {
+                problems.push(err.to_string());
+            }
+        }
+
+        Ok(StagingReport {
+            valid: problems.is_empty(),
+            files: files.len(),
+            problems,
+        })
+    }
+
+    /// Validate a staging session, check its token can still write everything in it and
+    /// that it fits in the storage quotas, and move everything in it into the live tree,
+    /// in one transaction, then close it. The session and the token's paths are locked
+    /// for all of that, so they can't change (or be promoted twice) in between. Invalid
+    /// sessions are left alone, and come back as [`InvalidStaging`].
+    pub async fn promote_staging(&self, session: &StagingSession) -> Result<Vec<MavenFile>> {
+        let mut conn = self.pool.get().await?;
+
+        let published = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                async move {
+                    let session = staging_sessions::table
+                        .filter(staging_sessions::id.eq(session.id))
+                        .select(StagingSession::as_select())
+                        .for_update()
+                        .first(conn)
+                        .await
+                        .optional()?
+                        .ok_or(MavenError::not_found())?;
+
+                    let report = self.validate_staging_inner(&session, conn).await?;
+
+                    if !report.valid {
+                        return Err(InvalidStaging(report).into());
+                    }
+
+                    let files = self.get_staged_files_inner(&session, conn).await?;
+
+                    // Permissions might have changed since the files were staged.
+                    let writable = self
+                        .get_token_writable_paths_inner(session.token, conn)
+                        .await?;
+
+                    if !files
+                        .iter()
+                        .all(|file| writable.iter().any(|it| file.path.starts_with(it)))
+                    {
+                        return Err(MavenError::forbidden().into());
+                    }
+
+                    self.check_staging_quotas_inner(session.token, &files, conn)
+                        .await?;
+
+                    let mut published = Vec::new();
+
+                    // Signatures go last, so the files they sign are live by then, and
+                    // POMs go first, so replicas with `require_pom` take the metadata.
+                    for file in files.into_iter().sorted_by_key(|it| {
+                        (
+                            signature_target(&it.path).is_some(),
+                            !it.path.ends_with(".pom"),
+                        )
+                    }) {
+                        let bytes = Bytes::from(file.get_bytes(&self.storage).await?);
+
+                        let fingerprint = self
+                            .verify_signature_inner(&file.path, &bytes, None, conn)
+                            .await?;
+
+                        published.push(
+                            self.publish_inner(
+                                file.into_file(Some(session.token)),
+                                &bytes,
+                                fingerprint,
+                                conn,
+                            )
+                            .await?,
+                        );
+                    }
+
+                    delete(staging_sessions::table)
+                        .filter(staging_sessions::id.eq(session.id))
+                        .execute(conn)
+                        .await?;
+
+                    Ok(published)
+                }
+                .scope_boxed()
+            })
+            .await?;
+
+        self.notify.notify_waiters();
+        self.replication_notify.notify_one();
+        self.webhook_notify.notify_one();
+
+        Ok(published)
+    }
+
+    /// Throw away a staging session and everything in it.
+    pub async fn drop_staging(&self, session: &StagingSession) -> Result<()> {
+        delete(staging_sessions::table)
+            .filter(staging_sessions::id.eq(session.id))
+            .execute(&mut self.pool.get().await?)
+            .await?;
+
+        Ok(())
+    }
+}
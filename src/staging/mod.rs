@@ -0,0 +1,3 @@
+pub mod cx;
+pub mod models;
+pub mod models_in;
@@ -0,0 +1,109 @@
+use crate::files::models_in::MavenFileIn;
+use anyhow::{Result, anyhow};
+use chrono::NaiveDateTime;
+use object_store::ObjectStore;
+use std::{fmt::Display, sync::Arc};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable, Identifiable, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::staging_sessions)]
+pub struct StagingSession {
+    pub id: i32,
+
+    /// The ID used in staging URLs.
+    pub key: String,
+
+    /// The token that opened this session. Only it can use the session.
+    #[serde(skip)]
+    pub token: i32,
+    pub description: Option<String>,
+    pub created: NaiveDateTime,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Serialize,
+    Deserialize,
+    Insertable,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Associations,
+)]
+#[diesel(table_name = crate::schema::staging_files)]
+#[diesel(belongs_to(StagingSession, foreign_key = session))]
+pub struct StagedFile {
+    pub id: i32,
+    pub session: i32,
+    pub path: String,
+    pub size: i64,
+    pub uploaded: NaiveDateTime,
+    pub md5: String,
+    pub sha1: String,
+    pub sha256: String,
+    pub sha512: String,
+    pub kind: String,
+}
+
+/// The result of checking a staging session before it's promoted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StagingReport {
+    pub valid: bool,
+    pub files: usize,
+    pub problems: Vec<String>,
+}
+
+/// Returned when a staging session can't be promoted, with everything that's wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidStaging(pub StagingReport);
+
+impl Display for InvalidStaging {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid staging session: {}", self.0.problems.join("; "))
+    }
+}
+
+impl std::error::Error for InvalidStaging {}
+
+impl StagedFile {
+    pub async fn get_bytes<S: ObjectStore>(&self, store: &Arc<S>) -> Result<Vec<u8>> {
+        Ok(store
+            .get(&self.md5.clone().into())
+            .await?
+            .bytes()
+            .await?
+            .to_vec())
+    }
+
+    /// The folder this file is in, with a trailing slash (like `files.parent`).
+    pub fn parent(&self) -> &str {
+        match self.path.rfind('/') {
+            Some(idx) => &self.path[..=idx],
+            None => "/",
+        }
+    }
+
+    pub fn get_hash(&self, alg: impl AsRef<str>) -> Result<String> {
+        match alg.as_ref() {
+            "md5" => Ok(self.md5.clone()),
+            "sha1" => Ok(self.sha1.clone()),
+            "sha256" => Ok(self.sha256.clone()),
+            "sha512" => Ok(self.sha512.clone()),
+            it => Err(anyhow!("Unknown hash algorithm: {it}")),
+        }
+    }
+
+    /// The record this file gets in the live tree.
+    pub fn into_file(self, uploader: Option<i32>) -> MavenFileIn {
+        MavenFileIn {
+            path: self.path,
+            size: self.size,
+            md5: self.md5,
+            sha1: self.sha1,
+            sha256: self.sha256,
+            sha512: self.sha512,
+            kind: self.kind,
+            uploader,
+        }
+    }
+}
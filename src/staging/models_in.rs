@@ -0,0 +1,48 @@
+use crate::files::models_in::MavenFileIn;
+use random_string::charsets::ALPHANUMERIC;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::staging_sessions)]
+pub struct StagingSessionIn {
+    pub key: String,
+    pub token: i32,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::staging_files)]
+pub struct StagedFileIn {
+    pub session: i32,
+    pub path: String,
+    pub size: i64,
+    pub md5: String,
+    pub sha1: String,
+    pub sha256: String,
+    pub sha512: String,
+    pub kind: String,
+}
+
+impl StagingSessionIn {
+    pub fn new(token: i32, description: Option<String>) -> Self {
+        Self {
+            key: random_string::generate(24, ALPHANUMERIC),
+            token,
+            description,
+        }
+    }
+}
+
+impl StagedFileIn {
+    pub fn new(session: i32, file: MavenFileIn) -> Self {
+        Self {
+            session,
+            path: file.path,
+            size: file.size,
+            md5: file.md5,
+            sha1: file.sha1,
+            sha256: file.sha256,
+            sha512: file.sha512,
+            kind: file.kind,
+        }
+    }
+}
@@ -1,19 +1,24 @@
 use super::{
     hash::{check_password, hash_token_value},
-    models::{MasterKey, MavenToken, MavenTokenPath, MavenTokenSafe},
-    models_in::{MavenTokenIn, MavenTokenPathIn},
+    models::{MasterKey, MavenToken, MavenTokenCertificate, MavenTokenPath, MavenTokenSafe},
+    models_in::{MasterKeyIn, MavenTokenCertificateIn, MavenTokenIn, MavenTokenPathIn},
     perms::MavenTokenPermissions,
 };
 use crate::{
     cx::RouteContext,
-    schema::{master_keys, token_paths, tokens},
+    err::MavenError,
+    ratelimit::current_client_ip,
+    schema::{master_keys, token_certificates, token_paths, tokens},
+    webhooks::models::{WebhookEvent, WebhookPayload},
 };
-use anyhow::{Result, anyhow};
+use anyhow::Result;
 use diesel::{
-    BelongingToDsl, BoolExpressionMethods, ExpressionMethods, QueryDsl, SelectableHelper, delete,
-    insert_into,
+    BelongingToDsl, BoolExpressionMethods, ExpressionMethods, OptionalExtension, QueryDsl,
+    SelectableHelper, delete, insert_into, pg::Pg, update,
 };
-use diesel_async::RunQueryDsl;
+use diesel_async::{AsyncConnection, RunQueryDsl, scoped_futures::ScopedFutureExt};
+use itertools::Itertools;
+use random_string::charsets::ALPHANUMERIC;
 
 impl RouteContext {
     pub async fn validate_master_key(&self, key: impl AsRef<str>) -> Result<bool> {
@@ -25,6 +30,28 @@ impl RouteContext {
             .is_ok())
     }
 
+    /// Replace every master key with a new one, and return it.
+    pub async fn rotate_master_key(&self, value: Option<String>) -> Result<MasterKey> {
+        let mut conn = self.pool.get().await?;
+
+        conn.transaction::<_, anyhow::Error, _>(|conn| {
+            async move {
+                delete(master_keys::table).execute(conn).await?;
+
+                Ok(insert_into(master_keys::table)
+                    .values(MasterKeyIn {
+                        value: value.unwrap_or_else(|| random_string::generate(32, ALPHANUMERIC)),
+                        is_init: true,
+                    })
+                    .returning(MasterKey::as_returning())
+                    .get_result(conn)
+                    .await?)
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
     pub async fn create_token(
         &self,
         mut token: MavenTokenIn,
@@ -38,12 +65,35 @@ impl RouteContext {
 
         debug!("Inserting into db...");
 
-        Ok(insert_into(tokens::table)
-            .values(token)
-            .returning(MavenToken::as_returning())
-            .get_result(&mut self.pool.get().await?)
-            .await?
-            .safe(if generated { Some(orig_value) } else { None }))
+        let mut conn = self.pool.get().await?;
+
+        let created = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                async move {
+                    let created = insert_into(tokens::table)
+                        .values(token)
+                        .returning(MavenToken::as_returning())
+                        .get_result(conn)
+                        .await?;
+
+                    self.record_webhook_inner(
+                        WebhookPayload {
+                            token: Some(created.name.clone()),
+                            ..WebhookPayload::new(WebhookEvent::TokenCreated)
+                        },
+                        conn,
+                    )
+                    .await?;
+
+                    Ok(created)
+                }
+                .scope_boxed()
+            })
+            .await?;
+
+        self.webhook_notify.notify_one();
+
+        Ok(created.safe(if generated { Some(orig_value) } else { None }))
     }
 
     pub async fn get_token(
@@ -51,19 +101,50 @@ impl RouteContext {
         name: impl AsRef<str>,
         value: impl AsRef<str>,
     ) -> Result<MavenToken> {
+        let name = name.as_ref();
+        let ip = current_client_ip();
+
+        // Checked before hashing anything, so locked out tokens cost next to nothing.
+        self.auth_limiter.check_token(name, ip)?;
+
         let token = tokens::table
-            .filter(tokens::name.eq(name.as_ref()))
+            .filter(tokens::name.eq(name))
             .select(MavenToken::as_select())
             .get_result(&mut self.pool.get().await?)
-            .await?;
+            .await
+            .optional()?;
+
+        match token {
+            Some(token) if check_password(value, &token.value) => {
+                self.auth_limiter.token_succeeded(name, ip);
+
+                Ok(token)
+            }
 
-        if check_password(value, &token.value) {
-            Ok(token)
-        } else {
-            Err(anyhow!("Invalid token!"))
+            _ => {
+                self.auth_limiter.token_failed(name, ip);
+
+                Err(MavenError::Unauthorized("Invalid token!".into()).into())
+            }
         }
     }
 
+    /// Get the token a TLS client certificate belongs to. The certificate has already
+    /// been verified by the time this is called, so there's nothing to hash.
+    pub async fn get_token_by_certificate(
+        &self,
+        fingerprint: impl AsRef<str>,
+    ) -> Result<MavenToken> {
+        tokens::table
+            .inner_join(token_certificates::table)
+            .filter(token_certificates::fingerprint.eq(fingerprint.as_ref()))
+            .select(MavenToken::as_select())
+            .first(&mut self.pool.get().await?)
+            .await
+            .optional()?
+            .ok_or(MavenError::Unauthorized("Unknown client certificate!".into()).into())
+    }
+
     pub async fn delete_token(&self, name: impl AsRef<str>) -> Result<()> {
         delete(tokens::table)
             .filter(tokens::name.eq(name.as_ref()))
@@ -89,19 +170,33 @@ impl RouteContext {
     }
 
     pub async fn get_token_writable_paths(&self, token: &MavenToken) -> Result<Vec<String>> {
-        self.get_token_paths(token).await.map(|items| {
-            items
-                .into_iter()
-                .filter_map(
-                    |it| match MavenTokenPermissions::from_value(it.permission) {
-                        Ok(MavenTokenPermissions::Read) => None,
-                        Ok(MavenTokenPermissions::Write) => Some(it.path),
-                        Ok(MavenTokenPermissions::ReadWrite) => Some(it.path),
-                        Err(_) => None,
-                    },
-                )
-                .collect()
-        })
+        self.get_token_writable_paths_inner(token.id, &mut self.pool.get().await?)
+            .await
+    }
+
+    /// Like [`RouteContext::get_token_writable_paths`], but the paths are locked, so in
+    /// a transaction they can't be changed or taken away until it's done.
+    pub async fn get_token_writable_paths_inner(
+        &self,
+        token: i32,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<Vec<String>> {
+        Ok(token_paths::table
+            .filter(token_paths::token.eq(token))
+            .select(MavenTokenPath::as_select())
+            .for_share()
+            .load(conn)
+            .await?
+            .into_iter()
+            .filter_map(
+                |it| match MavenTokenPermissions::from_value(it.permission) {
+                    Ok(MavenTokenPermissions::Read) => None,
+                    Ok(MavenTokenPermissions::Write) => Some(it.path),
+                    Ok(MavenTokenPermissions::ReadWrite) => Some(it.path),
+                    Err(_) => None,
+                },
+            )
+            .collect())
     }
 
     pub async fn get_token_readable_paths(&self, token: &MavenToken) -> Result<Vec<String>> {
@@ -155,6 +250,54 @@ impl RouteContext {
         Ok(())
     }
 
+    pub async fn add_token_certificate(
+        &self,
+        name: impl AsRef<str>,
+        fingerprint: impl AsRef<str>,
+    ) -> Result<MavenTokenCertificate> {
+        let token = self.get_token_by_name(name).await?;
+
+        Ok(insert_into(token_certificates::table)
+            .values(MavenTokenCertificateIn {
+                token: token.id,
+                fingerprint: fingerprint.as_ref().to_lowercase(),
+            })
+            .returning(MavenTokenCertificate::as_returning())
+            .get_result(&mut self.pool.get().await?)
+            .await?)
+    }
+
+    pub async fn remove_token_certificate(
+        &self,
+        name: impl AsRef<str>,
+        fingerprint: impl AsRef<str>,
+    ) -> Result<()> {
+        let token = self.get_token_by_name(name).await?;
+
+        delete(token_certificates::table)
+            .filter(
+                token_certificates::token
+                    .eq(token.id)
+                    .and(token_certificates::fingerprint.eq(fingerprint.as_ref().to_lowercase())),
+            )
+            .execute(&mut self.pool.get().await?)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_token_certificates_by_name(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<Vec<MavenTokenCertificate>> {
+        let token = self.get_token_by_name(name).await?;
+
+        Ok(MavenTokenCertificate::belonging_to(&token)
+            .select(MavenTokenCertificate::as_select())
+            .load(&mut self.pool.get().await?)
+            .await?)
+    }
+
     pub async fn get_token_paths_by_name(&self, name: impl AsRef<str>) -> Result<Vec<MavenTokenPath>> {
         let token = self.get_token_by_name(name).await?;
 
@@ -164,4 +307,63 @@ impl RouteContext {
             .load(&mut self.pool.get().await?)
             .await?)
     }
+
+    /// Get every token along with its paths, oldest first.
+    pub async fn get_tokens_with_paths(&self) -> Result<Vec<(MavenToken, Vec<MavenTokenPath>)>> {
+        Ok(tokens::table
+            .left_join(token_paths::table)
+            .select((
+                MavenToken::as_select(),
+                Option::<MavenTokenPath>::as_select(),
+            ))
+            .load::<(MavenToken, Option<MavenTokenPath>)>(&mut self.pool.get().await?)
+            .await?
+            .into_iter()
+            .into_group_map()
+            .into_iter()
+            .map(|(token, paths)| (token, paths.into_iter().flatten().collect_vec()))
+            .sorted_by_cached_key(|it| it.0.created)
+            .collect_vec())
+    }
+
+    /// Replace a token's value. Its paths and certificates stay as they are.
+    pub async fn set_token_value(&self, token: &MavenToken, value: impl AsRef<str>) -> Result<()> {
+        update(tokens::table)
+            .filter(tokens::id.eq(token.id))
+            .set(tokens::value.eq(hash_token_value(value.as_ref())?))
+            .execute(&mut self.pool.get().await?)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Give a token a path, or change its permission there if it already has it.
+    pub async fn set_token_path(
+        &self,
+        token: &MavenToken,
+        path: impl AsRef<str>,
+        permissions: MavenTokenPermissions,
+    ) -> Result<()> {
+        let path = path.as_ref();
+        let mut conn = self.pool.get().await?;
+
+        let updated = update(token_paths::table)
+            .filter(
+                token_paths::token
+                    .eq(token.id)
+                    .and(token_paths::path.eq(path)),
+            )
+            .set(token_paths::permission.eq(permissions.value()))
+            .execute(&mut conn)
+            .await?;
+
+        if updated == 0 {
+            insert_into(token_paths::table)
+                .values(MavenTokenPathIn::new(token.id, path, permissions.value()))
+                .execute(&mut conn)
+                .await?;
+        }
+
+        Ok(())
+    }
 }
@@ -61,6 +61,29 @@ pub struct MavenTokenPath {
     pub permission: i16,
 }
 
+/// A TLS client certificate that authenticates as a token.
+#[derive(
+    Debug,
+    Clone,
+    Serialize,
+    Deserialize,
+    Insertable,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Associations,
+)]
+#[diesel(table_name = crate::schema::token_certificates)]
+#[diesel(belongs_to(MavenToken, foreign_key = token))]
+pub struct MavenTokenCertificate {
+    pub id: i32,
+    pub token: i32,
+
+    /// The hex SHA-256 of the certificate (in DER form).
+    pub fingerprint: String,
+    pub added: NaiveDateTime,
+}
+
 impl MavenTokenPath {
     pub fn can_read(&self) -> bool {
         self.permission == 0 || self.is_read_write()
@@ -17,6 +17,13 @@ pub struct MavenTokenPathIn {
     pub permission: i16,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::token_certificates)]
+pub struct MavenTokenCertificateIn {
+    pub token: i32,
+    pub fingerprint: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
 #[diesel(table_name = crate::schema::master_keys)]
 pub struct MasterKeyIn {
@@ -1,10 +1,16 @@
 use anyhow::{Result, anyhow};
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[repr(i16)]
 pub enum MavenTokenPermissions {
+    #[serde(alias = "read")]
     Read = 0,
+
+    #[serde(alias = "write")]
     Write = 1,
+
+    #[serde(alias = "read_write", alias = "read-write")]
     ReadWrite = 2,
 }
 
@@ -33,6 +39,19 @@ impl Into<i16> for MavenTokenPermissions {
     }
 }
 
+impl FromStr for MavenTokenPermissions {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "read" => Ok(Self::Read),
+            "write" => Ok(Self::Write),
+            "read_write" | "readwrite" => Ok(Self::ReadWrite),
+            _ => Err(anyhow!("Unknown permission: {s}")),
+        }
+    }
+}
+
 impl TryFrom<i16> for MavenTokenPermissions {
     type Error = anyhow::Error;
 
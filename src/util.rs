@@ -1,3 +1,4 @@
+use chrono::TimeDelta;
 use std::ops::Deref;
 
 #[repr(transparent)]
@@ -19,3 +20,18 @@ impl<T> Deref for Synced<T> {
 
 unsafe impl<T> Send for Synced<T> {}
 unsafe impl<T> Sync for Synced<T> {}
+
+/// Escape a string so it can be used as a literal prefix in a `LIKE` pattern.
+pub fn escape_like(value: impl AsRef<str>) -> String {
+    value
+        .as_ref()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// How long to wait before trying something again, after it has already failed
+/// `attempts` times. This starts at 5 seconds and doubles up to an hour.
+pub fn retry_backoff(attempts: i32) -> TimeDelta {
+    TimeDelta::seconds((5i64 << attempts.clamp(0, 20)).min(60 * 60))
+}
@@ -0,0 +1,358 @@
+use super::models::{
+    Webhook, WebhookDelivery, WebhookDeliveryIn, WebhookEvent, WebhookIn, WebhookInfo,
+    WebhookPayload,
+};
+use crate::{
+    cx::RouteContext,
+    err::MavenError,
+    schema::{webhook_deliveries, webhooks},
+    util::retry_backoff,
+};
+use anyhow::{Result, anyhow};
+use chrono::{TimeDelta, Utc};
+use diesel::{
+    BelongingToDsl, ExpressionMethods, QueryDsl, SelectableHelper, delete, insert_into, pg::Pg,
+    update,
+};
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use hmac::{Hmac, Mac};
+use itertools::Itertools;
+use random_string::charsets::ALPHANUMERIC;
+use reqwest::{Client, Url, header::CONTENT_TYPE};
+use sha2::Sha256;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+pub const EVENT_HEADER: &str = "X-Maven-Event";
+pub const DELIVERY_HEADER: &str = "X-Maven-Delivery";
+
+/// `sha256=` and the hex HMAC of the body, keyed with the webhook's secret.
+pub const SIGNATURE_HEADER: &str = "X-Maven-Signature";
+
+/// How many deliveries to send between checking the queue again.
+const BATCH_SIZE: i64 = 50;
+
+/// Deliveries that fail this many times are given up on.
+const MAX_ATTEMPTS: i32 = 10;
+
+/// How long a webhook gets to answer.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long to wait between checks when nothing wakes the worker up.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How many deliveries to show for each webhook.
+const HISTORY_SIZE: usize = 20;
+
+/// How long finished deliveries are kept around for.
+const HISTORY_DAYS: i64 = 30;
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_DELIVERED: &str = "delivered";
+pub const STATUS_FAILED: &str = "failed";
+
+/// Sign a webhook body, the same way receivers are expected to check it.
+pub fn sign_payload(secret: impl AsRef<[u8]>, payload: impl AsRef<[u8]>) -> Result<String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_ref())?;
+
+    mac.update(payload.as_ref());
+
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+impl RouteContext {
+    pub async fn create_webhook(
+        &self,
+        url: impl AsRef<str>,
+        prefix: impl AsRef<str>,
+        events: Vec<WebhookEvent>,
+    ) -> Result<Webhook> {
+        let url = url.as_ref();
+
+        if !url
+            .parse::<Url>()
+            .is_ok_and(|it| ["http", "https"].contains(&it.scheme()))
+        {
+            return Err(MavenError::BadRequest(format!("Not an HTTP(S) URL: {url}")).into());
+        }
+
+        if events.is_empty() {
+            return Err(MavenError::BadRequest("Webhooks need at least one event".into()).into());
+        }
+
+        Ok(insert_into(webhooks::table)
+            .values(WebhookIn {
+                url: url.into(),
+                secret: random_string::generate(32, ALPHANUMERIC),
+                prefix: format!("/{}", prefix.as_ref().trim_start_matches('/')),
+                events: events
+                    .into_iter()
+                    .unique()
+                    .map(|it| it.as_str().into())
+                    .collect(),
+            })
+            .returning(Webhook::as_returning())
+            .get_result(&mut self.pool.get().await?)
+            .await?)
+    }
+
+    pub async fn delete_webhook(&self, id: i32) -> Result<()> {
+        let deleted = delete(webhooks::table)
+            .filter(webhooks::id.eq(id))
+            .execute(&mut self.pool.get().await?)
+            .await?;
+
+        if deleted == 0 {
+            return Err(MavenError::not_found().into());
+        }
+
+        Ok(())
+    }
+
+    /// Every webhook, with its most recent deliveries.
+    pub async fn get_webhooks(&self) -> Result<Vec<WebhookInfo>> {
+        let mut conn = self.pool.get().await?;
+
+        let hooks = webhooks::table
+            .select(Webhook::as_select())
+            .order_by(webhooks::id.asc())
+            .load(&mut conn)
+            .await?;
+
+        let deliveries = WebhookDelivery::belonging_to(&hooks)
+            .select(WebhookDelivery::as_select())
+            .order_by(webhook_deliveries::id.desc())
+            .load(&mut conn)
+            .await?
+            .into_iter()
+            .into_group_map_by(|it| it.webhook);
+
+        Ok(hooks
+            .into_iter()
+            .map(|webhook| WebhookInfo {
+                deliveries: deliveries
+                    .get(&webhook.id)
+                    .map(|it| it.iter().take(HISTORY_SIZE).cloned().collect())
+                    .unwrap_or_default(),
+                webhook,
+            })
+            .collect())
+    }
+
+    /// Queue deliveries for every webhook that wants to hear about something. This
+    /// should happen in the same transaction as the change itself, so nothing gets lost.
+    pub async fn record_webhook_inner(
+        &self,
+        payload: WebhookPayload,
+        conn: &mut impl AsyncConnection<Backend = Pg>,
+    ) -> Result<()> {
+        let deliveries = webhooks::table
+            .select(Webhook::as_select())
+            .load(conn)
+            .await?
+            .into_iter()
+            .filter(|it| it.wants(payload.event, payload.path.as_deref()))
+            .map(|it| {
+                Ok(WebhookDeliveryIn {
+                    webhook: it.id,
+                    event: payload.event.as_str().into(),
+                    payload: serde_json::to_string(&payload)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if !deliveries.is_empty() {
+            insert_into(webhook_deliveries::table)
+                .values(deliveries)
+                .execute(conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Queue a ping for a webhook, to check it's set up right.
+    pub async fn test_webhook(&self, id: i32) -> Result<WebhookDelivery> {
+        let mut conn = self.pool.get().await?;
+
+        let webhook = webhooks::table
+            .filter(webhooks::id.eq(id))
+            .select(Webhook::as_select())
+            .get_result(&mut conn)
+            .await
+            .map_err(|_| MavenError::not_found())?;
+
+        let delivery = insert_into(webhook_deliveries::table)
+            .values(WebhookDeliveryIn {
+                webhook: webhook.id,
+                event: WebhookEvent::Ping.as_str().into(),
+                payload: serde_json::to_string(&WebhookPayload::new(WebhookEvent::Ping))?,
+            })
+            .returning(WebhookDelivery::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        self.webhook_notify.notify_one();
+
+        Ok(delivery)
+    }
+
+    /// Send whatever is due in the queue. Returns how many deliveries were attempted,
+    /// so a full batch means there's probably more waiting.
+    pub async fn deliver_webhooks(&self, client: &Client) -> Result<usize> {
+        let mut conn = self.pool.get().await?;
+
+        let due = webhook_deliveries::table
+            .inner_join(webhooks::table)
+            .filter(webhook_deliveries::status.eq(STATUS_PENDING))
+            .filter(webhook_deliveries::next_attempt.le(Utc::now().naive_utc()))
+            .select((WebhookDelivery::as_select(), Webhook::as_select()))
+            .order_by(webhook_deliveries::id.asc())
+            .limit(BATCH_SIZE)
+            .load::<(WebhookDelivery, Webhook)>(&mut conn)
+            .await?;
+
+        for (delivery, webhook) in &due {
+            let (response_status, res) = send(client, webhook, delivery).await;
+            let now = Utc::now().naive_utc();
+
+            match res {
+                Ok(()) => {
+                    update(webhook_deliveries::table)
+                        .filter(webhook_deliveries::id.eq(delivery.id))
+                        .set((
+                            webhook_deliveries::status.eq(STATUS_DELIVERED),
+                            webhook_deliveries::attempts.eq(delivery.attempts + 1),
+                            webhook_deliveries::finished.eq(now),
+                            webhook_deliveries::response_status.eq(response_status),
+                            webhook_deliveries::last_error.eq(None::<String>),
+                        ))
+                        .execute(&mut conn)
+                        .await?;
+                }
+
+                Err(err) => {
+                    let attempts = delivery.attempts + 1;
+                    let gave_up = attempts >= MAX_ATTEMPTS;
+
+                    warn!(
+                        "Could not deliver {} to {} (attempt {attempts}): {err}",
+                        delivery.event, webhook.url
+                    );
+
+                    update(webhook_deliveries::table)
+                        .filter(webhook_deliveries::id.eq(delivery.id))
+                        .set((
+                            webhook_deliveries::status.eq(if gave_up {
+                                STATUS_FAILED
+                            } else {
+                                STATUS_PENDING
+                            }),
+                            webhook_deliveries::attempts.eq(attempts),
+                            webhook_deliveries::next_attempt
+                                .eq(now + retry_backoff(delivery.attempts)),
+                            webhook_deliveries::finished.eq(gave_up.then_some(now)),
+                            webhook_deliveries::response_status.eq(response_status),
+                            webhook_deliveries::last_error.eq(err.to_string()),
+                        ))
+                        .execute(&mut conn)
+                        .await?;
+                }
+            }
+        }
+
+        Ok(due.len())
+    }
+
+    /// Forget about deliveries that finished a while ago.
+    pub async fn prune_webhook_deliveries(&self) -> Result<usize> {
+        Ok(delete(webhook_deliveries::table)
+            .filter(webhook_deliveries::status.ne(STATUS_PENDING))
+            .filter(
+                webhook_deliveries::finished
+                    .lt(Utc::now().naive_utc() - TimeDelta::days(HISTORY_DAYS)),
+            )
+            .execute(&mut self.pool.get().await?)
+            .await?)
+    }
+}
+
+/// Send a delivery once. Returns the status it got back, if it got that far.
+async fn send(
+    client: &Client,
+    webhook: &Webhook,
+    delivery: &WebhookDelivery,
+) -> (Option<i32>, Result<()>) {
+    let signature = match sign_payload(&webhook.secret, &delivery.payload) {
+        Ok(it) => it,
+        Err(err) => return (None, Err(err)),
+    };
+
+    let res = client
+        .post(&webhook.url)
+        .header(CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, &delivery.event)
+        .header(DELIVERY_HEADER, delivery.id.to_string())
+        .header(SIGNATURE_HEADER, format!("sha256={signature}"))
+        .body(delivery.payload.clone())
+        .send()
+        .await;
+
+    match res {
+        Ok(res) if res.status().is_success() => (Some(res.status().as_u16() as i32), Ok(())),
+
+        Ok(res) => (
+            Some(res.status().as_u16() as i32),
+            Err(anyhow!("{} returned {}", webhook.url, res.status())),
+        ),
+
+        Err(err) => (None, Err(err.into())),
+    }
+}
+
+/// Deliver webhooks as they're queued, for as long as the server runs.
+pub async fn webhook_thread(cx: Arc<RouteContext>) {
+    let client = match Client::builder()
+        .user_agent(concat!("mvn/", env!("CARGO_PKG_VERSION")))
+        .timeout(TIMEOUT)
+        .build()
+    {
+        Ok(it) => it,
+
+        Err(err) => {
+            warn!("Could not start delivering webhooks: {err}");
+            return;
+        }
+    };
+
+    let mut last_pruned = None::<Instant>;
+
+    loop {
+        if cx.drain.is_draining() {
+            return;
+        }
+
+        if last_pruned.is_none_or(|it| it.elapsed() > Duration::from_secs(60 * 60)) {
+            match cx.prune_webhook_deliveries().await {
+                Ok(0) => {}
+                Ok(count) => info!("Pruned {count} old webhook deliveries!"),
+                Err(err) => warn!("Could not prune webhook deliveries: {err}"),
+            }
+
+            last_pruned = Some(Instant::now());
+        }
+
+        match cx.deliver_webhooks(&client).await {
+            Ok(count) if count as i64 >= BATCH_SIZE => continue,
+            Ok(_) => {}
+            Err(err) => warn!("Could not deliver webhooks: {err}"),
+        }
+
+        tokio::select! {
+            _ = cx.webhook_notify.notified() => {}
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+        }
+    }
+}
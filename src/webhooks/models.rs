@@ -0,0 +1,207 @@
+use crate::{files::models::MavenFile, maven::pom::Pom};
+use anyhow::{Result, anyhow};
+use chrono::{NaiveDateTime, Utc};
+use std::{fmt::Display, str::FromStr};
+
+/// Something a webhook can be told about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// Any file was uploaded (or replaced).
+    ArtifactUploaded,
+
+    /// A POM was uploaded, so there's a new version of something.
+    VersionPublished,
+    FileDeleted,
+    TokenCreated,
+
+    /// Sent from the dashboard (or the API) to check a webhook works. Every webhook
+    /// gets these when asked, whatever it's subscribed to.
+    Ping,
+}
+
+impl WebhookEvent {
+    pub const ALL: &[Self] = &[
+        Self::ArtifactUploaded,
+        Self::VersionPublished,
+        Self::FileDeleted,
+        Self::TokenCreated,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ArtifactUploaded => "artifact_uploaded",
+            Self::VersionPublished => "version_published",
+            Self::FileDeleted => "file_deleted",
+            Self::TokenCreated => "token_created",
+            Self::Ping => "ping",
+        }
+    }
+}
+
+impl Display for WebhookEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for WebhookEvent {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .chain([&Self::Ping])
+            .find(|it| it.as_str() == s)
+            .copied()
+            .ok_or_else(|| anyhow!("Unknown webhook event: {s}"))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Identifiable, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::webhooks)]
+pub struct Webhook {
+    pub id: i32,
+    pub url: String,
+
+    /// What deliveries are signed with.
+    pub secret: String,
+
+    /// Only changes to paths under this trigger the webhook. Token events aren't tied to
+    /// a path, so they ignore this.
+    pub prefix: String,
+    pub events: Vec<String>,
+    pub created: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::webhooks)]
+pub struct WebhookIn {
+    pub url: String,
+    pub secret: String,
+    pub prefix: String,
+    pub events: Vec<String>,
+}
+
+impl Webhook {
+    /// Whether this webhook wants to hear about an event on a path.
+    pub fn wants(&self, event: WebhookEvent, path: Option<&str>) -> bool {
+        self.events.iter().any(|it| it == event.as_str())
+            && path.is_none_or(|it| it.starts_with(&self.prefix))
+    }
+}
+
+/// One attempt (or series of attempts) at telling a webhook about something. These
+/// double as the delivery queue and the delivery history.
+#[derive(
+    Debug, Clone, Serialize, Deserialize, Identifiable, Queryable, Selectable, Associations,
+)]
+#[diesel(table_name = crate::schema::webhook_deliveries)]
+#[diesel(belongs_to(Webhook, foreign_key = webhook))]
+pub struct WebhookDelivery {
+    pub id: i32,
+    pub webhook: i32,
+    pub event: String,
+    pub payload: String,
+
+    /// `pending`, `delivered` or `failed` (after running out of attempts).
+    pub status: String,
+    pub created: NaiveDateTime,
+    pub attempts: i32,
+    pub next_attempt: NaiveDateTime,
+    pub finished: Option<NaiveDateTime>,
+
+    /// The HTTP status the last attempt got back, if it got that far.
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::webhook_deliveries)]
+pub struct WebhookDeliveryIn {
+    pub webhook: i32,
+    pub event: String,
+    pub payload: String,
+}
+
+/// The JSON body webhooks get sent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    pub event: WebhookEvent,
+    pub timestamp: NaiveDateTime,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<WebhookFile>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<WebhookVersion>,
+
+    /// The name of the token, for token events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl WebhookPayload {
+    pub fn new(event: WebhookEvent) -> Self {
+        Self {
+            event,
+            timestamp: Utc::now().naive_utc(),
+            path: None,
+            file: None,
+            version: None,
+            token: None,
+        }
+    }
+
+    pub fn for_file(event: WebhookEvent, file: &MavenFile) -> Self {
+        Self {
+            path: Some(file.path.clone()),
+            file: Some(WebhookFile {
+                size: file.size,
+                md5: file.md5.clone(),
+                sha1: file.sha1.clone(),
+                sha256: file.sha256.clone(),
+                sha512: file.sha512.clone(),
+            }),
+            ..Self::new(event)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookFile {
+    pub size: i64,
+    pub md5: String,
+    pub sha1: String,
+    pub sha256: String,
+    pub sha512: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookVersion {
+    pub group_id: String,
+    pub artifact_id: String,
+    pub version: String,
+}
+
+impl WebhookVersion {
+    /// The coordinates a POM is for, if it says (or inherits) all of them.
+    pub fn from_pom(pom: &Pom) -> Option<Self> {
+        Some(Self {
+            group_id: pom.group_id()?,
+            artifact_id: pom.artifact_id.clone(),
+            version: pom.version()?,
+        })
+    }
+}
+
+/// A webhook, with its latest deliveries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookInfo {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    pub deliveries: Vec<WebhookDelivery>,
+}